@@ -0,0 +1,210 @@
+use crate::scene::{is_image_file, natural_cmp};
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+use std::sync::RwLock;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+/// Largest archive entry read into memory; bigger ones are rejected rather
+/// than trusting a size from a possibly corrupt or crafted archive
+const MAX_ENTRY_BYTES: u64 = 512 << 20;
+
+/// Password for the encrypted entries of the loaded collection's archives
+///
+/// Held in memory only, replaced on every collection load and never written
+/// to disk.
+static PASSWORD: RwLock<Option<String>> = RwLock::new(None);
+
+/// Separator between an archive path and an entry name in a page image path,
+/// e.g. `/books/vol1.cbz!/001.png`
+pub const ENTRY_SEPARATOR: &str = "!/";
+
+/// Check whether a path has the extension of an archive opened as a scene
+/// (`.cbz` or `.zip`)
+pub fn is_archive_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "cbz" | "zip"))
+}
+
+/// Page image path of an entry inside an archive
+pub fn entry_path(archive: &Path, entry: &str) -> String {
+    format!("{}{}{}", archive.to_string_lossy(), ENTRY_SEPARATOR, entry)
+}
+
+/// Split a page image path into the archive and the entry name, if it points
+/// inside an archive
+pub fn split_entry_path(path: &str) -> Option<(&Path, &str)> {
+    path.match_indices(ENTRY_SEPARATOR)
+        .map(|(i, _)| (Path::new(&path[..i]), &path[i + ENTRY_SEPARATOR.len()..]))
+        .find(|(archive, _)| is_archive_path(archive))
+}
+
+/// An encrypted entry was read without a password or with the wrong one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WrongPassword;
+
+impl fmt::Display for WrongPassword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Wrong or missing password for encrypted archive")
+    }
+}
+
+impl std::error::Error for WrongPassword {}
+
+/// Set the password used to read encrypted archive entries, or clear it
+pub fn set_password(password: Option<String>) {
+    *PASSWORD.write().unwrap() = password;
+}
+
+fn open(archive: &Path) -> Result<ZipArchive<BufReader<File>>> {
+    let file = File::open(archive)
+        .with_context(|| format!("Failed to open archive: {:?}", archive))?;
+    ZipArchive::new(BufReader::new(file))
+        .with_context(|| format!("Failed to read archive: {:?}", archive))
+}
+
+/// Names of the image entries of an archive, in natural order
+///
+/// Directories and macOS resource fork entries are left out.
+fn image_entries<R: Read + Seek>(zip: &ZipArchive<R>) -> Vec<String> {
+    let mut names: Vec<String> = zip.file_names()
+        .filter(|name| !name.ends_with('/') && !name.starts_with("__MACOSX/"))
+        .filter(|name| is_image_file(Path::new(name)))
+        .map(str::to_string)
+        .collect();
+    names.sort_by(|a, b| natural_cmp(a, b));
+    names
+}
+
+/// List the image entries of an archive file, in natural order
+pub fn list_images(archive: &Path) -> Result<Vec<String>> {
+    Ok(image_entries(&open(archive)?))
+}
+
+/// Read the bytes of one entry of an archive, decrypting it with the
+/// password set by set_password if it is encrypted
+pub fn read_entry(archive: &Path, entry: &str) -> Result<Vec<u8>> {
+    let password = PASSWORD.read().unwrap().clone();
+    read_entry_from(&mut open(archive)?, entry, password.as_deref())
+        .with_context(|| format!("Failed to read entry {:?} of archive {:?}", entry, archive))
+}
+
+/// Check that `password` opens an entry of an archive, before it is set for
+/// the collection
+///
+/// Fails with WrongPassword when the entry is encrypted and `password` is
+/// missing or wrong; unencrypted entries need none.
+pub fn check_password(archive: &Path, entry: &str, password: Option<&str>) -> Result<()> {
+    read_entry_from(&mut open(archive)?, entry, password)
+        .with_context(|| format!("Failed to read entry {:?} of archive {:?}", entry, archive))?;
+    Ok(())
+}
+
+fn read_entry_from<R: Read + Seek>(zip: &mut ZipArchive<R>, entry: &str, password: Option<&str>) -> Result<Vec<u8>> {
+    let file = match password {
+        Some(password) => zip.by_name_decrypt(entry, password.as_bytes()),
+        None => zip.by_name(entry),
+    };
+    let file = match file {
+        Ok(file) => file,
+        Err(ZipError::InvalidPassword) => return Err(WrongPassword.into()),
+        Err(ZipError::UnsupportedArchive(message)) if message == ZipError::PASSWORD_REQUIRED => {
+            return Err(WrongPassword.into());
+        }
+        Err(e) => return Err(e).with_context(|| format!("No entry {:?}", entry)),
+    };
+    read_limited(file, MAX_ENTRY_BYTES)
+}
+
+/// Read an entry, failing once it goes past `limit` bytes whatever its
+/// header claims
+fn read_limited<R: Read>(file: R, limit: u64) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    file.take(limit + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > limit {
+        anyhow::bail!("Entry is larger than {} bytes", limit);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::Scene;
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+        image::RgbImage::new(width, height)
+            .write_to(&mut Cursor::new(&mut data), image::ImageFormat::Png)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn test_archive_scene() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, data) in [
+            ("page10.png", png(4, 2)),
+            ("notes.txt", b"not an image".to_vec()),
+            ("page2.png", png(8, 6)),
+            ("__MACOSX/._page2.png", b"resource fork".to_vec()),
+        ] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(&data).unwrap();
+        }
+        writer.add_directory("extras/", options).unwrap();
+        let data = writer.finish().unwrap().into_inner();
+
+        let zip = ZipArchive::new(Cursor::new(data.clone())).unwrap();
+        assert_eq!(image_entries(&zip), ["page2.png", "page10.png"]);
+
+        let path = std::env::temp_dir().join(format!("fastviewer-archive-{}.cbz", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+        let scene = Scene::open(&path);
+        let page = crate::image_loader::load_image(entry_path(&path, "page10.png"));
+        std::fs::remove_file(&path).unwrap();
+
+        let scene = scene.unwrap();
+        assert_eq!(scene.page_count(), 2);
+        assert_eq!(scene.get_page_image(0), Some(entry_path(&path, "page2.png").as_str()));
+        assert_eq!((scene.metadata.image_size.width, scene.metadata.image_size.height), (8, 6));
+        assert_eq!(image::GenericImageView::dimensions(&page.unwrap()), (4, 2));
+        assert_eq!(
+            split_entry_path(&entry_path(&path, "ch1/p1.png")),
+            Some((path.as_path(), "ch1/p1.png"))
+        );
+        assert_eq!(split_entry_path("/books/notes!/p1.png"), None);
+    }
+
+    #[test]
+    fn test_encrypted_entry() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let encrypted = SimpleFileOptions::default().with_aes_encryption(zip::AesMode::Aes256, "secret");
+        writer.start_file("page1.png", encrypted).unwrap();
+        writer.write_all(&png(4, 2)).unwrap();
+        writer.start_file("page2.png", SimpleFileOptions::default()).unwrap();
+        writer.write_all(&png(2, 2)).unwrap();
+        let mut zip = ZipArchive::new(writer.finish().unwrap()).unwrap();
+
+        assert_eq!(read_entry_from(&mut zip, "page1.png", Some("secret")).unwrap(), png(4, 2));
+        for password in [None, Some("wrong")] {
+            let error = read_entry_from(&mut zip, "page1.png", password).unwrap_err();
+            assert_eq!(error.downcast_ref::<WrongPassword>(), Some(&WrongPassword));
+        }
+        assert_eq!(read_entry_from(&mut zip, "page2.png", Some("secret")).unwrap(), png(2, 2));
+        assert!(read_entry_from(&mut zip, "missing.png", None).unwrap_err().downcast_ref::<WrongPassword>().is_none());
+    }
+
+    #[test]
+    fn test_read_limited() {
+        assert_eq!(read_limited(&[1u8, 2, 3][..], 3).unwrap(), [1, 2, 3]);
+        assert!(read_limited(&[1u8, 2, 3, 4][..], 3).is_err());
+    }
+}
@@ -1,539 +1,5264 @@
-use crate::image_loader::{load_image_cached, image_to_base64_jpeg, ImageCache, EncodedImageCache};
-use crate::scene::{Scene, SceneCollection};
-use anyhow::Result;
+use crate::image_loader::{
+    load_image_with_retry, load_image_cached_with_size, load_image_cached_timed, extract_exif_thumbnail, encode_image, encode_keeping_alpha, is_png_data_url, image_to_base64_jpeg, image_to_jpeg, image_to_base64_png, fit_dimensions,
+    resize_to_fit, detect_format, ImageCache, EncodedImageCache, EncodeFormat, JpegOptions, MAX_DIMENSION, MAX_TARGET_SIZE, PREVIEW_DIMENSION,
+};
+use crate::archive;
+use crate::decode_pool;
+use crate::disk_cache::{DiskThumbnailCache, DISK_THUMBNAIL_DIR};
+use crate::error::AppError;
+use crate::image_loader;
+use crate::image_ops::{
+    average_color, blurhash, compose_spread, diff_images, group_similar, pack_sprite_sheet, ocr_binarize, perceptual_hash, rotate, sprite_rows, trim_borders,
+};
+use crate::reading::{PageSet, READ_PAGES_FILE, READ_PAGE_IDS_FILE};
+use crate::recent::{self, RecentItem};
+use crate::scene::{computed_spreads, DEFAULT_COLLECTION_SEARCH_DEPTH, ImageSize, Scene, SceneCollection, SceneCollectionConfig, SceneSummary, SpreadMode};
+use crate::share_link::ShareLink;
+use crate::storage;
+use anyhow::{Context, Result};
+use image::{DynamicImage, GenericImageView};
+use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, Mutex};  // PathBufを削除
-use tauri::State;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::task::{JoinError, JoinSet};
+
+/// File in the app data directory holding per-scene rotations
+const ROTATIONS_FILE: &str = "scene_rotations.json";
+
+/// Number of newly found scene files between scene-count-updated events
+const DISCOVERY_BATCH_SIZE: usize = 64;
+
+/// JPEG quality used for native resolution images
+const NATIVE_JPEG_QUALITY: u8 = 95;
+
+/// Native images above this many pixels get a warning in ImageData
+const NATIVE_WARN_PIXELS: u64 = 50_000_000;
+
+/// Largest width or height allowed for a thumbnail sprite sheet
+const MAX_SPRITE_DIMENSION: u32 = 16384;
+
+/// Number of scene covers decoded at the same time by warm_scene_covers
+const COVER_WARM_CONCURRENCY: usize = 4;
+
+/// Number of scene covers decoded at the same time by get_collection_montage
+const MONTAGE_CONCURRENCY: usize = 4;
+
+/// Number of thumbnails decoded at the same time by get_table_of_contents
+const TOC_CONCURRENCY: usize = 4;
+
+/// Cover size used when a scene's metadata has no usable thumbnail size
+const DEFAULT_COVER_SIZE: u32 = 320;
+
+/// Default wait after the last navigation before OnIdle preloading starts
+const DEFAULT_PRELOAD_IDLE_DELAY: Duration = Duration::from_millis(500);
+
+/// Most resolution levels get_image_pyramid returns
+const MAX_PYRAMID_LEVELS: u32 = 8;
+
+/// Pages decoded and written at the same time by export_page_range
+const EXPORT_CONCURRENCY: usize = 4;
+
+/// Pages decoded at the same time by verify_all_decodes
+const VERIFY_CONCURRENCY: usize = 4;
+
+/// Page files stat'ed at the same time by get_scene_file_infos
+const FILE_INFO_CONCURRENCY: usize = 16;
+
+/// Page headers read at the same time by get_scene_dimension_summary
+const DIMENSION_READ_CONCURRENCY: usize = 16;
+
+/// How often the memory watchdog checks the caches against the memory limit
+const MEMORY_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Most pages whose dimensions get_scene_layout_hint reads per scene
+const LAYOUT_SAMPLE_PAGES: usize = 16;
+
+/// Share of pages with one orientation above which a scene counts as that
+/// orientation rather than mixed
+const LAYOUT_MAJORITY: f64 = 0.8;
+
+/// RGBA payload size above which get_image_rgba logs a warning (a 2048x2048 image)
+const RGBA_WARN_BYTES: usize = 16 * 1024 * 1024;
+
+/// Number of recent page moves the preload direction is predicted from
+const NAVIGATION_HISTORY_LEN: usize = 8;
+
+/// Most unparseable scenes next_scene/prev_scene skip over before giving up
+const MAX_SKIPPED_SCENES: usize = 8;
 
 /// Application state shared across commands
 pub struct AppState {
     pub cache: Arc<ImageCache>,
     pub encoded_cache: Arc<EncodedImageCache>,
-    pub current_scene: Arc<Mutex<Option<Scene>>>,
-    pub current_collection: Arc<Mutex<Option<SceneCollection>>>,
-    pub current_scene_index: Arc<Mutex<usize>>,
-    pub current_page_index: Arc<Mutex<usize>>,
-    pub scene_loop_enabled: Arc<Mutex<bool>>,
+    /// Encoded page 0 thumbnails of scenes, keyed by page 0 image path
+    pub cover_cache: Arc<EncodedImageCache>,
+    /// Current collection, scene and position; one lock so commands always
+    /// see them consistent with each other. Read-only commands share it,
+    /// navigation takes it exclusively.
+    pub session: Arc<RwLock<ViewerSession>>,
+    pub last_page_timings: Arc<Mutex<Option<PageTimings>>>,
+    pub pinned_keys: Arc<Mutex<Vec<String>>>,
+    /// Incremented on every collection load so background work for a
+    /// previously loaded collection can tell it is stale
+    pub load_generation: Arc<AtomicU64>,
+    /// Page count per scene index, filled in as scenes are loaded
+    pub scene_page_counts: Arc<Mutex<HashMap<usize, usize>>>,
+    pub jpeg_options: Arc<Mutex<JpegOptions>>,
+    /// Naming patterns for scene files and collection directories
+    pub collection_config: Arc<Mutex<SceneCollectionConfig>>,
+    pub preload_strategy: Arc<Mutex<PreloadStrategy>>,
+    pub preload_idle_delay: Arc<Mutex<Duration>>,
+    /// Number of images preloading decodes at the same time
+    pub preload_concurrency: Arc<Mutex<usize>>,
+    /// Pages preloaded on each side of the current page
+    pub preload_counts: Arc<Mutex<PreloadCounts>>,
+    /// Skip preloading while set, e.g. during an export
+    pub preload_paused: Arc<AtomicBool>,
+    /// Recent page moves, for preloading in the direction the reader is going
+    pub navigation_history: Arc<Mutex<NavigationHistory>>,
+    /// Incremented on every preload request so a waiting OnIdle preload can
+    /// tell navigation happened in the meantime
+    pub preload_generation: Arc<AtomicU64>,
+    /// Perceptual hash per image path
+    pub phash_cache: Arc<Mutex<HashMap<String, u64>>>,
+    /// Clockwise rotation in degrees per scene file path, persisted to disk
+    pub scene_rotations: Arc<Mutex<HashMap<String, u32>>>,
+    /// Limits how many commands decode images at the same time
+    pub decode_limit: Arc<Semaphore>,
+    /// Total number of permits of decode_limit
+    pub max_concurrent_decodes: Arc<Mutex<usize>>,
+    /// Return a quick low-res tier from get_image and upgrade it via an event
+    pub progressive_loading: Arc<Mutex<bool>>,
+    /// Average color per page image path
+    pub color_cache: Arc<Mutex<HashMap<String, [u8; 3]>>>,
+    /// BlurHash per `{path}#{x}x{y}` (page image path and component counts)
+    pub blurhash_cache: Arc<Mutex<HashMap<String, String>>>,
+    /// Scenes with page edits not yet written by save_scene, per scene file
+    /// path; loading a scene prefers these over the file
+    pub edited_scenes: Arc<Mutex<HashMap<PathBuf, Scene>>>,
+    /// Page dimension summary per scene file path
+    pub dimension_summaries: Arc<Mutex<HashMap<PathBuf, DimensionSummary>>>,
+    /// Layout hint per scene file path (qualified by rotation)
+    pub layout_hints: Arc<Mutex<HashMap<String, LayoutHint>>>,
+    /// What to show for pages without a thumbnail file
+    pub thumbnail_fallback: Arc<Mutex<ThumbnailFallback>>,
+    /// Fail scene navigation on an unparseable scene instead of skipping it
+    pub strict_scene_loading: Arc<Mutex<bool>>,
+    pub tier_formats: Arc<Mutex<TierFormats>>,
+    /// Read pages without a page ID per scene file path, persisted to disk
+    pub read_pages: Arc<Mutex<HashMap<String, PageSet>>>,
+    /// IDs of read pages per scene file path, persisted to disk
+    pub read_page_ids: Arc<Mutex<HashMap<String, HashSet<String>>>>,
+    /// Mark pages read as get_image returns them
+    pub auto_mark_read: Arc<Mutex<bool>>,
+    /// Image paths whose high tier is being loaded by a background upgrade
+    pub inflight_upgrades: Arc<Mutex<HashSet<String>>>,
+    /// What next_page does at the last page of the last scene without looping
+    pub at_collection_end: Arc<Mutex<AtCollectionEnd>>,
+    /// Wrap around from the last sibling collection to the first (and back)
+    /// in next_collection/prev_collection
+    pub wrap_collections: Arc<Mutex<bool>>,
+    /// On-disk store of generated thumbnails; None while disabled
+    pub disk_thumbnails: Arc<Mutex<Option<DiskThumbnailCache>>>,
+    /// Most memory in bytes the image caches may hold before being trimmed
+    pub memory_limit: Arc<Mutex<Option<usize>>>,
+    /// Pairing of pages into spreads for scenes without authored spreads
+    pub spread_mode: Arc<Mutex<SpreadMode>>,
+    /// Order of the pages of a spread on screen
+    pub reading_direction: Arc<Mutex<ReadingDirection>>,
+    /// Screen size the high and preview tiers are sized for
+    pub display_resolution: Arc<Mutex<DisplayResolution>>,
+    /// Longest side any resized decode may have: caps the high and preview
+    /// tiers and sizes thumbnails and other non-tier decodes (diffs, spreads,
+    /// trimmed pages)
+    pub max_dimension: Arc<Mutex<u32>>,
+    /// Running slideshow task started by start_slideshow
+    pub slideshow: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
 }
 
 impl AppState {
+    /// Load settings persisted in the app data directory
+    pub fn load_persisted(&self, app: &AppHandle) {
+        match storage::load_json(app, ROTATIONS_FILE) {
+            Ok(Some(rotations)) => *self.scene_rotations.lock().unwrap() = rotations,
+            Ok(None) => {}
+            Err(e) => warn!("Failed to load scene rotations: {}", e),
+        }
+        match storage::load_json(app, READ_PAGES_FILE) {
+            Ok(Some(read_pages)) => *self.read_pages.lock().unwrap() = read_pages,
+            Ok(None) => {}
+            Err(e) => warn!("Failed to load read pages: {}", e),
+        }
+        match storage::load_json(app, READ_PAGE_IDS_FILE) {
+            Ok(Some(read_page_ids)) => *self.read_page_ids.lock().unwrap() = read_page_ids,
+            Ok(None) => {}
+            Err(e) => warn!("Failed to load read page IDs: {}", e),
+        }
+    }
+
     pub fn new() -> Self {
+        let max_decodes = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4);
+        info!("Using {} concurrent decodes and preloads", max_decodes);
+
         AppState {
             cache: Arc::new(ImageCache::new(8)), // Cache up to 8 images
             encoded_cache: Arc::new(EncodedImageCache::new(16)), // Cache up to 16 encoded images
-            current_scene: Arc::new(Mutex::new(None)),
-            current_collection: Arc::new(Mutex::new(None)),
-            current_scene_index: Arc::new(Mutex::new(0)),
-            current_page_index: Arc::new(Mutex::new(0)),
-            scene_loop_enabled: Arc::new(Mutex::new(false)), // Default OFF
+            cover_cache: Arc::new(EncodedImageCache::new(512)), // Covers are small, keep plenty
+            session: Arc::new(RwLock::new(ViewerSession::default())),
+            last_page_timings: Arc::new(Mutex::new(None)),
+            pinned_keys: Arc::new(Mutex::new(Vec::new())),
+            load_generation: Arc::new(AtomicU64::new(0)),
+            scene_page_counts: Arc::new(Mutex::new(HashMap::new())),
+            jpeg_options: Arc::new(Mutex::new(JpegOptions::default())),
+            collection_config: Arc::new(Mutex::new(SceneCollectionConfig::default())),
+            preload_strategy: Arc::new(Mutex::new(PreloadStrategy::Eager)),
+            preload_idle_delay: Arc::new(Mutex::new(DEFAULT_PRELOAD_IDLE_DELAY)),
+            preload_concurrency: Arc::new(Mutex::new(max_decodes)),
+            preload_counts: Arc::new(Mutex::new(PreloadCounts::default())),
+            preload_paused: Arc::new(AtomicBool::new(false)),
+            navigation_history: Arc::new(Mutex::new(NavigationHistory::default())),
+            preload_generation: Arc::new(AtomicU64::new(0)),
+            phash_cache: Arc::new(Mutex::new(HashMap::new())),
+            scene_rotations: Arc::new(Mutex::new(HashMap::new())),
+            decode_limit: Arc::new(Semaphore::new(max_decodes)),
+            max_concurrent_decodes: Arc::new(Mutex::new(max_decodes)),
+            progressive_loading: Arc::new(Mutex::new(false)),
+            color_cache: Arc::new(Mutex::new(HashMap::new())),
+            blurhash_cache: Arc::new(Mutex::new(HashMap::new())),
+            edited_scenes: Arc::new(Mutex::new(HashMap::new())),
+            dimension_summaries: Arc::new(Mutex::new(HashMap::new())),
+            layout_hints: Arc::new(Mutex::new(HashMap::new())),
+            thumbnail_fallback: Arc::new(Mutex::new(ThumbnailFallback::None)),
+            strict_scene_loading: Arc::new(Mutex::new(false)),
+            tier_formats: Arc::new(Mutex::new(TierFormats::default())),
+            read_pages: Arc::new(Mutex::new(HashMap::new())),
+            read_page_ids: Arc::new(Mutex::new(HashMap::new())),
+            auto_mark_read: Arc::new(Mutex::new(false)),
+            inflight_upgrades: Arc::new(Mutex::new(HashSet::new())),
+            at_collection_end: Arc::new(Mutex::new(AtCollectionEnd::default())),
+            wrap_collections: Arc::new(Mutex::new(false)),
+            disk_thumbnails: Arc::new(Mutex::new(None)),
+            memory_limit: Arc::new(Mutex::new(None)),
+            spread_mode: Arc::new(Mutex::new(SpreadMode::default())),
+            reading_direction: Arc::new(Mutex::new(ReadingDirection::default())),
+            display_resolution: Arc::new(Mutex::new(DisplayResolution::default())),
+            max_dimension: Arc::new(Mutex::new(MAX_DIMENSION)),
+            slideshow: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+/// Navigation state that changes together
+#[derive(Debug, Default)]
+pub struct ViewerSession {
+    pub scene: Option<Arc<Scene>>,
+    pub collection: Option<SceneCollection>,
+    pub scene_index: usize,
+    pub page_index: usize,
+    pub loop_mode: LoopMode,
+}
+
+impl ViewerSession {
+    /// Switch to a newly loaded collection, at the start page of `scene`
+    fn open(&mut self, collection: SceneCollection, scene: Scene) {
+        self.page_index = scene.start_page();
+        self.scene = Some(Arc::new(scene));
+        self.collection = Some(collection);
+        self.scene_index = 0;
+    }
+
+    /// Switch to scene `index` of the loaded collection, at its start page
+    fn enter_scene(&mut self, index: usize, scene: Scene) {
+        self.page_index = scene.start_page();
+        self.scene = Some(Arc::new(scene));
+        self.scene_index = index;
+    }
+
+    /// Check that a scene read from `coll` (a clone of the loaded collection)
+    /// outside the session lock can still be switched to
+    ///
+    /// Fails if another collection was loaded meanwhile, or if the current
+    /// scene moved away from `from` when given.
+    fn check_unchanged(&self, coll: &SceneCollection, index: usize, from: Option<usize>) -> Result<(), AppError> {
+        let same_collection = self.collection.as_ref().is_some_and(|current| {
+            current.base_path == coll.base_path && current.scene_files.get(index) == coll.scene_files.get(index)
+        });
+        if !same_collection {
+            return Err(AppError::SceneLoadFailed(format!("Collection changed while loading scene {}", index)));
+        }
+        if from.is_some_and(|from| from != self.scene_index) {
+            return Err(AppError::SceneLoadFailed(format!("Position changed while loading scene {}", index)));
+        }
+        Ok(())
+    }
+}
+
+/// How navigation behaves at scene and collection boundaries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoopMode {
+    /// Move across scenes, stopping at the start/end of the collection
+    #[default]
+    None,
+    /// Loop within the current scene
+    Scene,
+    /// Move across scenes, wrapping around at the start/end of the collection
+    Collection,
+}
+
+/// What next_page does at the last page of the last scene when the loop mode
+/// is None
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AtCollectionEnd {
+    /// Stay on the last page and emit collection-end-reached
+    #[default]
+    Stop,
+    /// Go back to the first scene
+    Wrap,
+    /// Load the next sibling collection (see next_collection)
+    NextCollection,
+}
+
+/// Order of the pages of a spread from left to right, and which way
+/// next_page and prev_page turn (see page_step)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReadingDirection {
+    #[default]
+    LeftToRight,
+    /// Right-to-left books (e.g. manga): the first page of a spread is on the right
+    RightToLeft,
+}
+
+/// Number of pages preloaded around the current page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreloadCounts {
+    /// Pages in the predicted direction (see NavigationDirection)
+    pub ahead: usize,
+    /// Pages the other way, in case the reader turns around
+    pub behind: usize,
+}
+
+impl Default for PreloadCounts {
+    fn default() -> Self {
+        PreloadCounts { ahead: 3, behind: 2 }
+    }
+}
+
+/// Direction the reader is predicted to move in next
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NavigationDirection {
+    #[default]
+    Forward,
+    Backward,
+}
+
+/// The last NAVIGATION_HISTORY_LEN page moves, most recent last
+#[derive(Debug, Default)]
+pub struct NavigationHistory {
+    moves: VecDeque<NavigationDirection>,
+}
+
+impl NavigationHistory {
+    fn record(&mut self, direction: NavigationDirection) {
+        if self.moves.len() == NAVIGATION_HISTORY_LEN {
+            self.moves.pop_front();
+        }
+        self.moves.push_back(direction);
+    }
+
+    /// The direction most recent moves went in; a tie goes to the latest move
+    /// and no history to Forward
+    fn predicted(&self) -> NavigationDirection {
+        let backward = self.moves.iter().filter(|&&m| m == NavigationDirection::Backward).count();
+        let forward = self.moves.len() - backward;
+        match forward.cmp(&backward) {
+            std::cmp::Ordering::Greater => NavigationDirection::Forward,
+            std::cmp::Ordering::Less => NavigationDirection::Backward,
+            std::cmp::Ordering::Equal => self.moves.back().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// When the pages after the current one are decoded in the background
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PreloadStrategy {
+    /// Right after every navigation
+    Eager,
+    /// Once navigation has paused for the idle delay
+    OnIdle,
+    /// Never
+    Off,
+}
+
+/// Encode format per image tier
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TierFormats {
+    /// Quick Instant/Preview images of progressive loading
+    pub preview: EncodeFormat,
+    /// Full resolution main images
+    pub high: EncodeFormat,
+    pub thumbnail: EncodeFormat,
+}
+
+/// Screen size the image tiers are derived from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayResolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for DisplayResolution {
+    fn default() -> Self {
+        DisplayResolution { width: 1920, height: 1080 }
+    }
+}
+
+impl DisplayResolution {
+    /// Longest side of the high tier: the screen's longest side
+    pub fn high_dimension(&self) -> u32 {
+        self.width.max(self.height)
+    }
+
+    /// Longest side of the preview tier: a third of the high tier
+    pub fn preview_dimension(&self) -> u32 {
+        (self.high_dimension() / 3).max(1)
+    }
+
+    /// Longest sides of the high and preview tiers actually decoded: the
+    /// screen's, capped by the max dimension (see set_max_dimension)
+    pub fn tier_dimensions(&self, max_dimension: u32) -> (u32, u32) {
+        (self.high_dimension().min(max_dimension), self.preview_dimension().min(max_dimension))
+    }
+}
+
+/// Encoder settings read once for background encoding
+#[derive(Debug, Clone, Copy)]
+struct EncodeSettings {
+    jpeg_options: JpegOptions,
+    formats: TierFormats,
+    /// Main images are resized to fit within this size
+    high_dimension: u32,
+    /// Thumbnails are resized to fit within this size
+    max_dimension: u32,
+    /// Whether pages without a thumbnail file get one made from the main image
+    generate_thumbnails: bool,
+}
+
+impl EncodeSettings {
+    fn from_state(state: &AppState) -> Self {
+        let max_dimension = *state.max_dimension.lock().unwrap();
+        EncodeSettings {
+            jpeg_options: *state.jpeg_options.lock().unwrap(),
+            formats: *state.tier_formats.lock().unwrap(),
+            high_dimension: state.display_resolution.lock().unwrap().tier_dimensions(max_dimension).0,
+            max_dimension,
+            generate_thumbnails: *state.thumbnail_fallback.lock().unwrap() == ThumbnailFallback::GenerateFromMain,
         }
     }
 }
 
+/// What is used as the thumbnail of a page without a thumbnail file
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ThumbnailFallback {
+    /// No thumbnail
+    #[default]
+    None,
+    /// The page's main image, shrunk to the scene's thumbnail size
+    GenerateFromMain,
+    /// The scene's cover (page 0 thumbnail, or page 0 itself)
+    SceneCover,
+    /// A fixed placeholder image file
+    Placeholder(String),
+}
+
+/// Current scene and page indices
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub scene_index: usize,
+    pub page_index: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SceneInfo {
     pub scene_name: String,
     pub scene_index: usize,
     pub total_pages: usize,
     pub current_page: usize,
+    /// Number of pages marked read
+    pub read_count: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageData {
     pub main_image: Option<String>,
     pub thumbnail_image: Option<String>,
     pub page_index: usize,
     pub scene_index: usize,
     pub image_path: String,
+    /// Dimensions of the returned main image (falls back to metadata.imageSize)
+    pub width: u32,
+    pub height: u32,
+    /// Set when the image may be slow or memory hungry to display
+    pub warning: Option<String>,
+    pub page_title: Option<String>,
+    pub page_caption: Option<String>,
+    pub tier: ImageTier,
+    /// Set when the main image has transparent pixels and was sent as PNG
+    pub has_alpha: bool,
+    /// Set when the source was scaled down further than requested to stay
+    /// within the max side limit (see set_max_image_side)
+    pub clamped_to_max_side: bool,
+}
+
+/// Quality tier of a returned main image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImageTier {
+    /// Thumbnail embedded in the file's EXIF data
+    Instant,
+    /// Downscaled to the display resolution's preview dimension
+    Preview,
+    /// The requested size (the display resolution's high dimension by default)
+    High,
+}
+
+/// Payload of the image-upgraded event, sent when the full tier of a page
+/// returned at a lower tier by get_image is ready
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageUpgradeEvent {
+    pub scene_index: usize,
+    pub page_index: usize,
+    pub image_path: String,
+    pub main_image: String,
+    pub width: u32,
+    pub height: u32,
+    pub tier: ImageTier,
+}
+
+/// Time spent producing the main image of the last page returned by get_image
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct PageTimings {
+    pub decode_ms: f64,
+    pub resize_ms: f64,
+    pub encode_ms: f64,
+    pub from_cache: bool,
+}
+
+/// Timings of loading one page from cold caches, from benchmark_cold_page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    /// Time to the quick (EXIF thumbnail or preview) tier, as progressive
+    /// loading first shows it
+    pub preview_ms: f64,
+    /// Time to the high tier after the preview
+    pub highres_ms: f64,
+    /// Length of the encoded data URLs returned to the frontend
+    pub preview_bytes: usize,
+    pub highres_bytes: usize,
+}
+
+/// The page next_page/prev_page would move to, from peek_next_page/peek_prev_page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PagePeek {
+    pub scene_index: usize,
+    pub page_index: usize,
+    pub image_path: String,
+}
+
+/// Decoded pixels of a page, 4 bytes per pixel in row order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+/// Payload of the scene-count-updated event emitted during incremental discovery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneCountUpdate {
+    pub scene_count: usize,
+    pub complete: bool,
+}
+
+/// All thumbnails of a scene (or covers of a collection) packed into one grid image
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpriteSheet {
+    /// Base64 JPEG of the whole grid; page (or scene) `i` is at column
+    /// `i % cols`, row `i / cols`
+    pub image: String,
+    pub cols: u32,
+    pub rows: u32,
+    pub cell_size: u32,
+    pub count: usize,
+}
+
+/// Payload of the scene-covers-progress event emitted by warm_scene_covers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverWarmProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Payload of the decode-verify-progress event emitted by verify_all_decodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodeVerifyProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+/// A page that failed to decode, from verify_all_decodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodeFailure {
+    pub scene_index: usize,
+    pub page_index: usize,
+    pub image_path: String,
+    pub error: String,
+}
+
+/// Pages of a scene composited side by side
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpreadImage {
+    pub image: String,
+    /// Page indices in the spread, left to right
+    pub pages: Vec<usize>,
+    /// Number of spreads in the scene
+    pub spread_count: usize,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// The two pages of a spread as separate images, from get_spread
+///
+/// A page shown alone (the cover, or an odd last page) leaves the other side
+/// None.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpreadPages {
+    pub scene_index: usize,
+    pub left_page: Option<usize>,
+    pub right_page: Option<usize>,
+    pub left_image: Option<String>,
+    pub right_image: Option<String>,
+}
+
+/// Payload of the export-progress event emitted by export_page_range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Filesystem details of a page's image file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageFileInfo {
+    pub path: String,
+    pub size_bytes: u64,
+    /// Last modification time in seconds since the Unix epoch, if the
+    /// filesystem records one
+    pub modified_unix: Option<u64>,
+    /// Format detected from the file's magic bytes, if recognized
+    pub format: Option<String>,
+}
+
+/// Approximate memory held by the image caches
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CacheMemory {
+    /// Pixel data of decoded images
+    pub decoded_bytes: usize,
+    /// Base64 strings of encoded images and covers
+    pub encoded_bytes: usize,
+}
+
+impl CacheMemory {
+    fn of(state: &AppState) -> Self {
+        CacheMemory {
+            decoded_bytes: state.cache.memory_bytes(),
+            encoded_bytes: state.encoded_cache.memory_bytes() + state.cover_cache.memory_bytes(),
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.decoded_bytes + self.encoded_bytes
+    }
+}
+
+/// Entry counts and lookup counters of the decoded and encoded image caches,
+/// from get_cache_stats
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub image_cache_size: usize,
+    pub image_cache_capacity: usize,
+    pub image_cache_hits: u64,
+    pub image_cache_misses: u64,
+    pub encoded_cache_size: usize,
+    pub encoded_cache_capacity: usize,
+    pub encoded_cache_hits: u64,
+    pub encoded_cache_misses: u64,
+}
+
+/// What clear_caches released
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClearedCaches {
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+/// Payload of the collection-end-reached event emitted when next_page stops
+/// at the last page of the collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionEndReached {
+    pub scene_index: usize,
+    pub page_index: usize,
+}
+
+/// One page of a scene's table of contents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TocEntry {
+    pub page_index: usize,
+    pub title: Option<String>,
+    /// Base64 JPEG thumbnail, None if the page has none and no fallback applies
+    pub thumbnail: Option<String>,
+}
+
+/// Dominant page orientation of a scene
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+    Mixed,
+}
+
+/// Summary of a scene's page shapes, for picking single or spread layout
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LayoutHint {
+    pub orientation: Orientation,
+    /// Mean width / height of the sampled pages
+    pub avg_aspect: f64,
+}
+
+impl LayoutHint {
+    /// Classify page dimensions; square pages count toward neither orientation
+    fn from_dimensions(dimensions: &[(u32, u32)]) -> Self {
+        let aspects: Vec<f64> = dimensions.iter()
+            .filter(|(_, height)| *height > 0)
+            .map(|(width, height)| *width as f64 / *height as f64)
+            .collect();
+        if aspects.is_empty() {
+            return LayoutHint { orientation: Orientation::Mixed, avg_aspect: 1.0 };
+        }
+
+        let count = aspects.len() as f64;
+        let portrait = aspects.iter().filter(|a| **a < 1.0).count() as f64 / count;
+        let landscape = aspects.iter().filter(|a| **a > 1.0).count() as f64 / count;
+        let orientation = if portrait >= LAYOUT_MAJORITY {
+            Orientation::Portrait
+        } else if landscape >= LAYOUT_MAJORITY {
+            Orientation::Landscape
+        } else {
+            Orientation::Mixed
+        };
+        LayoutHint { orientation, avg_aspect: aspects.iter().sum::<f64>() / count }
+    }
+}
+
+/// Spread of the page sizes of a scene, from get_scene_dimension_summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DimensionSummary {
+    /// Smallest width and smallest height (not necessarily of one page)
+    pub min: ImageSize,
+    /// Largest width and largest height
+    pub max: ImageSize,
+    /// Mean width and height, rounded
+    pub avg: ImageSize,
+    /// All pages have the same size
+    pub consistent: bool,
+}
+
+impl DimensionSummary {
+    /// Summarize page dimensions; None for an empty list
+    fn from_dimensions(dimensions: &[(u32, u32)]) -> Option<Self> {
+        let count = dimensions.len() as u64;
+        if count == 0 {
+            return None;
+        }
+        let widths = || dimensions.iter().map(|(width, _)| *width);
+        let heights = || dimensions.iter().map(|(_, height)| *height);
+        let mean = |sum: u64| ((sum + count / 2) / count) as u32;
+
+        let min = ImageSize { width: widths().min()?, height: heights().min()? };
+        let max = ImageSize { width: widths().max()?, height: heights().max()? };
+        Some(DimensionSummary {
+            consistent: min.width == max.width && min.height == max.height,
+            avg: ImageSize {
+                width: mean(widths().map(u64::from).sum()),
+                height: mean(heights().map(u64::from).sum()),
+            },
+            min,
+            max,
+        })
+    }
+}
+
+/// Payload of the scene-load-failed event emitted when scene navigation skips
+/// a scene that could not be loaded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneLoadFailed {
+    pub index: usize,
+    pub reason: String,
+}
+
+/// Result of a cheap check of a collection directory
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CollectionProbe {
+    pub exists: bool,
+    pub is_dir: bool,
+    pub scene_file_count: usize,
+    /// Scene name of the first scene, if its metadata could be read
+    pub first_scene_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SceneListItem {
+    pub name: String,
+    pub path: String,
+}
+
+impl SceneListItem {
+    /// Build a list item, using a lossy display name so non-UTF-8 names stay distinct
+    fn from_path(path: &Path) -> Self {
+        SceneListItem {
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Unknown".to_string()),
+            path: path.to_string_lossy().to_string(),
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SceneListItem {
-    pub name: String,
-    pub path: String,
+/// Load a scene collection from a directory
+///
+/// With `incremental`, only the first scene file found is loaded up front so
+/// it can be displayed immediately; the rest of the directory is scanned in
+/// the background, emitting `scene-count-updated` events as scenes are found.
+///
+/// `password` decrypts encrypted archive entries. It is kept in memory for
+/// this collection only and never saved, not even to the recent list.
+#[tauri::command]
+pub async fn load_scene_collection(
+    path: String,
+    incremental: Option<bool>,
+    password: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, AppError> {
+    let incremental = incremental.unwrap_or(false);
+    let config = state.collection_config.lock().unwrap().clone();
+    let collection = if incremental {
+        let first = SceneCollection::find_first_scene_file(&path, &config)
+            .map_err(|e| format!("Failed to load scene collection: {}", e))?;
+        SceneCollection::from_scene_files(PathBuf::from(&path), first.into_iter().collect())
+    } else {
+        SceneCollection::new(&path, &config)
+            .map_err(|e| format!("Failed to load scene collection: {}", e))?
+    };
+
+    // An empty collection is refused, keeping the current one open
+    let scene_count = collection.scene_count();
+    if scene_count == 0 {
+        warn!("No scene files in {}", path);
+        return Err(format!("Collection {} has no scenes", path).into());
+    }
+
+    // Load the first scene
+    let scene = load_scene_counted(&state, &collection, 0)
+        .map_err(|e| AppError::SceneLoadFailed(format!("Failed to load first scene: {}", e)))?;
+
+    // An archive is checked against the password up front so a wrong one
+    // fails the load instead of every page
+    if let Some((archive, entry)) = scene.get_page_image(0).and_then(archive::split_entry_path) {
+        if let Err(e) = archive::check_password(archive, entry, password.as_deref()) {
+            if e.downcast_ref::<archive::WrongPassword>().is_some() {
+                return Err(AppError::WrongPassword);
+            }
+            warn!("Failed to read the first page of {:?}: {:#}", archive, e);
+        }
+    }
+
+    // Only a load that passed the checks above stops the open collection's
+    // background work and drops its page counts
+    let generation = state.load_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    {
+        let mut counts = state.scene_page_counts.lock().unwrap();
+        counts.clear();
+        counts.insert(0, scene.page_count());
+    }
+    state.session.write().unwrap().open(collection, scene);
+    archive::set_password(password);
+
+    // Preload initial images in background
+    spawn_preload(&state);
+
+    if let Err(e) = recent::record_recent(&app, &path) {
+        warn!("Failed to update recent collections: {}", e);
+    }
+
+    if incremental {
+        spawn_scene_discovery(&state, app, PathBuf::from(&path), config, generation);
+    }
+
+    Ok(format!("Loaded {} scenes", scene_count))
+}
+
+/// Load the collection next to the current one in its parent directory,
+/// in find_scene_collections order
+#[tauri::command]
+pub async fn next_collection(state: State<'_, AppState>, app: AppHandle) -> Result<SceneInfo, AppError> {
+    load_adjacent_collection(state, app, true).await
+}
+
+/// Load the collection before the current one in its parent directory
+#[tauri::command]
+pub async fn prev_collection(state: State<'_, AppState>, app: AppHandle) -> Result<SceneInfo, AppError> {
+    load_adjacent_collection(state, app, false).await
+}
+
+/// Find the sibling collection after (or before) the loaded one and load it
+/// at its first scene
+///
+/// At the last (or first) collection this wraps around if wrap_collections is
+/// set and fails otherwise.
+async fn load_adjacent_collection(state: State<'_, AppState>, app: AppHandle, forward: bool) -> Result<SceneInfo, AppError> {
+    let current = state.session.read().unwrap().collection.as_ref()
+        .map(|coll| coll.base_path.clone())
+        .ok_or(AppError::NoCollectionLoaded)?;
+    let parent = current.parent()
+        .ok_or_else(|| format!("Collection {} has no parent directory", current.display()))?;
+
+    let config = state.collection_config.lock().unwrap().clone();
+    let siblings = SceneCollection::find_scene_collections(parent, &config)
+        .map_err(|e| format!("Failed to list collections in {}: {}", parent.display(), e))?;
+    let index = siblings.iter().position(|path| *path == current)
+        .ok_or_else(|| format!("Collection {} is not among its siblings", current.display()))?;
+
+    let wrap = *state.wrap_collections.lock().unwrap();
+    let target = match (forward, index) {
+        (true, i) if i + 1 < siblings.len() => i + 1,
+        (false, i) if i > 0 => i - 1,
+        _ if !wrap => {
+            return Err(if forward { "Already at the last collection" } else { "Already at the first collection" }.into());
+        }
+        (true, _) => 0,
+        (false, _) => siblings.len() - 1,
+    };
+
+    let path = siblings[target].to_string_lossy().into_owned();
+    info!("Moving to {} collection {}", if forward { "next" } else { "previous" }, path);
+    load_scene_collection(path, None, None, state.clone(), app).await?;
+    get_scene_info(state).await
+}
+
+/// Get what next_page does at the end of the collection
+#[tauri::command]
+pub async fn get_at_collection_end(state: State<'_, AppState>) -> Result<AtCollectionEnd, AppError> {
+    Ok(*state.at_collection_end.lock().unwrap())
+}
+
+/// Set what next_page does at the last page of the last scene when the loop
+/// mode is None
+#[tauri::command]
+pub async fn set_at_collection_end(behavior: AtCollectionEnd, state: State<'_, AppState>) -> Result<(), AppError> {
+    *state.at_collection_end.lock().unwrap() = behavior;
+    info!("At collection end set to {:?}", behavior);
+    Ok(())
+}
+
+/// Get whether next_collection/prev_collection wrap around at the ends
+#[tauri::command]
+pub async fn get_wrap_collections(state: State<'_, AppState>) -> Result<bool, AppError> {
+    Ok(*state.wrap_collections.lock().unwrap())
+}
+
+/// Enable or disable wrapping around in next_collection/prev_collection
+#[tauri::command]
+pub async fn set_wrap_collections(enabled: bool, state: State<'_, AppState>) -> Result<(), AppError> {
+    *state.wrap_collections.lock().unwrap() = enabled;
+    info!("Collection wrapping {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Check whether a path is a loadable collection without loading any images
+///
+/// Counts the scene files and reads only the metadata of the first one.
+#[tauri::command]
+pub async fn probe_collection(path: String, state: State<'_, AppState>) -> Result<CollectionProbe, AppError> {
+    let dir = Path::new(&path);
+    let mut probe = CollectionProbe {
+        exists: dir.exists(),
+        is_dir: dir.is_dir(),
+        scene_file_count: 0,
+        first_scene_name: None,
+    };
+    if !probe.is_dir {
+        return Ok(probe);
+    }
+
+    let config = state.collection_config.lock().unwrap().clone();
+    let scene_files = SceneCollection::scan_scene_files(dir, &config, usize::MAX, |_| true)
+        .map_err(|e| format!("Failed to scan collection: {}", e))?;
+    // Same order as load_scene_collection, so the first scene is the one it opens
+    let collection = SceneCollection::from_scene_files(dir.to_path_buf(), scene_files);
+    probe.scene_file_count = collection.scene_count();
+
+    if let Some(first) = collection.scene_files.first() {
+        match Scene::load_metadata(first) {
+            Ok(metadata) => probe.first_scene_name = Some(metadata.scene_name),
+            Err(e) => warn!("Failed to read first scene of {:?}: {}", path, e),
+        }
+    }
+
+    Ok(probe)
+}
+
+/// Open a single scene file, without a collection directory
+///
+/// The scene becomes the only scene of a synthetic collection so the
+/// navigation commands keep working with a scene count of 1. A directory of
+/// images is opened as a scene with one page per image, in natural filename
+/// order.
+#[tauri::command]
+pub async fn load_single_scene(scene_path: String, state: State<'_, AppState>) -> Result<SceneInfo, AppError> {
+    let path = PathBuf::from(&scene_path);
+    let scene = Scene::open(&path)
+        .map_err(|e| AppError::SceneLoadFailed(format!("Failed to load scene: {}", e)))?;
+
+    let base_path = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let collection = SceneCollection::from_scene_files(base_path, vec![path]);
+
+    // Stop background work for the previous collection
+    state.load_generation.fetch_add(1, Ordering::SeqCst);
+    {
+        let mut counts = state.scene_page_counts.lock().unwrap();
+        counts.clear();
+        counts.insert(0, scene.page_count());
+    }
+
+    state.session.write().unwrap().open(collection, scene);
+    info!("Loaded single scene {:?}", scene_path);
+
+    spawn_preload(&state);
+    get_scene_info(state).await
+}
+
+/// Scan the rest of a collection directory in the background, updating the
+/// loaded collection and emitting scene-count-updated as scenes are found
+fn spawn_scene_discovery(
+    state: &AppState,
+    app: AppHandle,
+    base_path: PathBuf,
+    config: SceneCollectionConfig,
+    generation: u64,
+) {
+    let session = state.session.clone();
+    let scene_page_counts = state.scene_page_counts.clone();
+    let load_generation = state.load_generation.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let is_current = || load_generation.load(Ordering::SeqCst) == generation;
+
+        let result = SceneCollection::scan_scene_files(&base_path, &config, DISCOVERY_BATCH_SIZE, |found| {
+            let Some(scene_count) = update_scene_files(&session, found.to_vec(), is_current) else {
+                debug!("Collection changed, stopping discovery of {:?}", base_path);
+                return false;
+            };
+            // Indices shift as files are sorted in, so cached counts are no longer valid
+            scene_page_counts.lock().unwrap().clear();
+            let _ = app.emit("scene-count-updated", SceneCountUpdate { scene_count, complete: false });
+            true
+        });
+
+        match result {
+            Ok(scene_files) => {
+                if let Some(scene_count) = update_scene_files(&session, scene_files, is_current) {
+                    scene_page_counts.lock().unwrap().clear();
+                    info!("Discovered {} scenes in {:?}", scene_count, base_path);
+                    let _ = app.emit("scene-count-updated", SceneCountUpdate { scene_count, complete: true });
+                }
+            }
+            Err(e) => warn!("Scene discovery failed for {:?}: {}", base_path, e),
+        }
+    });
+}
+
+/// Swap in a newly discovered scene file list, keeping the current scene
+/// selected even if sorting moved it to a different index
+///
+/// `is_current` is checked under the session lock, so a collection loaded
+/// since the scan started never gets the old directory's files. None then.
+fn update_scene_files(session: &RwLock<ViewerSession>, scene_files: Vec<PathBuf>, is_current: impl Fn() -> bool) -> Option<usize> {
+    let mut guard = session.write().unwrap();
+    let session = &mut *guard;
+    if !is_current() {
+        return None;
+    }
+
+    Some(match session.collection.as_mut() {
+        Some(coll) => {
+            let current_file = coll.scene_files.get(session.scene_index).cloned();
+            coll.set_scene_files(scene_files);
+
+            if let Some(current_file) = current_file {
+                if let Some(new_index) = coll.scene_files.iter().position(|p| *p == current_file) {
+                    session.scene_index = new_index;
+                }
+            }
+            coll.scene_count()
+        }
+        None => 0,
+    })
+}
+
+/// Reorder the scenes of the loaded collection, e.g. for a playlist
+///
+/// `new_order[i]` is the current index of the scene that moves to index `i`.
+/// The current scene stays selected at its new index, and navigation follows
+/// the new order. The order only lives in memory; reloading the collection or
+/// background scene discovery restores the sorted order.
+#[tauri::command]
+pub async fn reorder_scenes(new_order: Vec<usize>, state: State<'_, AppState>) -> Result<(), AppError> {
+    let mut guard = state.session.write().unwrap();
+    let session = &mut *guard;
+    let coll = session.collection.as_mut().ok_or(AppError::NoCollectionLoaded)?;
+    coll.reorder(&new_order)
+        .map_err(|e| format!("Invalid scene order: {}", e))?;
+
+    if let Some(new_index) = new_order.iter().position(|&i| i == session.scene_index) {
+        session.scene_index = new_index;
+    }
+
+    // Move cached page counts along with their scenes
+    let mut counts = state.scene_page_counts.lock().unwrap();
+    *counts = new_order.iter().enumerate()
+        .filter_map(|(new_index, old_index)| counts.get(old_index).map(|&count| (new_index, count)))
+        .collect();
+
+    info!("Reordered {} scenes, current scene now at {}", new_order.len(), session.scene_index);
+    Ok(())
+}
+
+/// Get the current scene information
+#[tauri::command]
+pub async fn get_scene_info(state: State<'_, AppState>) -> Result<SceneInfo, AppError> {
+    let session = state.session.read().unwrap();
+
+    if let Some(scene) = session.scene.as_ref() {
+        let read_count = session.collection.as_ref()
+            .and_then(|coll| coll.scene_files.get(session.scene_index))
+            .map(|path| {
+                let scene_file = path.to_string_lossy();
+                let pages = state.read_pages.lock().unwrap().get(&*scene_file).cloned().unwrap_or_default();
+                let ids = state.read_page_ids.lock().unwrap().get(&*scene_file).cloned().unwrap_or_default();
+                read_count(scene, &pages, &ids)
+            })
+            .unwrap_or(0);
+
+        Ok(SceneInfo {
+            scene_name: scene.metadata.scene_name.clone(),
+            scene_index: session.scene_index,
+            total_pages: scene.page_count(),
+            current_page: session.page_index,
+            read_count,
+        })
+    } else {
+        Err(AppError::NoSceneLoaded)
+    }
+}
+
+/// Get an image at a specific page
+///
+/// When `target_size` is given, the main image is decoded and resized to fit
+/// within that size (capped at MAX_TARGET_SIZE) instead of the default tier.
+/// Used by the frontend to request sharper images as the zoom level changes.
+///
+/// With progressive loading enabled and the image not yet encoded, the EXIF
+/// thumbnail (or else a preview tier image) is returned right away and
+/// the full image follows as an `image-upgraded` event.
+#[tauri::command]
+pub async fn get_image(
+    scene_index: Option<usize>,
+    page_index: usize,
+    target_size: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ImageData, AppError> {
+    debug!("get_image called: scene_index={:?}, page_index={}, target_size={:?}", scene_index, page_index, target_size);
+    if target_size == Some(0) {
+        return Err("Target size must be greater than zero".into());
+    }
+    let _permit = acquire_decode_permit(&state).await?;
+
+    // Read a different scene if requested, without holding the session lock
+    let switch_to = scene_index.and_then(|new_scene_idx| {
+        let session = state.session.read().unwrap();
+        let coll = session.collection.as_ref().filter(|_| new_scene_idx != session.scene_index)?;
+        Some((coll.clone(), new_scene_idx))
+    });
+    let loaded = match switch_to {
+        Some((coll, new_scene_idx)) => {
+            let scene = load_scene_counted(&state, &coll, new_scene_idx)
+                .map_err(|e| AppError::SceneLoadFailed(format!("Failed to load scene {}: {}", new_scene_idx, e)))?;
+            Some((coll, new_scene_idx, scene))
+        }
+        None => None,
+    };
+
+    // Switch scenes and move to the page under a short session lock, then
+    // decode without holding it
+    let (scene, scene_idx, rotation) = {
+        let mut guard = state.session.write().unwrap();
+        let session = &mut *guard;
+
+        if let Some((coll, new_scene_idx, scene)) = loaded {
+            session.check_unchanged(&coll, new_scene_idx, None)?;
+            session.scene = Some(Arc::new(scene));
+            session.scene_index = new_scene_idx;
+        }
+
+        let Some(scene) = session.scene.clone() else {
+            warn!("No scene loaded in get_image");
+            return Err(AppError::NoSceneLoaded);
+        };
+        if page_index >= scene.page_count() {
+            return Err(AppError::PageOutOfBounds { index: page_index, total: scene.page_count() });
+        }
+
+        // Update current page index
+        session.page_index = page_index;
+        debug!("Updated current page index to: {}", page_index);
+
+        let rotation = collection_rotation(&state, session.collection.as_ref(), session.scene_index);
+        (scene, session.scene_index, rotation)
+    };
+
+    decode_pool::run(move || {
+        page_image(&app.state::<AppState>(), app.clone(), scene, scene_idx, page_index, rotation, target_size)
+    }).await?
+}
+
+/// Decode a page of the installed scene into the `ImageData` answered by
+/// get_image, without holding the session lock
+///
+/// Loads, resizes and encodes; callers run it on the decode pool.
+fn page_image(
+    state: &AppState,
+    app: AppHandle,
+    scene: Arc<Scene>,
+    scene_idx: usize,
+    page_index: usize,
+    rotation: u32,
+    target_size: Option<u32>,
+) -> Result<ImageData, AppError> {
+    if *state.auto_mark_read.lock().unwrap() {
+        if let Err(e) = mark_read(state, &app, scene_idx, page_index) {
+            warn!("Failed to mark page {} of scene {} read: {}", page_index, scene_idx, e);
+        }
+    }
+
+    let page = scene.get_page(page_index)
+        .ok_or("Failed to get page image")?;
+    let main_path = page.image.as_str();
+
+    let thumbnail_path = scene.get_thumbnail_path(main_path);
+
+    let formats = *state.tier_formats.lock().unwrap();
+    let max_dimension = *state.max_dimension.lock().unwrap();
+    let (high_dimension, preview_dimension) = state.display_resolution.lock().unwrap().tier_dimensions(max_dimension);
+
+    // Protect this page's cache entries from eviction by background preloading
+    let thumb_key = thumbnail_path.to_string_lossy().to_string();
+    let mut page_keys = vec![
+        format_key(main_path, rotation, formats.high),
+        format!("{}@{}", main_path, high_dimension),
+        format!("{}@{}", main_path, preview_dimension),
+        format!("{}@{}", thumb_key, max_dimension),
+        format_key(&thumb_key, rotation, formats.thumbnail),
+    ];
+    if let Some(size) = target_size {
+        page_keys.push(format_key(&format!("{}@{}", main_path, size.min(MAX_TARGET_SIZE)), rotation, formats.high));
+    }
+    pin_page_keys(state, page_keys);
+
+    let (encoded_key, size) = match target_size {
+        Some(size) => {
+            let size = size.min(MAX_TARGET_SIZE);
+            (format!("{}@{}", main_path, size), size)
+        }
+        None => (main_path.to_string(), high_dimension),
+    };
+
+    // Answer with a quick tier if the full image isn't encoded yet
+    let progressive = *state.progressive_loading.lock().unwrap()
+        && state.encoded_cache.get(&format_key(&encoded_key, rotation, formats.high)).is_none();
+    let quick = if progressive {
+        load_quick_image(main_path, rotation, preview_dimension, state)
+    } else {
+        None
+    };
+
+    let (main_image, dimensions, tier) = match quick {
+        Some((image, dimensions, tier)) => {
+            spawn_upgrade(app, scene_idx, page_index, main_path.to_string(), encoded_key, size, rotation);
+            (Some(image), Some(dimensions), tier)
+        }
+        None => {
+            // Load main image - check encoded cache first
+            let (main_image, dimensions, timings) = load_main_image(main_path, encoded_key, size, rotation, state);
+            *state.last_page_timings.lock().unwrap() = Some(timings);
+            (main_image, dimensions, ImageTier::High)
+        }
+    };
+
+    let thumbnail_image = load_thumbnail(&scene, main_path, rotation, state);
+
+    let (width, height) = dimensions.unwrap_or_else(|| rotated_dimensions(
+        scene.metadata.image_size.width,
+        scene.metadata.image_size.height,
+        rotation,
+    ));
+
+    let has_alpha = main_image.as_deref().is_some_and(is_png_data_url);
+    // Only a request beyond the limit can be clamped by it; skip the header read otherwise
+    let requested = if image_loader::resize_enabled() { size } else { u32::MAX };
+    let clamped_to_max_side = requested > image_loader::max_side()
+        && image_loader::image_dimensions(main_path).is_ok_and(|(w, h)| {
+            let (w, h) = fit_dimensions(w, h, requested, requested);
+            image_loader::exceeds_max_side(w, h)
+        });
+    let result = ImageData {
+        main_image,
+        thumbnail_image,
+        page_index,
+        scene_index: scene_idx,
+        image_path: main_path.to_string(),
+        width,
+        height,
+        warning: if image_loader::resize_enabled() { None } else { large_image_warning(main_path, width, height) },
+        page_title: page.title.clone(),
+        page_caption: page.caption.clone(),
+        tier,
+        has_alpha,
+        clamped_to_max_side,
+    };
+    debug!("Returning ImageData: page_index={}, scene_index={}, path={}", result.page_index, result.scene_index, result.image_path);
+    Ok(result)
+}
+
+/// Pick the file a page's thumbnail is decoded from
+///
+/// The thumbnail file if it exists, otherwise whatever the fallback mode
+/// points at. The flag is true when the file is the page's own thumbnail.
+fn thumbnail_source(scene: &Scene, main_path: &str, fallback: &ThumbnailFallback) -> Option<(PathBuf, bool)> {
+    let thumbnail_path = scene.get_thumbnail_path(main_path);
+    if thumbnail_path.exists() {
+        return Some((thumbnail_path, true));
+    }
+
+    match fallback {
+        ThumbnailFallback::None => None,
+        ThumbnailFallback::GenerateFromMain => Some((PathBuf::from(main_path), false)),
+        ThumbnailFallback::SceneCover => {
+            let cover_path = scene.get_page_image(0)?;
+            let cover_thumb = scene.get_thumbnail_path(cover_path);
+            if cover_thumb.exists() {
+                Some((cover_thumb, false))
+            } else {
+                Some((PathBuf::from(cover_path), false))
+            }
+        }
+        ThumbnailFallback::Placeholder(path) => Some((PathBuf::from(path), false)),
+    }
+}
+
+/// Largest size of a thumbnail made from something other than a thumbnail file
+fn fallback_thumbnail_size(scene: &Scene) -> (u32, u32) {
+    let size = &scene.metadata.thumbnail_size;
+    if size.width > 0 && size.height > 0 {
+        (size.width, size.height)
+    } else {
+        (DEFAULT_COVER_SIZE, DEFAULT_COVER_SIZE)
+    }
+}
+
+/// Encoded cache key of the thumbnail generated from a main image
+fn generated_thumbnail_key(main_path: &str, rotation: u32, format: EncodeFormat) -> String {
+    format_key(&format!("{}@thumb", main_path), rotation, format)
+}
+
+/// Decode a page's thumbnail from `source`, shrinking it to the scene's
+/// thumbnail size unless it is the page's own thumbnail file
+fn decode_thumbnail(scene: &Scene, source: &str, is_thumbnail: bool, state: &AppState) -> Result<Arc<image::DynamicImage>> {
+    let img = load_image_cached_with_size(source, *state.max_dimension.lock().unwrap(), &state.cache)?;
+    if is_thumbnail {
+        return Ok(img);
+    }
+    let (max_width, max_height) = fallback_thumbnail_size(scene);
+    Ok(Arc::new(resize_to_fit(&img, max_width, max_height)))
+}
+
+/// Load and encode a page's thumbnail, honoring the thumbnail fallback when
+/// the page has no thumbnail file - check encoded cache first
+///
+/// Preloading generates thumbnails of upcoming pages ahead of time, so this
+/// usually finds them in the encoded cache.
+fn load_thumbnail(scene: &Scene, main_path: &str, rotation: u32, state: &AppState) -> Option<String> {
+    let fallback = state.thumbnail_fallback.lock().unwrap().clone();
+    let (source, is_thumbnail) = thumbnail_source(scene, main_path, &fallback)?;
+
+    let source_str = source.to_str()?;
+    let format = state.tier_formats.lock().unwrap().thumbnail;
+    let encoded_key = if is_thumbnail {
+        format_key(source_str, rotation, format)
+    } else {
+        generated_thumbnail_key(source_str, rotation, format)
+    };
+    if let Some(cached) = state.encoded_cache.get(&encoded_key) {
+        return Some(cached);
+    }
+
+    // Generated thumbnails are also kept on disk when enabled
+    let (max_width, max_height) = fallback_thumbnail_size(scene);
+    let disk = if is_thumbnail { None } else { state.disk_thumbnails.lock().unwrap().clone() };
+    let disk_variant = format!("{}@{}x{}", encoded_key, max_width, max_height);
+    if let Some(cached) = disk.as_ref().and_then(|disk| disk.get(&source, &disk_variant)) {
+        state.encoded_cache.insert(encoded_key, cached.clone());
+        return Some(cached);
+    }
+
+    match decode_thumbnail(scene, source_str, is_thumbnail, state) {
+        Ok(img) => match encode_keeping_alpha(&rotate(&img, rotation), format, 75, &state.jpeg_options.lock().unwrap()) {
+            Ok(base64) => {
+                if let Some(disk) = &disk {
+                    if let Err(e) = disk.insert(&source, &disk_variant, &base64) {
+                        warn!("Failed to store thumbnail of {:?} on disk: {}", source, e);
+                    }
+                }
+                // Store in encoded cache for future use
+                state.encoded_cache.insert(encoded_key, base64.clone());
+                Some(base64)
+            }
+            Err(e) => {
+                warn!("Failed to encode thumbnail: {}", e);
+                None
+            }
+        },
+        Err(e) => {
+            warn!("Failed to load thumbnail: {}", e);
+            None
+        }
+    }
+}
+
+/// Get an image at its original resolution, bypassing the MAX_DIMENSION clamp
+/// but not the max side limit
+///
+/// Does not change the current page. Only the encoded result is cached (under
+/// `{path}@native`) since native decodes can be very large.
+#[tauri::command]
+pub async fn get_image_native(
+    scene_index: usize,
+    page_index: usize,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ImageData, AppError> {
+    let _permit = acquire_decode_permit(&state).await?;
+    let scene = scene_at(&state, scene_index)?;
+    let main_path = page_path(&scene, page_index)?;
+    let rotation = scene_rotation(&state, scene_index);
+    let native_key = rotated_key(&format!("{}@native", main_path), rotation);
+
+    decode_pool::run(move || -> Result<ImageData, AppError> {
+        let state = app.state::<AppState>();
+        let (source_width, source_height) = image_loader::image_dimensions(&main_path)
+            .map_err(|e| format!("Failed to read image size: {}", e))?;
+        let clamped_to_max_side = image_loader::exceeds_max_side(source_width, source_height);
+        let (main_image, (width, height)) = match state.encoded_cache.get(&native_key) {
+            Some(cached) => {
+                debug!("Encoded cache hit: {}", native_key);
+                let side = image_loader::max_side();
+                let (width, height) = fit_dimensions(source_width, source_height, side, side);
+                (cached, rotated_dimensions(width, height, rotation))
+            }
+            None => {
+                let img = load_image_with_retry(&main_path).map_err(|e| AppError::ImageDecodeFailed(format!("Failed to load image: {}", e)))?;
+                let img = if clamped_to_max_side { resize_to_fit(&img, u32::MAX, u32::MAX) } else { img };
+                let img = rotate(&img, rotation);
+                let base64 = encode_keeping_alpha(&img, EncodeFormat::Jpeg, NATIVE_JPEG_QUALITY, &state.jpeg_options.lock().unwrap())
+                    .map_err(|e| format!("Failed to encode image: {}", e))?;
+                state.encoded_cache.insert(native_key, base64.clone());
+                (base64, img.dimensions())
+            }
+        };
+
+        let warning = large_image_warning(&main_path, width, height);
+
+        let thumbnail_image = load_thumbnail(&scene, &main_path, rotation, &state);
+        let page = scene.get_page(page_index);
+
+        Ok(ImageData {
+            has_alpha: is_png_data_url(&main_image),
+            main_image: Some(main_image),
+            thumbnail_image,
+            page_index,
+            scene_index,
+            image_path: main_path,
+            width,
+            height,
+            warning,
+            page_title: page.and_then(|p| p.title.clone()),
+            page_caption: page.and_then(|p| p.caption.clone()),
+            tier: ImageTier::High,
+            clamped_to_max_side,
+        })
+    }).await?
+}
+
+/// Warning for ImageData when an unresized image is big enough to be slow or
+/// memory hungry to display
+fn large_image_warning(path: &str, width: u32, height: u32) -> Option<String> {
+    let pixels = width as u64 * height as u64;
+    if pixels <= NATIVE_WARN_PIXELS {
+        return None;
+    }
+    warn!("Native image {} is very large ({}x{})", path, width, height);
+    Some(format!(
+        "Image is very large ({}x{}, {:.0} megapixels) and may be slow to display",
+        width, height, pixels as f64 / 1_000_000.0
+    ))
+}
+
+/// Get a scene by index: the current scene if it matches, otherwise loaded
+/// from the collection without changing the current position
+///
+/// The scene is read without holding the session lock.
+fn scene_at(state: &AppState, scene_index: usize) -> Result<Arc<Scene>, AppError> {
+    let coll = {
+        let session = state.session.read().unwrap();
+        if scene_index == session.scene_index {
+            if let Some(scene) = session.scene.as_ref() {
+                return Ok(scene.clone());
+            }
+        }
+        session.collection.clone().ok_or(AppError::NoCollectionLoaded)?
+    };
+
+    let scene = load_scene_counted(state, &coll, scene_index)
+        .map_err(|e| AppError::SceneLoadFailed(format!("Failed to load scene {}: {}", scene_index, e)))?;
+    state.session.read().unwrap().check_unchanged(&coll, scene_index, None)?;
+    Ok(Arc::new(scene))
+}
+
+/// Wait for a decode permit, limiting how many commands decode at once
+async fn acquire_decode_permit<'a>(state: &'a AppState) -> Result<SemaphorePermit<'a>, String> {
+    state.decode_limit.acquire().await
+        .map_err(|_| "Decode limiter closed".to_string())
+}
+
+/// Spawn a task per item with at most `limit` running at a time, passing each
+/// result to `on_done` as it finishes
+///
+/// `spawn` adds the item's task to the set, with spawn or spawn_blocking. An
+/// error from `on_done` stops early, aborting the tasks still running.
+async fn run_bounded<I, T>(
+    items: impl IntoIterator<Item = I>,
+    limit: usize,
+    mut spawn: impl FnMut(&mut JoinSet<T>, I),
+    mut on_done: impl FnMut(Result<T, JoinError>) -> Result<(), String>,
+) -> Result<(), String>
+where
+    T: Send + 'static,
+{
+    let mut tasks = JoinSet::new();
+    for item in items {
+        while tasks.len() >= limit.max(1) {
+            if let Some(result) = tasks.join_next().await {
+                on_done(result)?;
+            }
+        }
+        spawn(&mut tasks, item);
+    }
+    while let Some(result) = tasks.join_next().await {
+        on_done(result)?;
+    }
+    Ok(())
+}
+
+/// Get the rotation of a scene in the current collection (0 if none is set)
+fn scene_rotation(state: &AppState, scene_index: usize) -> u32 {
+    let session = state.session.read().unwrap();
+    collection_rotation(state, session.collection.as_ref(), scene_index)
+}
+
+/// Get the rotation of a scene of `collection`, for callers already holding
+/// the session lock
+fn collection_rotation(state: &AppState, collection: Option<&SceneCollection>, scene_index: usize) -> u32 {
+    collection
+        .and_then(|coll| coll.scene_files.get(scene_index))
+        .and_then(|path| state.scene_rotations.lock().unwrap().get(&*path.to_string_lossy()).copied())
+        .unwrap_or(0)
+}
+
+/// Encoded cache key for an image rotated by `rotation` degrees and encoded
+/// as `format`; JPEG keeps the plain rotated key
+fn format_key(key: &str, rotation: u32, format: EncodeFormat) -> String {
+    let key = rotated_key(key, rotation);
+    match format {
+        EncodeFormat::Jpeg => key,
+        EncodeFormat::WebP => format!("{}#webp", key),
+    }
+}
+
+/// Encoded cache key for an image rotated by `rotation` degrees
+fn rotated_key(key: &str, rotation: u32) -> String {
+    if rotation == 0 {
+        key.to_string()
+    } else {
+        format!("{}#rot{}", key, rotation)
+    }
+}
+
+/// Dimensions of an image after rotating it by `rotation` degrees
+fn rotated_dimensions(width: u32, height: u32, rotation: u32) -> (u32, u32) {
+    if rotation % 180 == 90 {
+        (height, width)
+    } else {
+        (width, height)
+    }
+}
+
+/// Load a scene from the collection, remembering its page count if `coll`
+/// is still the open collection
+///
+/// Must not be called with the session lock held.
+fn load_scene_counted(state: &AppState, coll: &SceneCollection, index: usize) -> Result<Scene> {
+    let edited = coll.scene_files.get(index)
+        .and_then(|path| state.edited_scenes.lock().unwrap().get(path).cloned());
+    let scene = match edited {
+        Some(scene) => scene,
+        None => coll.load_scene(index)?,
+    };
+    record_page_count(&state.session, &state.scene_page_counts, coll, index, scene.page_count());
+    Ok(scene)
+}
+
+/// Remember the page count of scene `index` of `coll`, unless another
+/// collection was opened since, whose counts it would poison
+fn record_page_count(
+    session: &RwLock<ViewerSession>,
+    scene_page_counts: &Mutex<HashMap<usize, usize>>,
+    coll: &SceneCollection,
+    index: usize,
+    page_count: usize,
+) {
+    let session = session.read().unwrap();
+    if session.check_unchanged(coll, index, None).is_ok() {
+        scene_page_counts.lock().unwrap().insert(index, page_count);
+    }
+}
+
+/// Get the image path of a page, with the same bounds error as get_image
+fn page_path(scene: &Scene, page_index: usize) -> Result<String, AppError> {
+    scene.get_page_image(page_index)
+        .map(str::to_string)
+        .ok_or(AppError::PageOutOfBounds { index: page_index, total: scene.page_count() })
+}
+
+/// Pin the cache entries of the page on screen, unpinning the previous page's
+fn pin_page_keys(state: &AppState, keys: Vec<String>) {
+    let mut pinned = state.pinned_keys.lock().unwrap();
+    for key in pinned.iter() {
+        state.cache.unpin(key);
+        state.encoded_cache.unpin(key);
+    }
+    for key in &keys {
+        state.cache.pin(key.clone());
+        state.encoded_cache.pin(key.clone());
+    }
+    *pinned = keys;
+}
+
+/// Load and encode a main image resized to fit within `size` and rotated by
+/// `rotation` degrees, stored in the encoded cache under `encoded_key`
+/// (qualified by the rotation and the high tier's format). Returns the
+/// encoded image, its dimensions, and how long each stage took.
+fn load_main_image(
+    path: &str,
+    encoded_key: String,
+    size: u32,
+    rotation: u32,
+    state: &AppState,
+) -> (Option<String>, Option<(u32, u32)>, PageTimings) {
+    let format = state.tier_formats.lock().unwrap().high;
+    let encoded_key = format_key(&encoded_key, rotation, format);
+    if let Some(cached) = state.encoded_cache.get(&encoded_key) {
+        debug!("Encoded cache hit: {}", encoded_key);
+        // Prefer the decoded image if still cached, otherwise read just the header
+        let dimensions = state.cache.get(&format!("{}@{}", path, size))
+            .map(|img| img.dimensions())
+            .or_else(|| {
+                image_loader::image_dimensions(path)
+                    .ok()
+                    .map(|(w, h)| {
+                        let limit = if image_loader::resize_enabled() { size } else { u32::MAX };
+                        let limit = limit.min(image_loader::max_side());
+                        fit_dimensions(w, h, limit, limit)
+                    })
+            })
+            .map(|(w, h)| rotated_dimensions(w, h, rotation));
+        return (Some(cached), dimensions, PageTimings { from_cache: true, ..Default::default() });
+    }
+
+    let mut timings = PageTimings::default();
+    let mut dimensions = None;
+    let image = match load_image_cached_timed(path, size, &state.cache) {
+        Ok((img, load)) => {
+            timings.decode_ms = load.decode.as_secs_f64() * 1000.0;
+            timings.resize_ms = load.resize.as_secs_f64() * 1000.0;
+            timings.from_cache = load.from_cache;
+            let img = rotate(&img, rotation);
+            dimensions = Some(img.dimensions());
+
+            let start = Instant::now();
+            let encoded = encode_keeping_alpha(&img, format, 85, &state.jpeg_options.lock().unwrap());
+            timings.encode_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+            match encoded {
+                Ok(base64) => {
+                    // Store in encoded cache for future use
+                    state.encoded_cache.insert(encoded_key, base64.clone());
+                    Some(base64)
+                }
+                Err(e) => {
+                    warn!("Failed to encode main image: {}", e);
+                    None
+                }
+            }
+        }
+        Err(e) => {
+            warn!("Failed to load main image: {}", e);
+            None
+        }
+    };
+
+    debug!(
+        "Main image {} timings: decode={:.1}ms resize={:.1}ms encode={:.1}ms",
+        path, timings.decode_ms, timings.resize_ms, timings.encode_ms
+    );
+    (image, dimensions, timings)
+}
+
+/// Encode a quick, low-res version of a main image: the EXIF thumbnail if
+/// the file has one, otherwise a preview resized to fit within `preview_size`
+fn load_quick_image(path: &str, rotation: u32, preview_size: u32, state: &AppState) -> Option<(String, (u32, u32), ImageTier)> {
+    let (img, tier) = match extract_exif_thumbnail(path) {
+        Some(img) => (img, ImageTier::Instant),
+        None => {
+            let img = load_image_cached_with_size(path, preview_size, &state.cache)
+                .map_err(|e| warn!("Failed to load preview of {}: {}", path, e))
+                .ok()?;
+            ((*img).clone(), ImageTier::Preview)
+        }
+    };
+
+    let img = rotate(&img, rotation);
+    let format = state.tier_formats.lock().unwrap().preview;
+    let encoded = encode_keeping_alpha(&img, format, 75, &state.jpeg_options.lock().unwrap())
+        .map_err(|e| warn!("Failed to encode preview of {}: {}", path, e))
+        .ok()?;
+    debug!("Returning {:?} tier for {}", tier, path);
+    Some((encoded, img.dimensions(), tier))
+}
+
+/// Removes a path from inflight_upgrades when dropped, so the path is freed
+/// even if the upgrade task panics or is cancelled
+struct InflightUpgrade {
+    inflight_upgrades: Arc<Mutex<HashSet<String>>>,
+    path: String,
+}
+
+impl Drop for InflightUpgrade {
+    fn drop(&mut self) {
+        self.inflight_upgrades.lock().unwrap().remove(&self.path);
+    }
+}
+
+/// Load the full tier of a page in the background and send it as an
+/// image-upgraded event
+///
+/// Does nothing if an upgrade of the same path is already running. The path
+/// is tracked in inflight_upgrades until the upgrade finishes or fails.
+fn spawn_upgrade(
+    app: AppHandle,
+    scene_index: usize,
+    page_index: usize,
+    path: String,
+    encoded_key: String,
+    size: u32,
+    rotation: u32,
+) {
+    let state = app.state::<AppState>();
+    if !state.inflight_upgrades.lock().unwrap().insert(path.clone()) {
+        debug!("Upgrade of {} already in progress", path);
+        return;
+    }
+    let inflight = InflightUpgrade {
+        inflight_upgrades: state.inflight_upgrades.clone(),
+        path: path.clone(),
+    };
+    let decode_limit = state.decode_limit.clone();
+
+    tokio::spawn(async move {
+        if let Ok(permit) = decode_limit.acquire_owned().await {
+            let upgraded = decode_pool::run(move || {
+                let _permit = permit;
+                let state = app.state::<AppState>();
+                let (main_image, dimensions, timings) = load_main_image(&path, encoded_key, size, rotation, &state);
+                *state.last_page_timings.lock().unwrap() = Some(timings);
+
+                let Some(main_image) = main_image else { return };
+                let (width, height) = dimensions.unwrap_or_default();
+                let _ = app.emit("image-upgraded", ImageUpgradeEvent {
+                    scene_index,
+                    page_index,
+                    image_path: path,
+                    main_image,
+                    width,
+                    height,
+                    tier: ImageTier::High,
+                });
+            }).await;
+            if let Err(e) = upgraded {
+                warn!("Upgrade of {} failed: {}", inflight.path, e);
+            }
+        }
+        drop(inflight);
+    });
+}
+
+/// Page count of the scene being navigated; a scene without pages is an error
+/// rather than a modulo by zero in the page arithmetic
+fn navigable_page_count(scene: &Scene) -> Result<usize, String> {
+    match scene.page_count() {
+        0 => Err("Scene has no pages".to_string()),
+        count => Ok(count),
+    }
+}
+
+/// Scene count of the collection being navigated; an empty collection is an
+/// error rather than a modulo by zero or an underflow
+fn navigable_scene_count(coll: &SceneCollection) -> Result<usize, String> {
+    match coll.scene_count() {
+        0 => Err("Collection is empty".to_string()),
+        count => Ok(count),
+    }
+}
+
+/// Which way through the book next_page (`next`) or prev_page turns
+///
+/// next_page and prev_page are the right-hand and left-hand page turns. Left
+/// to right, next_page moves forward through the pages and scenes; right to
+/// left (manga) the two swap, so next_page moves backward and prev_page
+/// forward. Either way a command crosses scene and collection boundaries
+/// exactly like the direction it maps to.
+fn page_step(direction: ReadingDirection, next: bool) -> NavigationDirection {
+    match (direction, next) {
+        (ReadingDirection::LeftToRight, true) | (ReadingDirection::RightToLeft, false) => NavigationDirection::Forward,
+        (ReadingDirection::LeftToRight, false) | (ReadingDirection::RightToLeft, true) => NavigationDirection::Backward,
+    }
+}
+
+/// Turn the page in `step` direction through the book
+async fn step_page(step: NavigationDirection, state: State<'_, AppState>, app: AppHandle) -> Result<ImageData, AppError> {
+    match step {
+        NavigationDirection::Forward => page_forward(state, app).await,
+        NavigationDirection::Backward => page_backward(state, app).await,
+    }
+}
+
+/// Navigate to the next page; the previous one in reading order when reading
+/// right to left (see page_step)
+#[tauri::command]
+pub async fn next_page(state: State<'_, AppState>, app: AppHandle) -> Result<ImageData, AppError> {
+    let step = page_step(*state.reading_direction.lock().unwrap(), true);
+    step_page(step, state, app).await
+}
+
+/// Navigate to the previous page; the next one in reading order when reading
+/// right to left (see page_step)
+#[tauri::command]
+pub async fn prev_page(state: State<'_, AppState>, app: AppHandle) -> Result<ImageData, AppError> {
+    let step = page_step(*state.reading_direction.lock().unwrap(), false);
+    step_page(step, state, app).await
+}
+
+/// Move forward one page, on to the next scene after the last page
+async fn page_forward(state: State<'_, AppState>, app: AppHandle) -> Result<ImageData, AppError> {
+    debug!("=== page_forward called ===");
+    let at_end = *state.at_collection_end.lock().unwrap();
+
+    // Decide under the session lock; a scene change is read after releasing it
+    let (scene_index, new_page, to_next_collection, scene_to_load) = {
+        let session = state.session.read().unwrap();
+        let loop_mode = session.loop_mode;
+        let scene_index = session.scene_index;
+        let current_page = session.page_index;
+
+        let Some(scene) = session.scene.as_ref() else {
+            warn!("No scene loaded");
+            return Err(AppError::NoSceneLoaded);
+        };
+        let total_pages = navigable_page_count(scene)?;
+
+        if loop_mode == LoopMode::Scene {
+            // Loop within scene
+            let new_page = (current_page + 1) % total_pages;
+            info!("Scene loop - Current page: {}, Total pages: {}, New page: {}", current_page, total_pages, new_page);
+            (scene_index, new_page, false, None)
+        } else if current_page + 1 < total_pages {
+            let new_page = current_page + 1;
+            info!("Normal navigation - Current page: {}, Total pages: {}, New page: {}", current_page, total_pages, new_page);
+            (scene_index, new_page, false, None)
+        } else {
+            // At last page, transition to next scene at boundary
+            info!("At last page, moving to next scene");
+            let coll = session.collection.as_ref().ok_or(AppError::NoCollectionLoaded)?;
+
+            let scene_count = navigable_scene_count(coll)?;
+            let at_collection_end = loop_mode == LoopMode::None && scene_index + 1 >= scene_count;
+            if at_collection_end && at_end == AtCollectionEnd::Stop {
+                // End of collection without looping: stay on the last page
+                info!("At end of collection, staying on page {}", current_page);
+                let _ = app.emit("collection-end-reached", CollectionEndReached { scene_index, page_index: current_page });
+                (scene_index, current_page, false, None)
+            } else if at_collection_end && at_end == AtCollectionEnd::NextCollection {
+                (scene_index, current_page, true, None)
+            } else {
+                let new_scene_idx = (scene_index + 1) % scene_count;
+                (new_scene_idx, 0, false, Some((coll.clone(), scene_index)))
+            }
+        }
+    };
+
+    let (scene_index, new_page) = match scene_to_load {
+        Some((coll, from)) => {
+            let scene = load_scene_counted(&state, &coll, scene_index)
+                .map_err(|e| AppError::SceneLoadFailed(format!("Failed to load next scene: {}", e)))?;
+
+            let mut session = state.session.write().unwrap();
+            session.check_unchanged(&coll, scene_index, Some(from))?;
+            session.enter_scene(scene_index, scene);
+            info!("Loaded next scene: {}", scene_index);
+            (scene_index, session.page_index)
+        }
+        None => (scene_index, new_page),
+    };
+
+    if to_next_collection {
+        info!("At end of collection, moving to next collection");
+        match load_adjacent_collection(state.clone(), app.clone(), true).await {
+            Ok(_) => {
+                let start_page = state.session.read().unwrap().page_index;
+                let result = get_image(Some(0), start_page, None, state.clone(), app).await;
+                if result.is_ok() {
+                    spawn_preload(&state);
+                }
+                return result;
+            }
+            Err(e) => {
+                info!("No next collection ({}), staying on page {}", e, new_page);
+                let _ = app.emit("collection-end-reached", CollectionEndReached { scene_index, page_index: new_page });
+            }
+        }
+    }
+
+    debug!("Calling get_image with scene_index: {}, page: {}", scene_index, new_page);
+    let result = get_image(Some(scene_index), new_page, None, state.clone(), app).await;
+
+    // Preload next images in background (don't wait for completion)
+    if result.is_ok() {
+        record_navigation(&state, NavigationDirection::Forward);
+        spawn_preload(&state);
+    }
+
+    debug!("=== page_forward completed ===");
+    result
+}
+
+/// Shortest slideshow interval accepted by start_slideshow
+const MIN_SLIDESHOW_INTERVAL_MS: u64 = 100;
+
+/// Replace the slideshow task, aborting the one running before; true if one
+/// was running
+fn set_slideshow_task(state: &AppState, task: Option<tokio::task::JoinHandle<()>>) -> bool {
+    let previous = std::mem::replace(&mut *state.slideshow.lock().unwrap(), task);
+    match previous {
+        Some(previous) if !previous.is_finished() => {
+            previous.abort();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Whether a slideshow task is running
+fn slideshow_running(state: &AppState) -> bool {
+    state.slideshow.lock().unwrap().as_ref().is_some_and(|task| !task.is_finished())
+}
+
+/// Start advancing a page every `interval_ms` milliseconds, replacing any
+/// running slideshow
+///
+/// Each step pages forward in reading order, whatever the reading direction,
+/// with the loop mode and AtCollectionEnd applying as in next_page, and
+/// emits page-changed with the new ImageData. The
+/// slideshow stops by itself when a step fails or no longer moves, e.g. at
+/// the end of the collection.
+#[tauri::command]
+pub async fn start_slideshow(interval_ms: u64, state: State<'_, AppState>, app: AppHandle) -> Result<(), AppError> {
+    if interval_ms < MIN_SLIDESHOW_INTERVAL_MS {
+        return Err(format!("Slideshow interval must be at least {} ms", MIN_SLIDESHOW_INTERVAL_MS).into());
+    }
+
+    let task = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_millis(interval_ms));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick completes immediately; the current page stays for a full interval
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let state = app.state::<AppState>();
+            let before = {
+                let session = state.session.read().unwrap();
+                (session.scene_index, session.page_index)
+            };
+            match page_forward(state, app.clone()).await {
+                Ok(image) if (image.scene_index, image.page_index) == before => {
+                    info!("Slideshow reached the end at scene {}, page {}", image.scene_index, image.page_index);
+                    break;
+                }
+                Ok(image) => {
+                    let _ = app.emit("page-changed", image);
+                }
+                Err(e) => {
+                    warn!("Slideshow stopped: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+
+    if set_slideshow_task(&state, Some(task)) {
+        debug!("Replaced the running slideshow");
+    }
+    info!("Slideshow started, every {} ms", interval_ms);
+    Ok(())
+}
+
+/// Stop the slideshow; returns whether one was running
+#[tauri::command]
+pub async fn stop_slideshow(state: State<'_, AppState>) -> Result<bool, AppError> {
+    let stopped = set_slideshow_task(&state, None);
+    if stopped {
+        info!("Slideshow stopped");
+    }
+    Ok(stopped)
+}
+
+/// Get whether a slideshow is running
+#[tauri::command]
+pub async fn get_slideshow_running(state: State<'_, AppState>) -> Result<bool, AppError> {
+    Ok(slideshow_running(&state))
+}
+
+/// Move back one page, to the last page of the previous scene before the first
+async fn page_backward(state: State<'_, AppState>, app: AppHandle) -> Result<ImageData, AppError> {
+    debug!("=== page_backward called ===");
+
+    // Decide under the session lock; a scene change is read after releasing it
+    let (scene_index, new_page, scene_to_load) = {
+        let session = state.session.read().unwrap();
+        let loop_mode = session.loop_mode;
+        let scene_index = session.scene_index;
+        let current_page = session.page_index;
+
+        let Some(scene) = session.scene.as_ref() else {
+            warn!("No scene loaded");
+            return Err(AppError::NoSceneLoaded);
+        };
+        let total_pages = navigable_page_count(scene)?;
+
+        if loop_mode == LoopMode::Scene {
+            // Loop within scene
+            let new_page = if current_page == 0 {
+                total_pages - 1
+            } else {
+                current_page - 1
+            };
+            info!("Scene loop - Current page: {}, Total pages: {}, New page: {}", current_page, total_pages, new_page);
+            (scene_index, new_page, None)
+        } else if current_page > 0 {
+            let new_page = current_page - 1;
+            info!("Normal navigation - Current page: {}, Total pages: {}, New page: {}", current_page, total_pages, new_page);
+            (scene_index, new_page, None)
+        } else {
+            // At first page, transition to the last page of the previous scene
+            info!("At first page, moving to previous scene");
+            let coll = session.collection.as_ref().ok_or(AppError::NoCollectionLoaded)?;
+
+            if loop_mode == LoopMode::None && scene_index == 0 {
+                // Start of collection without looping: stay on the first page
+                info!("At start of collection, staying on first page");
+                (scene_index, 0, None)
+            } else {
+                let new_scene_idx = if scene_index == 0 {
+                    navigable_scene_count(coll)? - 1
+                } else {
+                    scene_index - 1
+                };
+                (new_scene_idx, 0, Some((coll.clone(), scene_index)))
+            }
+        }
+    };
+
+    let (scene_index, new_page) = match scene_to_load {
+        Some((coll, from)) => {
+            let scene = load_scene_counted(&state, &coll, scene_index)
+                .map_err(|e| AppError::SceneLoadFailed(format!("Failed to load previous scene: {}", e)))?;
+
+            // Get the last page of the previous scene
+            let last_page = scene.page_count().saturating_sub(1);
+
+            let mut session = state.session.write().unwrap();
+            session.check_unchanged(&coll, scene_index, Some(from))?;
+            session.scene = Some(Arc::new(scene));
+            session.scene_index = scene_index;
+            info!("Loaded previous scene: {}, last page: {}", scene_index, last_page);
+            (scene_index, last_page)
+        }
+        None => (scene_index, new_page),
+    };
+
+    debug!("Calling get_image with scene_index: {}, page: {}", scene_index, new_page);
+    let result = get_image(Some(scene_index), new_page, None, state.clone(), app).await;
+
+    // Preload next images in background (don't wait for completion)
+    if result.is_ok() {
+        record_navigation(&state, NavigationDirection::Backward);
+        spawn_preload(&state);
+    }
+
+    debug!("=== prev_page command completed ===");
+    result
+}
+
+/// Remember a page move for predicting the preload direction
+fn record_navigation(state: &AppState, direction: NavigationDirection) {
+    state.navigation_history.lock().unwrap().record(direction);
+}
+
+/// Page offsets from the current page to preload, nearest first: `ahead`
+/// pages in the predicted direction, then `behind` pages the other way
+fn preload_offsets(direction: NavigationDirection, counts: PreloadCounts) -> Vec<isize> {
+    let sign = match direction {
+        NavigationDirection::Forward => 1,
+        NavigationDirection::Backward => -1,
+    };
+    (1..=counts.ahead as isize).map(|i| i * sign)
+        .chain((1..=counts.behind as isize).map(|i| -i * sign))
+        .collect()
+}
+
+/// Pages at `offsets` from `page_index`, wrapping around the scene's first
+/// and last page; the current page and repeats (in scenes shorter than the
+/// offsets) are left out
+fn preload_pages(page_index: usize, total_pages: usize, offsets: &[isize]) -> Vec<usize> {
+    let mut pages = Vec::with_capacity(offsets.len());
+    if total_pages == 0 {
+        return pages;
+    }
+    for offset in offsets {
+        let page = (page_index as isize + offset).rem_euclid(total_pages as isize) as usize;
+        if page != page_index && !pages.contains(&page) {
+            pages.push(page);
+        }
+    }
+    pages
+}
+
+/// Get the direction preloading currently predicts the reader moves in
+#[tauri::command]
+pub async fn get_navigation_direction(state: State<'_, AppState>) -> Result<NavigationDirection, AppError> {
+    Ok(state.navigation_history.lock().unwrap().predicted())
+}
+
+/// Get the page next_page would move to, without moving or decoding anything
+///
+/// None when next_page would stay on the current page at the end of the
+/// collection or move on to the next collection.
+#[tauri::command]
+pub async fn peek_next_page(state: State<'_, AppState>) -> Result<Option<PagePeek>, AppError> {
+    let step = page_step(*state.reading_direction.lock().unwrap(), true);
+    peek_page(&state, step == NavigationDirection::Forward)
+}
+
+/// Get the page prev_page would move to, without moving or decoding anything
+///
+/// None when prev_page would stay on the first page of the collection.
+#[tauri::command]
+pub async fn peek_prev_page(state: State<'_, AppState>) -> Result<Option<PagePeek>, AppError> {
+    let step = page_step(*state.reading_direction.lock().unwrap(), false);
+    peek_page(&state, step == NavigationDirection::Forward)
+}
+
+/// Resolve the page a forward (or backward) page turn moves to with the same
+/// boundary and loop rules, reading only scene files
+fn peek_page(state: &AppState, forward: bool) -> Result<Option<PagePeek>, AppError> {
+    let (scene_index, page_index, loop_mode, scene_count, total_pages) = {
+        let session = state.session.read().unwrap();
+        let scene = session.scene.as_ref().ok_or(AppError::NoSceneLoaded)?;
+        let scene_count = session.collection.as_ref().map_or(Ok(1), navigable_scene_count)?;
+        (session.scene_index, session.page_index, session.loop_mode, scene_count, navigable_page_count(scene)?)
+    };
+
+    let (new_scene, new_page) = if loop_mode == LoopMode::Scene {
+        let page = if forward { (page_index + 1) % total_pages } else { (page_index + total_pages - 1) % total_pages };
+        (scene_index, Some(page))
+    } else if forward && page_index + 1 < total_pages {
+        (scene_index, Some(page_index + 1))
+    } else if !forward && page_index > 0 {
+        (scene_index, Some(page_index - 1))
+    } else if forward {
+        let at_collection_end = loop_mode == LoopMode::None && scene_index + 1 >= scene_count;
+        if at_collection_end && *state.at_collection_end.lock().unwrap() != AtCollectionEnd::Wrap {
+            return Ok(None);
+        }
+        // The start page is only known once the scene is read below
+        ((scene_index + 1) % scene_count, None)
+    } else {
+        if loop_mode == LoopMode::None && scene_index == 0 {
+            return Ok(None);
+        }
+        ((scene_index + scene_count - 1) % scene_count, None)
+    };
+
+    let scene = scene_at(state, new_scene)?;
+    let new_page = new_page.unwrap_or_else(|| {
+        if forward { scene.start_page() } else { scene.page_count().saturating_sub(1) }
+    });
+    Ok(Some(PagePeek {
+        scene_index: new_scene,
+        page_index: new_page,
+        image_path: page_path(&scene, new_page)?,
+    }))
+}
+
+/// Start preloading the pages around the current one in the background,
+/// according to the preload strategy and the predicted navigation direction
+fn spawn_preload(state: &AppState) {
+    let strategy = *state.preload_strategy.lock().unwrap();
+    if strategy == PreloadStrategy::Off {
+        return;
+    }
+
+    let generation = state.preload_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let preload_generation = state.preload_generation.clone();
+    let paused = state.preload_paused.clone();
+    let idle_delay = *state.preload_idle_delay.lock().unwrap();
+    let concurrency = *state.preload_concurrency.lock().unwrap();
+
+    // Clone the Arcs needed for background task
+    let cache = state.cache.clone();
+    let encoded_cache = state.encoded_cache.clone();
+    let session = state.session.clone();
+    let settings = EncodeSettings::from_state(state);
+    let scene_index = state.session.read().unwrap().scene_index;
+    let rotation = scene_rotation(state, scene_index);
+    let counts = *state.preload_counts.lock().unwrap();
+    let offsets = preload_offsets(state.navigation_history.lock().unwrap().predicted(), counts);
+
+    tokio::spawn(async move {
+        if strategy == PreloadStrategy::OnIdle {
+            tokio::time::sleep(idle_delay).await;
+            if preload_generation.load(Ordering::SeqCst) != generation {
+                debug!("Navigation during idle wait, skipping preload");
+                return;
+            }
+        }
+        if paused.load(Ordering::SeqCst) {
+            debug!("Preloading paused, skipping preload");
+            return;
+        }
+        let _ = preload_next_images_task(cache, encoded_cache, session, settings, rotation, offsets, concurrency).await;
+    });
+}
+
+/// Longest sides of the levels of an image pyramid: `largest`, then halved
+/// for each further level, stopping before a level would be empty
+fn pyramid_sizes(largest: u32, levels: u32) -> Vec<u32> {
+    (0..levels.min(u32::BITS))
+        .map(|level| largest >> level)
+        .take_while(|&size| size > 0)
+        .collect()
+}
+
+/// Step `delta` pages (negative for backwards) from a position
+///
+/// Crosses as many scene boundaries as needed, like next_page/prev_page:
+/// `LoopMode::Scene` wraps within the scene, `Collection` wraps around the
+/// collection, and `None` clamps to the first/last page of the collection.
+/// `page_count` returns the number of pages of a scene by index.
+fn resolve_skip<F>(
+    scene_index: usize,
+    page_index: usize,
+    delta: i64,
+    scene_count: usize,
+    loop_mode: LoopMode,
+    mut page_count: F,
+) -> Result<(usize, usize), String>
+where
+    F: FnMut(usize) -> Result<usize, String>,
+{
+    let no_pages = || "No pages to navigate to".to_string();
+
+    if loop_mode == LoopMode::Scene {
+        let count = page_count(scene_index)? as i64;
+        if count == 0 {
+            return Err(no_pages());
+        }
+        return Ok((scene_index, (page_index as i64 + delta).rem_euclid(count) as usize));
+    }
+
+    let wrap = loop_mode == LoopMode::Collection;
+    let mut scene = scene_index;
+    // Page offset relative to the start of `scene`
+    let mut page = page_index as i64 + delta;
+
+    // Walk back while the offset falls before the current scene. After a
+    // full cycle the collection's total page count is known, so the rest of
+    // a large jump can be reduced with a modulo instead of more cycles.
+    let (mut visited, mut cycle_pages) = (0, 0);
+    while page < 0 {
+        if scene == 0 {
+            if !wrap {
+                return Ok((0, 0));
+            }
+            scene = scene_count - 1;
+        } else {
+            scene -= 1;
+        }
+        let count = page_count(scene)? as i64;
+        page += count;
+
+        visited += 1;
+        cycle_pages += count;
+        if visited == scene_count {
+            if cycle_pages == 0 {
+                return Err(no_pages());
+            }
+            page = page.rem_euclid(cycle_pages);
+        }
+    }
+
+    let (mut visited, mut cycle_pages) = (0, 0);
+    loop {
+        let count = page_count(scene)? as i64;
+        if page < count {
+            return Ok((scene, page as usize));
+        }
+        if scene + 1 >= scene_count && !wrap {
+            return Ok((scene, (count - 1).max(0) as usize));
+        }
+        page -= count;
+        scene = (scene + 1) % scene_count;
+
+        visited += 1;
+        cycle_pages += count;
+        if visited == scene_count {
+            if cycle_pages == 0 {
+                return Err(no_pages());
+            }
+            page = page.rem_euclid(cycle_pages);
+        }
+    }
+}
+
+/// Background task to preload next images, decoding up to `concurrency` at once
+async fn preload_next_images_task(
+    cache: Arc<ImageCache>,
+    encoded_cache: Arc<EncodedImageCache>,
+    session: Arc<RwLock<ViewerSession>>,
+    settings: EncodeSettings,
+    rotation: u32,
+    offsets: Vec<isize>,
+    concurrency: usize,
+) -> Result<(), String> {
+    debug!("=== Preloading images at offsets {:?} ===", offsets);
+
+    let (scene, page_index) = {
+        let session = session.read().unwrap();
+        (session.scene.clone(), session.page_index)
+    };
+
+    if let Some(scene) = scene {
+        // Get paths to preload
+        let mut paths_to_load = Vec::new();
+        for next_page in preload_pages(page_index, scene.page_count(), &offsets) {
+            if let Some(path) = scene.get_page_image(next_page) {
+                // Also get thumbnail path, or make one from the main image
+                let thumb_path = scene.get_thumbnail_path(path);
+                let generate_thumbnail = !thumb_path.exists() && settings.generate_thumbnails;
+                let thumb_size = generate_thumbnail.then(|| fallback_thumbnail_size(&scene));
+                paths_to_load.push((path.to_string(), 85, settings.formats.high, settings.high_dimension, thumb_size)); // main image with quality 85
+
+                if thumb_path.exists() {
+                    if let Some(thumb_str) = thumb_path.to_str() {
+                        paths_to_load.push((thumb_str.to_string(), 75, settings.formats.thumbnail, settings.max_dimension, None)); // thumbnail with quality 75
+                    }
+                }
+            }
+        }
+
+        // Load images into cache and encode them
+        let mut tasks = tokio::task::JoinSet::new();
+        for (path, quality, format, size, thumb_size) in paths_to_load {
+            // Skip if already in encoded cache
+            let encoded_key = format_key(&path, rotation, format);
+            let thumb_key = generated_thumbnail_key(&path, rotation, settings.formats.thumbnail);
+            let thumb_size = thumb_size.filter(|_| encoded_cache.get(&thumb_key).is_none());
+            if encoded_cache.get(&encoded_key).is_some() && thumb_size.is_none() {
+                debug!("Already in encoded cache: {}", encoded_key);
+                continue;
+            }
+
+            while tasks.len() >= concurrency.max(1) {
+                tasks.join_next().await;
+            }
+
+            let cache = cache.clone();
+            let encoded_cache = encoded_cache.clone();
+            tasks.spawn(decode_pool::run(move || match load_image_cached_with_size(&path, size, &cache) {
+                Ok(img) => {
+                    debug!("Preloaded to image cache: {}", path);
+                    // Encode and store in encoded cache
+                    match encode_keeping_alpha(&rotate(&img, rotation), format, quality, &settings.jpeg_options) {
+                        Ok(base64) => {
+                            encoded_cache.insert(encoded_key.clone(), base64);
+                            debug!("Encoded and cached: {}", encoded_key);
+                        }
+                        Err(e) => warn!("Failed to encode {}: {}", path, e),
+                    }
+
+                    // Generated thumbnail, shrunk from the decode just made
+                    if let Some((max_width, max_height)) = thumb_size {
+                        let thumb = resize_to_fit(&img, max_width, max_height);
+                        match encode_keeping_alpha(&rotate(&thumb, rotation), settings.formats.thumbnail, 75, &settings.jpeg_options) {
+                            Ok(base64) => {
+                                encoded_cache.insert(thumb_key.clone(), base64);
+                                debug!("Generated thumbnail: {}", thumb_key);
+                            }
+                            Err(e) => warn!("Failed to encode thumbnail of {}: {}", path, e),
+                        }
+                    }
+                }
+                Err(e) => warn!("Failed to preload {}: {}", path, e),
+            }));
+        }
+        while tasks.join_next().await.is_some() {}
+        debug!("=== Preloading completed ===");
+    }
+
+    Ok(())
+}
+
+/// Decode and encode the cover (page 0 thumbnail) of a scene
+///
+/// Uses the thumbnail file if present, otherwise shrinks the main image to
+/// the scene's thumbnail size. Returns the cover cache key and the encoded image.
+fn encode_scene_cover(scene: &Scene, jpeg_options: &JpegOptions) -> Result<(String, String)> {
+    let (main_path, img) = load_scene_cover(scene)?;
+    let (max_width, max_height) = fallback_thumbnail_size(scene);
+    let cover = resize_to_fit(&img, max_width, max_height);
+
+    Ok((main_path.to_string(), image_to_base64_jpeg(&cover, 75, jpeg_options)?))
+}
+
+/// Decode the cover of a scene: page 0's thumbnail file if present, otherwise
+/// page 0 itself. Returns page 0's image path and the decoded cover.
+fn load_scene_cover(scene: &Scene) -> Result<(&str, DynamicImage)> {
+    let main_path = scene.get_page_image(0)
+        .ok_or_else(|| anyhow::anyhow!("Scene {} has no pages", scene.metadata.scene_name))?;
+
+    let thumb_path = scene.get_thumbnail_path(main_path);
+    let img = if thumb_path.exists() {
+        load_image_with_retry(&thumb_path)?
+    } else {
+        load_image_with_retry(main_path)?
+    };
+    Ok((main_path, img))
+}
+
+/// Warm the cover of every scene in the background so switching scenes from a
+/// sidebar doesn't start cold
+///
+/// Returns immediately; emits scene-covers-progress as covers are cached and
+/// stops early if another collection is loaded.
+#[tauri::command]
+pub async fn warm_scene_covers(state: State<'_, AppState>, app: AppHandle) -> Result<(), AppError> {
+    let collection = state.session.read().unwrap().collection.clone()
+        .ok_or(AppError::NoCollectionLoaded)?;
+    let cover_cache = state.cover_cache.clone();
+    let jpeg_options = *state.jpeg_options.lock().unwrap();
+    let load_generation = state.load_generation.clone();
+    let generation = load_generation.load(Ordering::SeqCst);
+    let decode_limit = state.decode_limit.clone();
+    let total = collection.scene_count();
+    let collection = Arc::new(collection);
+
+    tokio::spawn(async move {
+        let mut tasks = tokio::task::JoinSet::new();
+        let mut done = 0;
+
+        for scene_index in 0..total {
+            if load_generation.load(Ordering::SeqCst) != generation {
+                debug!("Collection changed, stopping cover warming");
+                return;
+            }
+
+            // Bound the number of covers decoded at once
+            while tasks.len() >= COVER_WARM_CONCURRENCY {
+                tasks.join_next().await;
+                done += 1;
+                let _ = app.emit("scene-covers-progress", CoverWarmProgress { done, total });
+            }
+
+            let Ok(permit) = decode_limit.clone().acquire_owned().await else { return };
+            let collection = collection.clone();
+            let cover_cache = cover_cache.clone();
+            tasks.spawn(decode_pool::run(move || {
+                let _permit = permit;
+                let result = collection.load_scene(scene_index)
+                    .and_then(|scene| encode_scene_cover(&scene, &jpeg_options));
+                match result {
+                    Ok((key, cover)) => cover_cache.insert(key, cover),
+                    Err(e) => warn!("Failed to warm cover of scene {}: {}", scene_index, e),
+                }
+            }));
+        }
+
+        while tasks.join_next().await.is_some() {
+            done += 1;
+            let _ = app.emit("scene-covers-progress", CoverWarmProgress { done, total });
+        }
+        info!("Warmed {} scene covers", total);
+    });
+
+    Ok(())
+}
+
+/// Get the cover (page 0 thumbnail) of a scene, from the cover cache if warmed
+#[tauri::command]
+pub async fn get_scene_cover(scene_index: usize, state: State<'_, AppState>) -> Result<String, AppError> {
+    let scene = scene_at(&state, scene_index)?;
+    let key = page_path(&scene, 0)?;
+    if let Some(cover) = state.cover_cache.get(&key) {
+        return Ok(cover);
+    }
+
+    let _permit = acquire_decode_permit(&state).await?;
+    let jpeg_options = *state.jpeg_options.lock().unwrap();
+    let (key, cover) = decode_pool::run(move || encode_scene_cover(&scene, &jpeg_options))
+        .await?
+        .map_err(|e| format!("Failed to load cover of scene {}: {}", scene_index, e))?;
+    state.cover_cache.insert(key, cover.clone());
+    Ok(cover)
+}
+
+/// Jump `delta` pages forward (or backward if negative) from the current page
+///
+/// Scene boundaries are handled like next_page/prev_page, possibly crossing
+/// several scenes for a large jump.
+#[tauri::command]
+pub async fn skip_pages(delta: i32, state: State<'_, AppState>, app: AppHandle) -> Result<ImageData, AppError> {
+    let (coll, scene_index, page_index, current_pages, loop_mode) = {
+        let session = state.session.read().unwrap();
+        let current_pages = session.scene.as_ref()
+            .map(|scene| scene.page_count())
+            .ok_or(AppError::NoSceneLoaded)?;
+        let coll = session.collection.clone().ok_or(AppError::NoCollectionLoaded)?;
+        (coll, session.scene_index, session.page_index, current_pages, session.loop_mode)
+    };
+
+    // Scenes whose page count isn't known are read without the session lock
+    let page_count = |index: usize| -> Result<usize, String> {
+        if index == scene_index {
+            return Ok(current_pages);
+        }
+        if let Some(count) = state.scene_page_counts.lock().unwrap().get(&index) {
+            return Ok(*count);
+        }
+        load_scene_counted(&state, &coll, index)
+            .map(|scene| scene.page_count())
+            .map_err(|e| format!("Failed to load scene {}: {}", index, e))
+    };
+    let (new_scene, new_page) = resolve_skip(
+        scene_index, page_index, delta as i64, navigable_scene_count(&coll)?, loop_mode, page_count,
+    )?;
+    state.session.read().unwrap().check_unchanged(&coll, new_scene, Some(scene_index))?;
+    info!("Skipping {} pages: scene {} page {} -> scene {} page {}", delta, scene_index, page_index, new_scene, new_page);
+
+    let result = get_image(Some(new_scene), new_page, None, state.clone(), app).await;
+    if result.is_ok() {
+        if delta != 0 {
+            record_navigation(&state, if delta > 0 { NavigationDirection::Forward } else { NavigationDirection::Backward });
+        }
+        spawn_preload(&state);
+    }
+    result
+}
+
+/// Get the current scene and page indices as one consistent snapshot
+#[tauri::command]
+pub async fn get_position(state: State<'_, AppState>) -> Result<Position, AppError> {
+    let session = state.session.read().unwrap();
+    Ok(Position { scene_index: session.scene_index, page_index: session.page_index })
+}
+
+/// Move to a scene and page in one step and get the page's image
+///
+/// The position is checked before anything changes, then the scene and both
+/// indices are updated together so no other command sees a half-moved state.
+#[tauri::command]
+pub async fn set_position(
+    scene_index: usize,
+    page_index: usize,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ImageData, AppError> {
+    let _permit = acquire_decode_permit(&state).await?;
+
+    // Read the scene without holding the session lock if it isn't current
+    let (coll, switch) = {
+        let session = state.session.read().unwrap();
+        let coll = session.collection.clone().ok_or(AppError::NoCollectionLoaded)?;
+        (coll, session.scene_index != scene_index || session.scene.is_none())
+    };
+    let loaded = if switch {
+        let scene = load_scene_counted(&state, &coll, scene_index)
+            .map_err(|e| AppError::SceneLoadFailed(format!("Failed to load scene {}: {}", scene_index, e)))?;
+        Some(Arc::new(scene))
+    } else {
+        None
+    };
+
+    // Validate and install the position in one lock section, then decode
+    let (scene, rotation) = {
+        let mut session = state.session.write().unwrap();
+        session.check_unchanged(&coll, scene_index, loaded.is_none().then_some(scene_index))?;
+        let Some(scene) = loaded.or_else(|| session.scene.clone()) else {
+            return Err(AppError::NoSceneLoaded);
+        };
+        if page_index >= scene.page_count() {
+            return Err(AppError::PageOutOfBounds { index: page_index, total: scene.page_count() });
+        }
+        session.scene = Some(scene.clone());
+        session.scene_index = scene_index;
+        session.page_index = page_index;
+        let rotation = collection_rotation(&state, session.collection.as_ref(), scene_index);
+        (scene, rotation)
+    };
+    debug!("Position set to scene {} page {}", scene_index, page_index);
+
+    let result = decode_pool::run(move || {
+        page_image(&app.state::<AppState>(), app.clone(), scene, scene_index, page_index, rotation, None)
+    }).await?;
+    if result.is_ok() {
+        spawn_preload(&state);
+    }
+    result
+}
+
+/// Get a `fastviewer://open?...` link to the current collection, scene and page
+#[tauri::command]
+pub async fn get_share_link(state: State<'_, AppState>) -> Result<String, AppError> {
+    let session = state.session.read().unwrap();
+    let collection = session.collection.as_ref().ok_or(AppError::NoCollectionLoaded)?;
+    let link = ShareLink {
+        collection: collection.base_path.clone(),
+        scene_index: session.scene_index,
+        page_index: session.page_index,
+    };
+    Ok(link.to_url())
+}
+
+/// Navigate to the position of a share link and get the page's image
+///
+/// The link's collection is loaded first unless it is the loaded one.
+#[tauri::command]
+pub async fn open_share_link(url: String, state: State<'_, AppState>, app: AppHandle) -> Result<ImageData, AppError> {
+    let link = ShareLink::parse(&url)?;
+    let loaded = state.session.read().unwrap().collection.as_ref()
+        .is_some_and(|coll| coll.base_path == link.collection);
+    if !loaded {
+        let path = link.collection.to_string_lossy().into_owned();
+        load_scene_collection(path.clone(), None, None, state.clone(), app.clone()).await?;
+        if state.session.read().unwrap().collection.as_ref().map(|coll| &coll.base_path) != Some(&link.collection) {
+            return Err(format!("Collection {} has no scenes", path).into());
+        }
+    }
+
+    info!("Opening share link to scene {} page {} of {:?}", link.scene_index, link.page_index, link.collection);
+    set_position(link.scene_index, link.page_index, state, app).await
+}
+
+/// Get list of available scene collections, optionally only `limit` of them
+/// starting at `offset`
+///
+/// With `recursive`, collections in subdirectories are listed too, down to
+/// `max_depth` levels (DEFAULT_COLLECTION_SEARCH_DEPTH by default).
+#[tauri::command]
+pub async fn get_scene_list(
+    parent_dir: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    recursive: Option<bool>,
+    max_depth: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SceneListItem>, AppError> {
+    let config = state.collection_config.lock().unwrap().clone();
+    let collections = if recursive.unwrap_or(false) {
+        let max_depth = max_depth.unwrap_or(DEFAULT_COLLECTION_SEARCH_DEPTH);
+        tokio::task::spawn_blocking(move || {
+            SceneCollection::find_scene_collections_recursive(&parent_dir, &config, max_depth)
+        })
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+    } else {
+        SceneCollection::find_scene_collections(&parent_dir, &config)
+    }
+    .map_err(|e| format!("Failed to find scene collections: {}", e))?;
+
+    let items = collections
+        .iter()
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|path| SceneListItem::from_path(path))
+        .collect();
+
+    Ok(items)
+}
+
+/// Count the scene collections in a parent directory
+#[tauri::command]
+pub async fn count_scene_collections(parent_dir: String, state: State<'_, AppState>) -> Result<usize, AppError> {
+    let config = state.collection_config.lock().unwrap().clone();
+    SceneCollection::count_scene_collections(&parent_dir, &config)
+        .map_err(|e| format!("Failed to count scene collections: {}", e).into())
+}
+
+/// Load the first loadable scene stepping from `from` in one direction,
+/// wrapping around the collection
+///
+/// Unless strict scene loading is on, scenes that fail to load are skipped
+/// (up to MAX_SKIPPED_SCENES) with a scene-load-failed event for each.
+fn load_adjacent_scene(
+    state: &AppState,
+    coll: &SceneCollection,
+    from: usize,
+    forward: bool,
+    app: &AppHandle,
+) -> Result<(usize, Scene), String> {
+    let count = navigable_scene_count(coll)?;
+    let strict = *state.strict_scene_loading.lock().unwrap();
+
+    let mut index = from;
+    let mut last_error = String::new();
+    for _ in 0..(MAX_SKIPPED_SCENES + 1).min(count) {
+        index = if forward {
+            (index + 1) % count
+        } else if index == 0 {
+            count - 1
+        } else {
+            index - 1
+        };
+
+        match load_scene_counted(state, coll, index) {
+            Ok(scene) => return Ok((index, scene)),
+            Err(e) if strict => return Err(format!("Failed to load scene {}: {}", index, e)),
+            Err(e) => {
+                warn!("Skipping scene {} that failed to load: {}", index, e);
+                last_error = e.to_string();
+                let _ = app.emit("scene-load-failed", SceneLoadFailed { index, reason: last_error.clone() });
+            }
+        }
+    }
+
+    Err(format!("No loadable scene found, last error: {}", last_error))
+}
+
+/// Navigate to next scene
+#[tauri::command]
+pub async fn next_scene(state: State<'_, AppState>, app: AppHandle) -> Result<SceneInfo, AppError> {
+    // Read the scene without holding the session lock, then switch to it
+    let (coll, from) = {
+        let session = state.session.read().unwrap();
+        let coll = session.collection.clone().ok_or(AppError::NoCollectionLoaded)?;
+        (coll, session.scene_index)
+    };
+    let (new_index, scene) = load_adjacent_scene(&state, &coll, from, true, &app)
+        .map_err(|e| AppError::SceneLoadFailed(format!("Failed to load next scene: {}", e)))?;
+    {
+        let mut session = state.session.write().unwrap();
+        session.check_unchanged(&coll, new_index, Some(from))?;
+        session.enter_scene(new_index, scene);
+    }
+
+    get_scene_info(state).await
+}
+
+/// Find the first scene of a collection whose name is `name`
+///
+/// Scenes whose file can't be read are skipped.
+fn find_scene_by_name(coll: &SceneCollection, name: &str, ignore_case: bool) -> Option<usize> {
+    let matches = |scene_name: &str| {
+        if ignore_case { scene_name.to_lowercase() == name.to_lowercase() } else { scene_name == name }
+    };
+
+    coll.scene_summaries().iter()
+        .position(|summary| summary.error.is_none() && matches(&summary.name))
+}
+
+/// Name and page count of every scene of the current collection
+///
+/// Read from the scene files on the first call after a collection is loaded;
+/// unsaved edits to the current scene aren't reflected.
+#[tauri::command]
+pub async fn get_scene_summaries(state: State<'_, AppState>) -> Result<Vec<SceneSummary>, AppError> {
+    let coll = state.session.read().unwrap().collection.clone().ok_or(AppError::NoCollectionLoaded)?;
+    // Clones share the summaries, so reading them here fills the session's too
+    let summaries = tokio::task::spawn_blocking(move || coll.scene_summaries().to_vec())
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?;
+    Ok(summaries)
+}
+
+/// Jump to the first page of the first scene named `name`
+///
+/// The match is exact unless `ignore_case` is set.
+#[tauri::command]
+pub async fn jump_to_scene_by_name(name: String, ignore_case: Option<bool>, state: State<'_, AppState>) -> Result<SceneInfo, AppError> {
+    // Look the name up without holding the session lock over file reads
+    let coll = state.session.read().unwrap().collection.clone().ok_or(AppError::NoCollectionLoaded)?;
+    let found = {
+        let (coll, name) = (coll.clone(), name.clone());
+        tokio::task::spawn_blocking(move || find_scene_by_name(&coll, &name, ignore_case.unwrap_or(false)))
+            .await
+            .map_err(|e| format!("Task failed: {}", e))?
+    };
+    let index = found.ok_or_else(|| format!("No scene named {:?}", name))?;
+    let scene = load_scene_counted(&state, &coll, index)
+        .map_err(|e| AppError::SceneLoadFailed(format!("Failed to load scene {}: {}", index, e)))?;
+
+    {
+        let mut session = state.session.write().unwrap();
+        session.check_unchanged(&coll, index, None)?;
+        session.enter_scene(index, scene);
+        session.page_index = 0;
+    }
+    info!("Jumped to scene {} ({:?})", index, name);
+
+    get_scene_info(state).await
+}
+
+/// Navigate to previous scene
+#[tauri::command]
+pub async fn prev_scene(state: State<'_, AppState>, app: AppHandle) -> Result<SceneInfo, AppError> {
+    // Read the scene without holding the session lock, then switch to it
+    let (coll, from) = {
+        let session = state.session.read().unwrap();
+        let coll = session.collection.clone().ok_or(AppError::NoCollectionLoaded)?;
+        (coll, session.scene_index)
+    };
+    let (new_index, scene) = load_adjacent_scene(&state, &coll, from, false, &app)
+        .map_err(|e| AppError::SceneLoadFailed(format!("Failed to load previous scene: {}", e)))?;
+    {
+        let mut session = state.session.write().unwrap();
+        session.check_unchanged(&coll, new_index, Some(from))?;
+        session.enter_scene(new_index, scene);
+    }
+
+    get_scene_info(state).await
+}
+
+/// Get whether scene navigation fails on an unparseable scene
+#[tauri::command]
+pub async fn get_strict_scene_loading(state: State<'_, AppState>) -> Result<bool, AppError> {
+    Ok(*state.strict_scene_loading.lock().unwrap())
+}
+
+/// Set whether scene navigation fails on an unparseable scene instead of
+/// skipping to the next loadable one
+#[tauri::command]
+pub async fn set_strict_scene_loading(enabled: bool, state: State<'_, AppState>) -> Result<(), AppError> {
+    *state.strict_scene_loading.lock().unwrap() = enabled;
+    info!("Strict scene loading {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Get scene loop enabled state (true when the loop mode is Scene)
+#[tauri::command]
+pub async fn get_scene_loop_enabled(state: State<'_, AppState>) -> Result<bool, AppError> {
+    Ok(state.session.read().unwrap().loop_mode == LoopMode::Scene)
+}
+
+/// Set scene loop enabled state (maps to the Scene / None loop modes)
+#[tauri::command]
+pub async fn set_scene_loop_enabled(enabled: bool, state: State<'_, AppState>) -> Result<(), AppError> {
+    state.session.write().unwrap().loop_mode = if enabled { LoopMode::Scene } else { LoopMode::None };
+    Ok(())
+}
+
+/// Get the loop mode
+#[tauri::command]
+pub async fn get_loop_mode(state: State<'_, AppState>) -> Result<LoopMode, AppError> {
+    Ok(state.session.read().unwrap().loop_mode)
+}
+
+/// Set the loop mode
+#[tauri::command]
+pub async fn set_loop_mode(mode: LoopMode, state: State<'_, AppState>) -> Result<(), AppError> {
+    state.session.write().unwrap().loop_mode = mode;
+    info!("Loop mode set to {:?}", mode);
+    Ok(())
+}
+
+/// Get whether get_image returns a quick tier first and upgrades it later
+#[tauri::command]
+pub async fn get_progressive_loading(state: State<'_, AppState>) -> Result<bool, AppError> {
+    Ok(*state.progressive_loading.lock().unwrap())
+}
+
+/// Enable or disable progressive loading (see get_image)
+#[tauri::command]
+pub async fn set_progressive_loading(enabled: bool, state: State<'_, AppState>) -> Result<(), AppError> {
+    *state.progressive_loading.lock().unwrap() = enabled;
+    info!("Progressive loading {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Get the screen size the image tiers are derived from
+#[tauri::command]
+pub async fn get_display_resolution(state: State<'_, AppState>) -> Result<DisplayResolution, AppError> {
+    Ok(*state.display_resolution.lock().unwrap())
+}
+
+/// Set the screen size the image tiers are derived from, dropping previously
+/// encoded images
+///
+/// The high tier fits the longest side of the screen and the preview tier a
+/// third of that, both capped by the max dimension (see set_max_dimension).
+#[tauri::command]
+pub async fn set_display_resolution(width: u32, height: u32, state: State<'_, AppState>) -> Result<(), AppError> {
+    if width == 0 || height == 0 {
+        return Err("Display resolution must be greater than zero".into());
+    }
+    let resolution = DisplayResolution {
+        width: width.min(MAX_TARGET_SIZE),
+        height: height.min(MAX_TARGET_SIZE),
+    };
+    let mut current = state.display_resolution.lock().unwrap();
+    if *current != resolution {
+        *current = resolution;
+        // Main images are encoded under their path alone, whatever the size
+        state.encoded_cache.clear();
+        info!(
+            "Display resolution set to {}x{} (high {}, preview {})",
+            resolution.width, resolution.height, resolution.high_dimension(), resolution.preview_dimension()
+        );
+    }
+    Ok(())
+}
+
+/// Get the longest side any resized decode may have
+#[tauri::command]
+pub async fn get_max_dimension(state: State<'_, AppState>) -> Result<u32, AppError> {
+    Ok(*state.max_dimension.lock().unwrap())
+}
+
+/// Change the max dimension, dropping the decodes cached at the old size and
+/// the encoded images made from them
+///
+/// Decodes at the old size are kept when it is still a tier size, e.g. the
+/// high tier of a 1920px screen when the max dimension goes from 1920 up.
+fn update_max_dimension(state: &AppState, dimension: u32) {
+    let resolution = *state.display_resolution.lock().unwrap();
+    let mut current = state.max_dimension.lock().unwrap();
+    if *current != dimension {
+        let (high, preview) = resolution.tier_dimensions(dimension);
+        let removed = if *current == high || *current == preview {
+            0
+        } else {
+            state.cache.invalidate_size(*current)
+        };
+        // Encoded keys don't carry the size they were made at
+        state.encoded_cache.clear();
+        info!("Max dimension set to {} (dropped {} decodes at {})", dimension, removed, *current);
+        *current = dimension;
+    }
+}
+
+/// Set the longest side any resized decode may have, e.g. higher for 4K
+/// screens or lower to save memory
+///
+/// Caps the high and preview tiers sized by set_display_resolution, and sizes
+/// thumbnails and other non-tier decodes (diffs, spreads, trimmed pages).
+#[tauri::command]
+pub async fn set_max_dimension(dimension: u32, state: State<'_, AppState>) -> Result<(), AppError> {
+    if dimension == 0 {
+        return Err("Max dimension must be greater than zero".into());
+    }
+    update_max_dimension(&state, dimension.min(MAX_TARGET_SIZE));
+    Ok(())
+}
+
+/// Get whether a background upgrade (see get_image) is running for an image path
+#[tauri::command]
+pub async fn is_upgrade_in_progress(path: String, state: State<'_, AppState>) -> Result<bool, AppError> {
+    Ok(state.inflight_upgrades.lock().unwrap().contains(&path))
+}
+
+/// Get the image paths of all running background upgrades
+#[tauri::command]
+pub async fn get_inflight_upgrades(state: State<'_, AppState>) -> Result<Vec<String>, AppError> {
+    let mut paths: Vec<String> = state.inflight_upgrades.lock().unwrap().iter().cloned().collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Evict least recently used images until the caches fit within `limit`
+/// bytes, encoded images first since they are cheaper to rebuild
+///
+/// Pinned entries (the current page) are kept even if that leaves the caches
+/// over the limit. Returns the memory held afterwards.
+fn trim_caches(state: &AppState, limit: usize) -> CacheMemory {
+    let mut memory = CacheMemory::of(state);
+    if memory.total() <= limit {
+        return memory;
+    }
+
+    let before = memory.total();
+    while memory.total() > limit
+        && (state.encoded_cache.evict_one() || state.cover_cache.evict_one() || state.cache.evict_one())
+    {
+        memory = CacheMemory::of(state);
+    }
+    info!("Trimmed caches from {} to {} bytes (limit {})", before, memory.total(), limit);
+    memory
+}
+
+/// Periodically trim the caches while a memory limit is set
+///
+/// Runs for the lifetime of the app on its own thread.
+pub fn spawn_memory_watchdog(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(MEMORY_CHECK_INTERVAL);
+        let state = app.state::<AppState>();
+        let limit = *state.memory_limit.lock().unwrap();
+        if let Some(limit) = limit {
+            trim_caches(&state, limit);
+        }
+    });
+}
+
+/// Get the memory limit of the image caches in megabytes, if any
+#[tauri::command]
+pub async fn get_memory_limit_mb(state: State<'_, AppState>) -> Result<Option<u64>, AppError> {
+    Ok(state.memory_limit.lock().unwrap().map(|bytes| (bytes / (1024 * 1024)) as u64))
+}
+
+/// Set the memory limit of the image caches in megabytes, or remove it with
+/// None
+///
+/// While set, the caches are checked every few seconds and trimmed back under
+/// the limit, on top of their fixed entry counts.
+#[tauri::command]
+pub async fn set_memory_limit_mb(limit: Option<u64>, state: State<'_, AppState>) -> Result<(), AppError> {
+    if limit == Some(0) {
+        return Err("Memory limit must be greater than zero".into());
+    }
+    let bytes = limit.map(|mb| (mb as usize).saturating_mul(1024 * 1024));
+    *state.memory_limit.lock().unwrap() = bytes;
+    match limit {
+        Some(mb) => info!("Cache memory limit set to {} MB", mb),
+        None => info!("Cache memory limit removed"),
+    }
+    Ok(())
+}
+
+/// Trim the caches now: down to the memory limit if one is set, otherwise
+/// empty them except for pinned entries. Returns the memory held afterwards.
+#[tauri::command]
+pub async fn trim_memory(state: State<'_, AppState>) -> Result<CacheMemory, AppError> {
+    let limit = state.memory_limit.lock().unwrap().unwrap_or(0);
+    Ok(trim_caches(&state, limit))
+}
+
+/// Get the entry counts and hit/miss counters of the image caches, e.g. for
+/// a debug overlay. The counters run from app start.
+#[tauri::command]
+pub async fn get_cache_stats(state: State<'_, AppState>) -> Result<CacheStats, AppError> {
+    Ok(CacheStats {
+        image_cache_size: state.cache.size(),
+        image_cache_capacity: state.cache.capacity(),
+        image_cache_hits: state.cache.hits(),
+        image_cache_misses: state.cache.misses(),
+        encoded_cache_size: state.encoded_cache.size(),
+        encoded_cache_capacity: state.encoded_cache.capacity(),
+        encoded_cache_hits: state.encoded_cache.hits(),
+        encoded_cache_misses: state.encoded_cache.misses(),
+    })
+}
+
+/// Empty the encoded cache, and the decoded cache too unless `encoded_only`
+fn clear_image_caches(state: &AppState, encoded_only: bool) -> ClearedCaches {
+    let mut cleared = ClearedCaches {
+        entries: state.encoded_cache.size(),
+        bytes: state.encoded_cache.memory_bytes(),
+    };
+    state.encoded_cache.clear();
+    if !encoded_only {
+        cleared.entries += state.cache.size();
+        cleared.bytes += state.cache.memory_bytes();
+        state.cache.clear();
+    }
+    cleared
+}
+
+/// Free the memory of the image caches, e.g. after leaving a large
+/// collection; returns the entries and bytes released
+///
+/// With `encoded_only`, decoded images are kept since they cost more to
+/// rebuild than their encodings.
+#[tauri::command]
+pub async fn clear_caches(encoded_only: Option<bool>, state: State<'_, AppState>) -> Result<ClearedCaches, AppError> {
+    let cleared = clear_image_caches(&state, encoded_only.unwrap_or(false));
+    info!("Cleared {} cache entries, {} bytes", cleared.entries, cleared.bytes);
+    Ok(cleared)
+}
+
+/// Get whether generated thumbnails are kept on disk across restarts
+#[tauri::command]
+pub async fn get_disk_thumbnail_cache(state: State<'_, AppState>) -> Result<bool, AppError> {
+    Ok(state.disk_thumbnails.lock().unwrap().is_some())
+}
+
+/// Enable or disable keeping generated thumbnails (see ThumbnailFallback) in
+/// the app cache directory, so they aren't decoded again after a restart
+#[tauri::command]
+pub async fn set_disk_thumbnail_cache(enabled: bool, state: State<'_, AppState>, app: AppHandle) -> Result<(), AppError> {
+    let cache = if enabled {
+        let dir = storage::cache_dir(&app, DISK_THUMBNAIL_DIR).map_err(|e| format!("{:#}", e))?;
+        Some(DiskThumbnailCache::new(dir))
+    } else {
+        None
+    };
+    *state.disk_thumbnails.lock().unwrap() = cache;
+    info!("Disk thumbnail cache {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Delete all thumbnails stored on disk, returning the number of bytes freed
+#[tauri::command]
+pub async fn clear_disk_thumbnail_cache(app: AppHandle) -> Result<u64, AppError> {
+    let dir = storage::cache_dir(&app, DISK_THUMBNAIL_DIR).map_err(|e| format!("{:#}", e))?;
+    let freed = DiskThumbnailCache::new(dir).clear()
+        .map_err(|e| format!("Failed to clear disk thumbnail cache: {:#}", e))?;
+    info!("Cleared disk thumbnail cache, freed {} bytes", freed);
+    Ok(freed)
+}
+
+/// Get whether decoded images are cached in addition to encoded ones
+#[tauri::command]
+pub async fn get_cache_decoded(state: State<'_, AppState>) -> Result<bool, AppError> {
+    Ok(state.cache.cache_decoded())
+}
+
+/// Enable or disable the decoded image cache; disabling it roughly halves
+/// memory use when pages are only viewed, not re-resized
+#[tauri::command]
+pub async fn set_cache_decoded(enabled: bool, state: State<'_, AppState>) -> Result<(), AppError> {
+    state.cache.set_cache_decoded(enabled);
+    info!("Decoded image cache {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Get the preload strategy
+#[tauri::command]
+pub async fn get_preload_strategy(state: State<'_, AppState>) -> Result<PreloadStrategy, AppError> {
+    Ok(*state.preload_strategy.lock().unwrap())
+}
+
+/// Set the preload strategy, and optionally the idle delay used by OnIdle
+#[tauri::command]
+pub async fn set_preload_strategy(
+    strategy: PreloadStrategy,
+    idle_delay_ms: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    *state.preload_strategy.lock().unwrap() = strategy;
+    if let Some(ms) = idle_delay_ms {
+        *state.preload_idle_delay.lock().unwrap() = Duration::from_millis(ms);
+    }
+    info!("Preload strategy set to {:?} (idle delay {:?})", strategy, *state.preload_idle_delay.lock().unwrap());
+    Ok(())
+}
+
+/// Get whether preloading is paused
+#[tauri::command]
+pub async fn get_preload_paused(state: State<'_, AppState>) -> Result<bool, AppError> {
+    Ok(state.preload_paused.load(Ordering::SeqCst))
+}
+
+/// Pause or resume preloading; while paused navigation works as usual but
+/// doesn't warm the neighboring pages
+#[tauri::command]
+pub async fn set_preload_paused(paused: bool, state: State<'_, AppState>) -> Result<(), AppError> {
+    state.preload_paused.store(paused, Ordering::SeqCst);
+    info!("Preloading {}", if paused { "paused" } else { "resumed" });
+    Ok(())
+}
+
+/// Get the number of threads background decoding runs on
+#[tauri::command]
+pub async fn get_decode_threads() -> Result<usize, AppError> {
+    Ok(decode_pool::threads())
+}
+
+/// Set the number of threads background decoding (preloading, upgrades,
+/// cover warming, exports) runs on, separate from the async runtime's
+/// blocking pool
+#[tauri::command]
+pub async fn set_decode_threads(count: usize) -> Result<(), AppError> {
+    if count == 0 {
+        return Err("Decode thread count must be at least 1".into());
+    }
+    Ok(decode_pool::set_threads(count)?)
+}
+
+/// Get the number of images preloading decodes at the same time
+#[tauri::command]
+pub async fn get_preload_concurrency(state: State<'_, AppState>) -> Result<usize, AppError> {
+    Ok(*state.preload_concurrency.lock().unwrap())
+}
+
+/// Set the number of images preloading decodes at the same time
+///
+/// Defaults to the number of CPUs; takes effect from the next preload.
+#[tauri::command]
+pub async fn set_preload_concurrency(count: usize, state: State<'_, AppState>) -> Result<(), AppError> {
+    if count == 0 {
+        return Err("Preload concurrency must be at least 1".into());
+    }
+    *state.preload_concurrency.lock().unwrap() = count;
+    info!("Preload concurrency set to {}", count);
+    Ok(())
+}
+
+/// Get how many pages are preloaded ahead of and behind the current page
+#[tauri::command]
+pub async fn get_preload_counts(state: State<'_, AppState>) -> Result<PreloadCounts, AppError> {
+    Ok(*state.preload_counts.lock().unwrap())
+}
+
+/// Set how many pages are preloaded in the predicted reading direction
+/// (`ahead`) and the other way (`behind`); takes effect from the next preload
+#[tauri::command]
+pub async fn set_preload_counts(ahead: usize, behind: usize, state: State<'_, AppState>) -> Result<(), AppError> {
+    *state.preload_counts.lock().unwrap() = PreloadCounts { ahead, behind };
+    info!("Preloading {} pages ahead and {} behind", ahead, behind);
+    Ok(())
+}
+
+/// Get the encode format of each image tier
+#[tauri::command]
+pub async fn get_tier_formats(state: State<'_, AppState>) -> Result<TierFormats, AppError> {
+    Ok(*state.tier_formats.lock().unwrap())
+}
+
+/// Set the encode format of each image tier
+///
+/// Already encoded images stay cached under their own format's keys.
+#[tauri::command]
+pub async fn set_tier_formats(formats: TierFormats, state: State<'_, AppState>) -> Result<(), AppError> {
+    *state.tier_formats.lock().unwrap() = formats;
+    info!("Tier formats set to {:?}", formats);
+    Ok(())
+}
+
+/// Get what is used as the thumbnail of pages without a thumbnail file
+#[tauri::command]
+pub async fn get_thumbnail_fallback(state: State<'_, AppState>) -> Result<ThumbnailFallback, AppError> {
+    Ok(state.thumbnail_fallback.lock().unwrap().clone())
+}
+
+/// Set what is used as the thumbnail of pages without a thumbnail file
+#[tauri::command]
+pub async fn set_thumbnail_fallback(mode: ThumbnailFallback, state: State<'_, AppState>) -> Result<(), AppError> {
+    if let ThumbnailFallback::Placeholder(path) = &mode {
+        if !Path::new(path).is_file() {
+            return Err(format!("Placeholder image not found: {}", path).into());
+        }
+    }
+    info!("Thumbnail fallback set to {:?}", mode);
+    *state.thumbnail_fallback.lock().unwrap() = mode;
+    Ok(())
+}
+
+/// Measure how long a page takes to load with nothing cached
+///
+/// Drops the page's decoded and encoded cache entries, then times the quick
+/// tier and the high tier exactly as progressive loading produces them. The
+/// current page doesn't change.
+#[tauri::command]
+pub async fn benchmark_cold_page(
+    scene_index: usize,
+    page_index: usize,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<BenchmarkResult, AppError> {
+    let scene = scene_at(&state, scene_index)?;
+    let path = page_path(&scene, page_index)?;
+    let rotation = scene_rotation(&state, scene_index);
+    let max_dimension = *state.max_dimension.lock().unwrap();
+    let (high_dimension, preview_dimension) = state.display_resolution.lock().unwrap().tier_dimensions(max_dimension);
+
+    let _permit = acquire_decode_permit(&state).await?;
+    decode_pool::run(move || {
+        let state = app.state::<AppState>();
+        let dropped = state.cache.invalidate(&path) + state.encoded_cache.invalidate(&path);
+        debug!("Benchmarking {} cold ({} cache entries dropped)", path, dropped);
+
+        let start = Instant::now();
+        let (preview, _, tier) = load_quick_image(&path, rotation, preview_dimension, &state)
+            .ok_or_else(|| format!("Failed to load preview of {}", path))?;
+        let preview_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        let start = Instant::now();
+        let (highres, _, _) = load_main_image(&path, path.clone(), high_dimension, rotation, &state);
+        let highres = highres.ok_or_else(|| format!("Failed to load {}", path))?;
+        let highres_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        let result = BenchmarkResult {
+            preview_ms,
+            highres_ms,
+            preview_bytes: preview.len(),
+            highres_bytes: highres.len(),
+        };
+        info!("Cold page {}: {:?} tier in {:.1}ms, high tier in {:.1}ms", path, tier, preview_ms, highres_ms);
+        Ok(result)
+    }).await?
+}
+
+/// Get decode/resize/encode timings for the last page returned by get_image
+#[tauri::command]
+pub async fn get_last_page_timings(state: State<'_, AppState>) -> Result<Option<PageTimings>, AppError> {
+    Ok(*state.last_page_timings.lock().unwrap())
+}
+
+/// Set the log level at runtime ("off", "error", "warn", "info", "debug" or "trace")
+#[tauri::command]
+pub async fn set_log_level(level: String) -> Result<(), AppError> {
+    let filter: log::LevelFilter = level
+        .parse()
+        .map_err(|_| format!("Invalid log level: {}", level))?;
+    log::set_max_level(filter);
+    info!("Log level set to {}", filter);
+    Ok(())
+}
+
+/// Get the naming patterns used to recognize scene files and collections
+#[tauri::command]
+pub async fn get_collection_config(state: State<'_, AppState>) -> Result<SceneCollectionConfig, AppError> {
+    Ok(state.collection_config.lock().unwrap().clone())
+}
+
+/// Set the naming patterns used to recognize scene files and collections
+///
+/// Takes effect on the next load_scene_collection / get_scene_list.
+#[tauri::command]
+pub async fn set_collection_config(config: SceneCollectionConfig, state: State<'_, AppState>) -> Result<(), AppError> {
+    if config.file_ext.is_empty() {
+        return Err("Scene file extension must not be empty".into());
+    }
+    info!("Collection config set to {:?}", config);
+    *state.collection_config.lock().unwrap() = config;
+    Ok(())
+}
+
+/// Get the longest side any decoded image may have
+#[tauri::command]
+pub async fn get_max_image_side() -> Result<u32, AppError> {
+    Ok(image_loader::max_side())
+}
+
+/// Set the longest side any decoded image may have, even with resizing
+/// disabled; larger sources are scaled down and flagged clamped_to_max_side
+#[tauri::command]
+pub async fn set_max_image_side(side: u32, state: State<'_, AppState>) -> Result<(), AppError> {
+    if side == 0 {
+        return Err("Max image side must be at least 1".into());
+    }
+    if image_loader::max_side() != side {
+        image_loader::set_max_side(side);
+        // Cache keys carry the requested size, not the limit applied to it
+        state.cache.clear();
+        state.encoded_cache.clear();
+        info!("Max image side set to {}", side);
+    }
+    Ok(())
+}
+
+/// Get whether decoded images are shrunk to the display resolution or
+/// requested size
+#[tauri::command]
+pub async fn get_resize_enabled() -> Result<bool, AppError> {
+    Ok(image_loader::resize_enabled())
+}
+
+/// Enable or disable shrinking decoded images, dropping previously cached ones
+///
+/// With resizing disabled every page is shown with its original pixels (up to
+/// the max side limit), for lossless workflows; get_image then warns about
+/// very large pages. Memory is best bounded with set_memory_limit_mb in this
+/// mode.
+#[tauri::command]
+pub async fn set_resize_enabled(enabled: bool, state: State<'_, AppState>) -> Result<(), AppError> {
+    if image_loader::resize_enabled() != enabled {
+        image_loader::set_resize_enabled(enabled);
+        // Cache keys carry the requested size, not whether it was applied
+        state.cache.clear();
+        state.encoded_cache.clear();
+        info!("Resizing {}", if enabled { "enabled" } else { "disabled" });
+    }
+    Ok(())
+}
+
+/// Set how many times an image load is retried after a transient I/O error
+#[tauri::command]
+pub async fn set_load_retries(count: u32) -> Result<(), AppError> {
+    image_loader::set_load_retries(count);
+    info!("Load retries set to {}", count);
+    Ok(())
+}
+
+/// Get the JPEG encoder settings
+#[tauri::command]
+pub async fn get_jpeg_options(state: State<'_, AppState>) -> Result<JpegOptions, AppError> {
+    Ok(*state.jpeg_options.lock().unwrap())
+}
+
+/// Set the JPEG encoder settings, dropping previously encoded images
+#[tauri::command]
+pub async fn set_jpeg_options(options: JpegOptions, state: State<'_, AppState>) -> Result<(), AppError> {
+    let mut current = state.jpeg_options.lock().unwrap();
+    if *current != options {
+        *current = options;
+        state.encoded_cache.clear();
+        state.cover_cache.clear();
+        info!("JPEG options set to {:?}", options);
+    }
+    Ok(())
+}
+
+/// Compare two pages and return a base64 PNG overlay with changes in red
+#[tauri::command]
+pub async fn diff_pages(
+    scene_a: usize,
+    page_a: usize,
+    scene_b: usize,
+    page_b: usize,
+    state: State<'_, AppState>,
+) -> Result<String, AppError> {
+    let _permit = acquire_decode_permit(&state).await?;
+    let path_a = page_path(&*scene_at(&state, scene_a)?, page_a)?;
+    let path_b = page_path(&*scene_at(&state, scene_b)?, page_b)?;
+    info!("Diffing {} against {}", path_a, path_b);
+
+    let max_dimension = *state.max_dimension.lock().unwrap();
+    let cache = state.cache.clone();
+    decode_pool::run(move || {
+        let img_a = load_image_cached_with_size(&path_a, max_dimension, &cache)
+            .map_err(|e| AppError::ImageDecodeFailed(format!("Failed to load image: {}", e)))?;
+        let img_b = load_image_cached_with_size(&path_b, max_dimension, &cache)
+            .map_err(|e| AppError::ImageDecodeFailed(format!("Failed to load image: {}", e)))?;
+
+        let diff = diff_images(&img_a, &img_b);
+        image_to_base64_png(&diff).map_err(|e| format!("Failed to encode diff: {}", e).into())
+    }).await?
+}
+
+/// Get the page count of a scene, parsing only that scene if not yet known
+#[tauri::command]
+pub async fn get_scene_page_count(scene_index: usize, state: State<'_, AppState>) -> Result<usize, AppError> {
+    if let Some(count) = state.scene_page_counts.lock().unwrap().get(&scene_index) {
+        return Ok(*count);
+    }
+
+    Ok(scene_at(&state, scene_index)?.page_count())
+}
+
+/// Get recently opened collections, most recent first
+#[tauri::command]
+pub async fn get_recent_collections(app: AppHandle) -> Result<Vec<RecentItem>, AppError> {
+    let entries = recent::load_recent(&app)
+        .map_err(|e| format!("Failed to load recent collections: {}", e))?;
+    Ok(entries.into_iter().map(RecentItem::from).collect())
+}
+
+/// Clear the recent collections list
+#[tauri::command]
+pub async fn clear_recent_collections(app: AppHandle) -> Result<(), AppError> {
+    recent::clear_recent(&app)
+        .map_err(|e| format!("Failed to clear recent collections: {}", e).into())
+}
+
+/// Get the title of every page in a scene, for a table of contents
+#[tauri::command]
+pub async fn get_page_titles(scene_index: usize, state: State<'_, AppState>) -> Result<Vec<Option<String>>, AppError> {
+    Ok(scene_at(&state, scene_index)?.page_titles())
+}
+
+/// Get every thumbnail of a scene packed into a single sprite sheet
+///
+/// Pages without a thumbnail file follow the thumbnail fallback, leaving an
+/// empty cell when there is none. Images are read directly rather than
+/// through the image cache so a large scene doesn't evict the pages being
+/// viewed.
+#[tauri::command]
+pub async fn get_thumbnail_sprite(
+    scene_index: usize,
+    cols: u32,
+    cell_size: u32,
+    state: State<'_, AppState>,
+) -> Result<SpriteSheet, AppError> {
+    if cols == 0 || cell_size == 0 {
+        return Err("Columns and cell size must be greater than zero".into());
+    }
+    let _permit = acquire_decode_permit(&state).await?;
+
+    let scene = scene_at(&state, scene_index)?;
+    let count = scene.page_count();
+    if count == 0 {
+        return Err(format!("Scene {} has no pages", scene_index).into());
+    }
+    let rows = sprite_rows(count, cols);
+    if cols as u64 * cell_size as u64 > MAX_SPRITE_DIMENSION as u64
+        || rows as u64 * cell_size as u64 > MAX_SPRITE_DIMENSION as u64
+    {
+        return Err(format!(
+            "Sprite sheet of {}x{} cells of {}px exceeds {}px",
+            cols, rows, cell_size, MAX_SPRITE_DIMENSION
+        ).into());
+    }
+
+    let fallback = state.thumbnail_fallback.lock().unwrap().clone();
+    let jpeg_options = *state.jpeg_options.lock().unwrap();
+    let image = decode_pool::run(move || {
+        // Shrink each cell as it is decoded; a source may be a full-size page
+        let images: Vec<_> = scene.pages.iter()
+            .map(|page| {
+                let (path, _) = thumbnail_source(&scene, &page.image, &fallback)?;
+                load_image_with_retry(&path)
+                    .map(|img| resize_to_fit(&img, cell_size, cell_size))
+                    .map_err(|e| warn!("Failed to load sprite cell {:?}: {}", path, e))
+                    .ok()
+            })
+            .collect();
+
+        let atlas = pack_sprite_sheet(&images, cols, cell_size);
+        image_to_base64_jpeg(&atlas, 80, &jpeg_options)
+            .map_err(|e| format!("Failed to encode sprite sheet: {}", e))
+    }).await??;
+    debug!("Built {}x{} sprite sheet for scene {} ({} pages)", cols, rows, scene_index, count);
+
+    Ok(SpriteSheet { image, cols, rows, cell_size, count })
+}
+
+/// Get the cover of every scene in the collection packed into a single sprite
+/// sheet, for a library overview
+///
+/// Scenes whose cover fails to load leave an empty cell. Covers are decoded a
+/// few at a time, bypassing the image cache like get_thumbnail_sprite.
+#[tauri::command]
+pub async fn get_collection_montage(
+    cols: u32,
+    cell_size: u32,
+    state: State<'_, AppState>,
+) -> Result<SpriteSheet, AppError> {
+    if cols == 0 || cell_size == 0 {
+        return Err("Columns and cell size must be greater than zero".into());
+    }
+    let collection = state.session.read().unwrap().collection.clone()
+        .ok_or(AppError::NoCollectionLoaded)?;
+    let count = collection.scene_count();
+    if count == 0 {
+        return Err("Collection has no scenes".into());
+    }
+    let rows = sprite_rows(count, cols);
+    if cols as u64 * cell_size as u64 > MAX_SPRITE_DIMENSION as u64
+        || rows as u64 * cell_size as u64 > MAX_SPRITE_DIMENSION as u64
+    {
+        return Err(format!(
+            "Montage of {}x{} cells of {}px exceeds {}px",
+            cols, rows, cell_size, MAX_SPRITE_DIMENSION
+        ).into());
+    }
+
+    let collection = Arc::new(collection);
+    let mut images: Vec<Option<DynamicImage>> = vec![None; count];
+    run_bounded(0..count, MONTAGE_CONCURRENCY, |tasks, scene_index| {
+        let collection = collection.clone();
+        let decode_limit = state.decode_limit.clone();
+        let session = state.session.clone();
+        let scene_page_counts = state.scene_page_counts.clone();
+        tasks.spawn(async move {
+            let Ok(permit) = decode_limit.acquire_owned().await else { return (scene_index, None) };
+            let cover = decode_pool::run(move || {
+                let _permit = permit;
+                collection.load_scene(scene_index).and_then(|scene| {
+                    record_page_count(&session, &scene_page_counts, &collection, scene_index, scene.page_count());
+                    let (_, img) = load_scene_cover(&scene)?;
+                    Ok(resize_to_fit(&img, cell_size, cell_size))
+                })
+            }).await;
+            match cover {
+                Ok(Ok(img)) => (scene_index, Some(img)),
+                Ok(Err(e)) => {
+                    warn!("Failed to load montage cover of scene {}: {}", scene_index, e);
+                    (scene_index, None)
+                }
+                Err(e) => {
+                    warn!("Failed to load montage cover of scene {}: {}", scene_index, e);
+                    (scene_index, None)
+                }
+            }
+        });
+    }, |result| {
+        if let Ok((scene_index, img)) = result {
+            images[scene_index] = img;
+        }
+        Ok(())
+    }).await?;
+
+    let jpeg_options = *state.jpeg_options.lock().unwrap();
+    let image = decode_pool::run(move || {
+        let atlas = pack_sprite_sheet(&images, cols, cell_size);
+        image_to_base64_jpeg(&atlas, 80, &jpeg_options)
+            .map_err(|e| format!("Failed to encode montage: {}", e))
+    }).await??;
+    debug!("Built {}x{} collection montage ({} scenes)", cols, rows, count);
+
+    Ok(SpriteSheet { image, cols, rows, cell_size, count })
+}
+
+/// Get a scene's table of contents: every page's title and a thumbnail that
+/// fits within `thumb_size`
+///
+/// Thumbnails follow the thumbnail fallback and are decoded a few at a time,
+/// bypassing the image caches like get_thumbnail_sprite.
+#[tauri::command]
+pub async fn get_table_of_contents(
+    scene_index: usize,
+    thumb_size: u32,
+    state: State<'_, AppState>,
+) -> Result<Vec<TocEntry>, AppError> {
+    if thumb_size == 0 {
+        return Err("Thumbnail size must be greater than zero".into());
+    }
+    let scene = scene_at(&state, scene_index)?;
+    let rotation = scene_rotation(&state, scene_index);
+    let fallback = state.thumbnail_fallback.lock().unwrap().clone();
+    let jpeg_options = *state.jpeg_options.lock().unwrap();
+
+    let mut entries: Vec<TocEntry> = scene.pages.iter().enumerate()
+        .map(|(page_index, page)| TocEntry { page_index, title: page.title.clone(), thumbnail: None })
+        .collect();
+
+    let sources = scene.pages.iter().enumerate().filter_map(|(page_index, page)| {
+        thumbnail_source(&scene, &page.image, &fallback).map(|(source, _)| (page_index, source))
+    });
+    run_bounded(sources, TOC_CONCURRENCY, |tasks, (page_index, source)| {
+        let decode_limit = state.decode_limit.clone();
+        tasks.spawn(async move {
+            let Ok(permit) = decode_limit.acquire_owned().await else { return (page_index, None) };
+            let thumbnail = decode_pool::run(move || {
+                let _permit = permit;
+                load_image_with_retry(&source)
+                    .map(|img| rotate(&resize_to_fit(&img, thumb_size, thumb_size), rotation))
+                    .and_then(|img| image_to_base64_jpeg(&img, 75, &jpeg_options))
+                    .map_err(|e| warn!("Failed to load TOC thumbnail {:?}: {}", source, e))
+                    .ok()
+            }).await;
+            (page_index, thumbnail.unwrap_or_else(|e| {
+                warn!("Failed to load TOC thumbnail of page {}: {}", page_index, e);
+                None
+            }))
+        });
+    }, |result| {
+        if let Ok((page_index, thumbnail)) = result {
+            entries[page_index].thumbnail = thumbnail;
+        }
+        Ok(())
+    }).await?;
+
+    Ok(entries)
+}
+
+/// Get whether a scene's pages are mostly portrait, mostly landscape or mixed
+///
+/// Reads the headers of up to LAYOUT_SAMPLE_PAGES evenly spaced pages in
+/// parallel, falling back to the scene's metadata.imageSize for pages whose
+/// header can't be read. Cached per scene file and rotation.
+#[tauri::command]
+pub async fn get_scene_layout_hint(scene_index: usize, state: State<'_, AppState>) -> Result<LayoutHint, AppError> {
+    let rotation = scene_rotation(&state, scene_index);
+    let key = rotated_key(&scene_file_path(&state, scene_index)?.to_string_lossy(), rotation);
+    if let Some(hint) = state.layout_hints.lock().unwrap().get(&key) {
+        return Ok(*hint);
+    }
+
+    let scene = scene_at(&state, scene_index)?;
+    let count = scene.page_count();
+    let step = count.div_ceil(LAYOUT_SAMPLE_PAGES).max(1);
+    let fallback = (scene.metadata.image_size.width, scene.metadata.image_size.height);
+
+    let mut tasks = tokio::task::JoinSet::new();
+    for page in scene.pages.iter().step_by(step) {
+        let path = page.image.clone();
+        tasks.spawn_blocking(move || image_loader::image_dimensions(&path).unwrap_or(fallback));
+    }
+    let mut dimensions = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        let (width, height) = result.map_err(|e| format!("Dimension task failed: {}", e))?;
+        dimensions.push(rotated_dimensions(width, height, rotation));
+    }
+
+    let hint = LayoutHint::from_dimensions(&dimensions);
+    debug!("Layout hint of scene {}: {:?} from {} pages", scene_index, hint, dimensions.len());
+    state.layout_hints.lock().unwrap().insert(key, hint);
+    Ok(hint)
+}
+
+/// Get the smallest, largest and average page size of a scene, to spot
+/// scenes mixing scans of different sizes
+///
+/// Reads only the image headers, in parallel; pages whose header can't be
+/// read are left out. Cached per scene file.
+#[tauri::command]
+pub async fn get_scene_dimension_summary(scene_index: usize, state: State<'_, AppState>) -> Result<DimensionSummary, AppError> {
+    let scene_path = scene_file_path(&state, scene_index)?;
+    if let Some(summary) = state.dimension_summaries.lock().unwrap().get(&scene_path) {
+        return Ok(summary.clone());
+    }
+
+    let scene = scene_at(&state, scene_index)?;
+    let mut dimensions = Vec::with_capacity(scene.page_count());
+    run_bounded(&scene.pages, DIMENSION_READ_CONCURRENCY, |tasks, page| {
+        let path = page.image.clone();
+        tasks.spawn_blocking(move || image_loader::image_dimensions(path));
+    }, |result| {
+        match result.map_err(|e| format!("Dimension task failed: {}", e))? {
+            Ok(size) => dimensions.push(size),
+            Err(e) => warn!("Failed to read the size of a page of scene {}: {}", scene_index, e),
+        }
+        Ok(())
+    }).await?;
+
+    let summary = DimensionSummary::from_dimensions(&dimensions)
+        .ok_or_else(|| format!("No readable pages in scene {}", scene_index))?;
+    state.dimension_summaries.lock().unwrap().insert(scene_path, summary.clone());
+    Ok(summary)
+}
+
+/// Get the pixel size of a page from its header, without decoding it
+///
+/// Uses the current scene when `scene_index` is omitted. Unlike get_image,
+/// this does not move the current position.
+#[tauri::command]
+pub async fn get_image_dimensions(scene_index: Option<usize>, page_index: usize, state: State<'_, AppState>) -> Result<ImageSize, AppError> {
+    let scene_index = scene_index.unwrap_or_else(|| state.session.read().unwrap().scene_index);
+    let path = page_path(&*scene_at(&state, scene_index)?, page_index)?;
+    let (width, height) = image_loader::image_dimensions(&path)
+        .map_err(|e| format!("Failed to read the size of {}: {}", path, e))?;
+    Ok(ImageSize { width, height })
+}
+
+/// Check that a page decodes completely, catching truncated or corrupt files
+/// whose header reads fine
+///
+/// The decoded image is discarded, not cached. Errors only for an invalid
+/// position; a page that fails to decode gives false.
+#[tauri::command]
+pub async fn verify_page_decodes(scene_index: usize, page_index: usize, state: State<'_, AppState>) -> Result<bool, AppError> {
+    let path = page_path(&*scene_at(&state, scene_index)?, page_index)?;
+    let _permit = acquire_decode_permit(&state).await?;
+    let decoded = {
+        let path = path.clone();
+        decode_pool::run(move || load_image_with_retry(&path).map(|_| ())).await?
+    };
+    match decoded {
+        Ok(()) => Ok(true),
+        Err(e) => {
+            warn!("Page {} of scene {} failed to decode: {:#}", page_index, scene_index, e);
+            Ok(false)
+        }
+    }
+}
+
+/// Fully decode every page of the collection and get the pages that fail
+///
+/// Decodes VERIFY_CONCURRENCY pages at a time, emitting decode-verify-progress
+/// as pages finish. Scenes whose file can't be read are skipped with a warning.
+#[tauri::command]
+pub async fn verify_all_decodes(state: State<'_, AppState>, app: AppHandle) -> Result<Vec<DecodeFailure>, AppError> {
+    let scene_count = state.session.read().unwrap().collection.as_ref()
+        .map(|coll| coll.scene_count())
+        .ok_or(AppError::NoCollectionLoaded)?;
+
+    let mut pages = Vec::new();
+    for scene_index in 0..scene_count {
+        match scene_at(&state, scene_index) {
+            Ok(scene) => pages.extend(scene.pages.iter().enumerate()
+                .map(|(page_index, page)| (scene_index, page_index, page.image.clone()))),
+            Err(e) => warn!("Skipping scene {} in decode verification: {}", scene_index, e),
+        }
+    }
+
+    let total = pages.len();
+    let mut failures = Vec::new();
+    let mut done = 0;
+    run_bounded(pages, VERIFY_CONCURRENCY, |tasks, (scene_index, page_index, image_path)| {
+        let decode_limit = state.decode_limit.clone();
+        tasks.spawn(async move {
+            let path = image_path.clone();
+            let error = match decode_limit.acquire_owned().await {
+                Ok(_permit) => decode_pool::run(move || load_image_with_retry(&path).err().map(|e| format!("{:#}", e)))
+                    .await
+                    .unwrap_or_else(|e| Some(e.to_string())),
+                Err(_) => Some("Decode limiter closed".to_string()),
+            };
+            error.map(|error| DecodeFailure { scene_index, page_index, image_path, error })
+        });
+    }, |result| {
+        if let Some(failure) = result.map_err(|e| format!("Decode task failed: {}", e))? {
+            failures.push(failure);
+        }
+        done += 1;
+        let _ = app.emit("decode-verify-progress", DecodeVerifyProgress { done, total });
+        Ok(())
+    }).await?;
+
+    failures.sort_by_key(|failure| (failure.scene_index, failure.page_index));
+    info!("Verified {} pages, {} failed to decode", total, failures.len());
+    Ok(failures)
+}
+
+/// Get the actual format of a page's file, detected from its magic bytes
+#[tauri::command]
+pub async fn detect_page_format(
+    scene_index: usize,
+    page_index: usize,
+    state: State<'_, AppState>,
+) -> Result<String, AppError> {
+    let path = page_path(&*scene_at(&state, scene_index)?, page_index)?;
+    detect_format(&path).map_err(|e| format!("Failed to detect format: {:#}", e).into())
+}
+
+/// Read the size, modification time and format of a page file without decoding it
+fn page_file_info(path: String) -> Result<PageFileInfo> {
+    let metadata = std::fs::metadata(&path)
+        .with_context(|| format!("Failed to read metadata of {}", path))?;
+    let modified_unix = metadata.modified().ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|since| since.as_secs());
+    let format = detect_format(&path).ok();
+
+    Ok(PageFileInfo { path, size_bytes: metadata.len(), modified_unix, format })
+}
+
+/// Get the file size, modification time and format of a page
+#[tauri::command]
+pub async fn get_page_file_info(
+    scene_index: usize,
+    page_index: usize,
+    state: State<'_, AppState>,
+) -> Result<PageFileInfo, AppError> {
+    let path = page_path(&*scene_at(&state, scene_index)?, page_index)?;
+    page_file_info(path).map_err(|e| format!("{:#}", e).into())
+}
+
+/// Get the file size, modification time and format of every page of a scene,
+/// in page order
+#[tauri::command]
+pub async fn get_scene_file_infos(scene_index: usize, state: State<'_, AppState>) -> Result<Vec<PageFileInfo>, AppError> {
+    let scene = scene_at(&state, scene_index)?;
+    let mut infos = Vec::with_capacity(scene.page_count());
+    run_bounded(scene.pages.iter().enumerate(), FILE_INFO_CONCURRENCY, |tasks, (page_index, page)| {
+        let path = page.image.clone();
+        tasks.spawn_blocking(move || (page_index, page_file_info(path)));
+    }, |result| {
+        infos.push(result.map_err(|e| format!("File info task failed: {}", e))?);
+        Ok(())
+    }).await?;
+
+    infos.sort_by_key(|(page_index, _)| *page_index);
+    infos.into_iter()
+        .map(|(_, info)| info.map_err(|e| format!("{:#}", e).into()))
+        .collect()
+}
+
+/// Get the perceptual hash of an image path, computing and caching it if needed
+///
+/// Decodes without going through the image cache so hashing a whole scene
+/// doesn't evict the pages being viewed.
+fn cached_phash(state: &AppState, path: &str) -> Result<u64, AppError> {
+    if let Some(hash) = state.phash_cache.lock().unwrap().get(path) {
+        return Ok(*hash);
+    }
+
+    let img = load_image_with_retry(path)
+        .map_err(|e| AppError::ImageDecodeFailed(format!("Failed to load image: {}", e)))?;
+    let hash = perceptual_hash(&img);
+    state.phash_cache.lock().unwrap().insert(path.to_string(), hash);
+    Ok(hash)
+}
+
+/// Get the 64-bit perceptual hash of a page
+#[tauri::command]
+pub async fn page_phash(scene_index: usize, page_index: usize, state: State<'_, AppState>) -> Result<u64, AppError> {
+    let _permit = acquire_decode_permit(&state).await?;
+    let path = page_path(&*scene_at(&state, scene_index)?, page_index)?;
+    cached_phash(&state, &path)
+}
+
+/// Find groups of near-duplicate pages in a scene
+///
+/// Pages whose perceptual hashes differ by at most `threshold` bits are
+/// grouped together. Returns page indices; pages that fail to load are skipped.
+#[tauri::command]
+pub async fn find_duplicate_pages(
+    scene_index: usize,
+    threshold: u32,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<Vec<usize>>, AppError> {
+    let _permit = acquire_decode_permit(&state).await?;
+    let scene = scene_at(&state, scene_index)?;
+
+    let (page_indices, hashes) = decode_pool::run(move || {
+        let state = app.state::<AppState>();
+        let mut page_indices = Vec::new();
+        let mut hashes = Vec::new();
+        for (page_index, page) in scene.pages.iter().enumerate() {
+            match cached_phash(&state, &page.image) {
+                Ok(hash) => {
+                    page_indices.push(page_index);
+                    hashes.push(hash);
+                }
+                Err(e) => warn!("Skipping page {} in duplicate search: {}", page_index, e),
+            }
+        }
+        (page_indices, hashes)
+    }).await?;
+
+    let groups = group_similar(&hashes, threshold)
+        .into_iter()
+        .map(|group| group.into_iter().map(|i| page_indices[i]).collect())
+        .collect();
+    Ok(groups)
+}
+
+/// Get the rotation of a scene in degrees clockwise
+#[tauri::command]
+pub async fn get_scene_rotation(scene_index: usize, state: State<'_, AppState>) -> Result<u32, AppError> {
+    Ok(scene_rotation(&state, scene_index))
+}
+
+/// Rotate every page of a scene clockwise by a multiple of 90 degrees
+///
+/// Persisted per scene file so it survives restarts; 0 resets it.
+#[tauri::command]
+pub async fn set_scene_rotation(
+    scene_index: usize,
+    degrees: u32,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), AppError> {
+    if !degrees.is_multiple_of(90) {
+        return Err(format!("Rotation must be a multiple of 90 degrees, got {}", degrees).into());
+    }
+    let degrees = degrees % 360;
+
+    let scene_file = {
+        let session = state.session.read().unwrap();
+        let coll = session.collection.as_ref().ok_or(AppError::NoCollectionLoaded)?;
+        coll.scene_files.get(scene_index)
+            .ok_or_else(|| format!("Scene index out of bounds: {}", scene_index))?
+            .to_string_lossy()
+            .to_string()
+    };
+
+    let rotations = {
+        let mut rotations = state.scene_rotations.lock().unwrap();
+        if degrees == 0 {
+            rotations.remove(&scene_file);
+        } else {
+            rotations.insert(scene_file.clone(), degrees);
+        }
+        rotations.clone()
+    };
+    info!("Rotation of {} set to {}", scene_file, degrees);
+
+    storage::save_json(&app, ROTATIONS_FILE, &rotations)
+        .map_err(|e| format!("Failed to save scene rotations: {}", e).into())
+}
+
+/// Get the maximum number of commands allowed to decode images at once
+#[tauri::command]
+pub async fn get_max_concurrent_decodes(state: State<'_, AppState>) -> Result<usize, AppError> {
+    Ok(*state.max_concurrent_decodes.lock().unwrap())
+}
+
+/// Set the maximum number of commands allowed to decode images at once
+///
+/// Lowering the limit takes effect as in-flight decodes finish.
+#[tauri::command]
+pub async fn set_max_concurrent_decodes(count: usize, state: State<'_, AppState>) -> Result<(), AppError> {
+    if count == 0 {
+        return Err("Concurrent decode limit must be at least 1".into());
+    }
+
+    let previous = std::mem::replace(&mut *state.max_concurrent_decodes.lock().unwrap(), count);
+    if count > previous {
+        state.decode_limit.add_permits(count - previous);
+    } else if count < previous {
+        // Take the surplus permits out of circulation once they are free
+        let decode_limit = state.decode_limit.clone();
+        let surplus = (previous - count) as u32;
+        tokio::spawn(async move {
+            if let Ok(permits) = decode_limit.acquire_many_owned(surplus).await {
+                permits.forget();
+            }
+        });
+    }
+    info!("Max concurrent decodes set to {}", count);
+    Ok(())
+}
+
+/// Get a page with its near-white borders cropped away, as a base64 JPEG
+///
+/// Rows and columns whose mean brightness is at or above `threshold` (0-255)
+/// count as margin. The trimmed page is resized within the max dimension
+/// (see set_max_dimension).
+#[tauri::command]
+pub async fn get_image_trimmed(
+    scene_index: usize,
+    page_index: usize,
+    threshold: u8,
+    state: State<'_, AppState>,
+) -> Result<String, AppError> {
+    let _permit = acquire_decode_permit(&state).await?;
+    let path = page_path(&*scene_at(&state, scene_index)?, page_index)?;
+    let rotation = scene_rotation(&state, scene_index);
+    let key = rotated_key(&format!("{}@trim{}", path, threshold), rotation);
+    if let Some(cached) = state.encoded_cache.get(&key) {
+        return Ok(cached);
+    }
+
+    let max_dimension = *state.max_dimension.lock().unwrap();
+    let jpeg_options = *state.jpeg_options.lock().unwrap();
+    let encoded = decode_pool::run(move || -> Result<String, AppError> {
+        let img = load_image_with_retry(&path)
+            .map_err(|e| AppError::ImageDecodeFailed(format!("Failed to load image: {}", e)))?;
+        let trimmed = resize_to_fit(&trim_borders(&img, threshold), max_dimension, max_dimension);
+        debug!("Trimmed {} from {:?} to {:?}", path, img.dimensions(), trimmed.dimensions());
+
+        Ok(image_to_base64_jpeg(&rotate(&trimmed, rotation), 85, &jpeg_options)
+            .map_err(|e| format!("Failed to encode image: {}", e))?)
+    }).await??;
+    state.encoded_cache.insert(key, encoded.clone());
+    Ok(encoded)
+}
+
+/// Get a page prepared for OCR: full resolution, binarized to black text on
+/// white, as raw PNG bytes for external tools
+#[tauri::command]
+pub async fn get_image_ocr_prep(
+    scene_index: usize,
+    page_index: usize,
+    state: State<'_, AppState>,
+) -> Result<Vec<u8>, AppError> {
+    let _permit = acquire_decode_permit(&state).await?;
+    let path = page_path(&*scene_at(&state, scene_index)?, page_index)?;
+    let rotation = scene_rotation(&state, scene_index);
+
+    decode_pool::run(move || {
+        let img = load_image_with_retry(&path)
+            .map_err(|e| AppError::ImageDecodeFailed(format!("Failed to load image: {}", e)))?;
+        let binary = DynamicImage::ImageLuma8(ocr_binarize(&rotate(&img, rotation)));
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        binary.write_to(&mut png, image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode image: {}", e))?;
+        debug!("Prepared {} for OCR ({} bytes)", path, png.get_ref().len());
+        Ok(png.into_inner())
+    }).await?
+}
+
+/// Get a page as raw RGBA pixels fitting within `size`, for canvas/WebGL
+/// renderers that would otherwise have to decode an encoded image again
+///
+/// Payloads are large (4 bytes per pixel, a 1920x1080 page is about 8 MB), so
+/// use this for modest sizes only. The decoded image is shared with get_image
+/// through the image cache.
+#[tauri::command]
+pub async fn get_image_rgba(
+    scene_index: usize,
+    page_index: usize,
+    size: u32,
+    state: State<'_, AppState>,
+) -> Result<RgbaImage, AppError> {
+    if size == 0 {
+        return Err("Size must be greater than zero".into());
+    }
+    let _permit = acquire_decode_permit(&state).await?;
+    let path = page_path(&*scene_at(&state, scene_index)?, page_index)?;
+    let rotation = scene_rotation(&state, scene_index);
+
+    let cache = state.cache.clone();
+    decode_pool::run(move || {
+        let img = load_image_cached_with_size(&path, size.min(MAX_TARGET_SIZE), &cache)
+            .map_err(|e| AppError::ImageDecodeFailed(format!("Failed to load image: {}", e)))?;
+        let rgba = rotate(&img, rotation).to_rgba8();
+        let (width, height) = rgba.dimensions();
+        let data = rgba.into_raw();
+        if data.len() > RGBA_WARN_BYTES {
+            warn!("RGBA payload of {} is {} MB; consider a smaller size", path, data.len() / (1024 * 1024));
+        }
+        Ok(RgbaImage { width, height, data })
+    }).await?
+}
+
+/// Get the path of a scene file in the current collection
+fn scene_file_path(state: &AppState, scene_index: usize) -> Result<PathBuf, AppError> {
+    let session = state.session.read().unwrap();
+    let coll = session.collection.as_ref().ok_or(AppError::NoCollectionLoaded)?;
+    coll.scene_files.get(scene_index)
+        .cloned()
+        .ok_or_else(|| AppError::Other(format!("Scene index out of bounds: {}", scene_index)))
+}
+
+/// Get the exact JSON text of a scene file, for editing
+#[tauri::command]
+pub async fn get_scene_source(scene_index: usize, state: State<'_, AppState>) -> Result<String, AppError> {
+    let path = scene_file_path(&state, scene_index)?;
+    Scene::read_source(&path).map_err(|e| AppError::SceneLoadFailed(format!("Failed to read scene: {}", e)))
+}
+
+/// Replace a scene file with edited JSON text and reload it
+///
+/// The text must parse as a scene; it is written unchanged otherwise.
+#[tauri::command]
+pub async fn save_scene_source(scene_index: usize, text: String, state: State<'_, AppState>) -> Result<(), AppError> {
+    let path = scene_file_path(&state, scene_index)?;
+    if !Scene::has_scene_file(&path) {
+        return Err(format!("Scene {} is an image directory or archive and has no scene file", path.display()).into());
+    }
+    let scene = Scene::write_source(&path, &text)
+        .map_err(|e| format!("Failed to save scene: {:#}", e))?;
+    info!("Saved scene {:?}", path);
+
+    // The file now holds the scene; unsaved page edits would shadow it
+    state.edited_scenes.lock().unwrap().remove(&path);
+    state.scene_page_counts.lock().unwrap().insert(scene_index, scene.page_count());
+
+    // Reload the current scene if it was the one edited
+    let mut session = state.session.write().unwrap();
+    if let Some(coll) = session.collection.as_mut() {
+        coll.invalidate_summaries();
+    }
+    if scene_index == session.scene_index {
+        session.page_index = session.page_index.min(scene.page_count().saturating_sub(1));
+        session.scene = Some(Arc::new(scene));
+    }
+    Ok(())
+}
+
+/// Load the image a page placeholder is computed from: the thumbnail when
+/// there is one, otherwise a PREVIEW_DIMENSION decode of the page
+fn load_placeholder_source(state: &AppState, scene: &Scene, path: &str) -> Result<Arc<image::DynamicImage>, AppError> {
+    let thumb_path = scene.get_thumbnail_path(path);
+    match thumb_path.to_str() {
+        Some(thumb) if thumb_path.exists() => load_image_cached_with_size(thumb, *state.max_dimension.lock().unwrap(), &state.cache),
+        _ => load_image_cached_with_size(path, PREVIEW_DIMENSION, &state.cache),
+    }
+    .map_err(|e| AppError::ImageDecodeFailed(format!("Failed to load image: {}", e)))
+}
+
+/// Apply a page edit to a scene and keep the result as its unsaved state
+///
+/// The current page index is clamped if the current scene shrank. Edits hold
+/// the unsaved scenes lock throughout, so concurrent edits apply one after
+/// the other, each starting from the previous one's result.
+fn edit_scene(state: &AppState, scene_index: usize, edit: impl FnOnce(&mut Scene) -> Result<()>) -> Result<(), AppError> {
+    let (coll, current) = {
+        let session = state.session.read().unwrap();
+        let coll = session.collection.clone().ok_or(AppError::NoCollectionLoaded)?;
+        (coll, session.scene.clone().filter(|_| session.scene_index == scene_index))
+    };
+    let path = coll.scene_files.get(scene_index)
+        .cloned()
+        .ok_or_else(|| AppError::Other(format!("Scene index out of bounds: {}", scene_index)))?;
+
+    let mut edited_scenes = state.edited_scenes.lock().unwrap();
+    let mut scene = match (edited_scenes.get(&path), current) {
+        (Some(scene), _) => scene.clone(),
+        (None, Some(scene)) => (*scene).clone(),
+        (None, None) => coll.load_scene(scene_index)
+            .map_err(|e| AppError::SceneLoadFailed(format!("Failed to load scene {}: {}", scene_index, e)))?,
+    };
+    edit(&mut scene).map_err(|e| AppError::Other(format!("Failed to edit scene {}: {}", scene_index, e)))?;
+
+    {
+        let mut session = state.session.write().unwrap();
+        session.check_unchanged(&coll, scene_index, None)?;
+        if scene_index == session.scene_index && session.scene.is_some() {
+            session.page_index = session.page_index.min(scene.page_count() - 1);
+            session.scene = Some(Arc::new(scene.clone()));
+        }
+    }
+
+    // Summaries of the scene were computed from the old page list
+    let prefix = path.to_string_lossy();
+    state.layout_hints.lock().unwrap().retain(|key, _| !key.starts_with(&*prefix));
+    state.dimension_summaries.lock().unwrap().remove(&path);
+    state.scene_page_counts.lock().unwrap().insert(scene_index, scene.page_count());
+    edited_scenes.insert(path, scene);
+    Ok(())
+}
+
+/// Remove a page from a scene, returning the scene's new page count
+///
+/// The edit is kept in memory until save_scene writes it.
+#[tauri::command]
+pub async fn remove_page(scene_index: usize, page_index: usize, state: State<'_, AppState>) -> Result<usize, AppError> {
+    let mut removed = None;
+    edit_scene(&state, scene_index, |scene| {
+        removed = Some(scene.remove_page(page_index)?);
+        Ok(())
+    })?;
+
+    if let Some(page) = removed {
+        let freed = state.cache.invalidate(&page.image)
+            + state.encoded_cache.invalidate(&page.image)
+            + state.cover_cache.invalidate(&page.image);
+        debug!("Removed page {} of scene {} ({} cache entries dropped)", page_index, scene_index, freed);
+    }
+    Ok(state.scene_page_counts.lock().unwrap().get(&scene_index).copied().unwrap_or(0))
+}
+
+/// Move a page of a scene from index `from` to `to`
+///
+/// The edit is kept in memory until save_scene writes it.
+#[tauri::command]
+pub async fn move_page(scene_index: usize, from: usize, to: usize, state: State<'_, AppState>) -> Result<(), AppError> {
+    edit_scene(&state, scene_index, |scene| scene.move_page(from, to))?;
+    debug!("Moved page {} of scene {} to {}", from, scene_index, to);
+    Ok(())
+}
+
+/// Write a scene's page edits back to its scene file
+#[tauri::command]
+pub async fn save_scene(scene_index: usize, state: State<'_, AppState>) -> Result<(), AppError> {
+    let path = scene_file_path(&state, scene_index)?;
+    if !Scene::has_scene_file(&path) {
+        return Err(format!("Scene {} is an image directory or archive and has no scene file", path.display()).into());
+    }
+
+    let scene = scene_at(&state, scene_index)?;
+    scene.save_to_file(&path)
+        .map_err(|e| format!("Failed to save scene: {:#}", e))?;
+    state.edited_scenes.lock().unwrap().remove(&path);
+    if let Some(coll) = state.session.write().unwrap().collection.as_mut() {
+        coll.invalidate_summaries();
+    }
+    info!("Saved scene {:?}", path);
+    Ok(())
+}
+
+/// Get the average color of a page, for a placeholder while it loads
+///
+/// Computed from the thumbnail when there is one and cached per path.
+#[tauri::command]
+pub async fn get_page_dominant_color(
+    scene_index: usize,
+    page_index: usize,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<[u8; 3], AppError> {
+    let scene = scene_at(&state, scene_index)?;
+    let path = page_path(&scene, page_index)?;
+    if let Some(color) = state.color_cache.lock().unwrap().get(&path) {
+        return Ok(*color);
+    }
+
+    let _permit = acquire_decode_permit(&state).await?;
+    let color = {
+        let path = path.clone();
+        decode_pool::run(move || -> Result<_, AppError> {
+            let img = load_placeholder_source(&app.state::<AppState>(), &scene, &path)?;
+            Ok(average_color(&img))
+        }).await??
+    };
+    state.color_cache.lock().unwrap().insert(path, color);
+    Ok(color)
+}
+
+/// Get the BlurHash of a page, for a placeholder while it loads
+///
+/// `x_comp` and `y_comp` are the number of components along each axis (1-9).
+/// Computed from the thumbnail when there is one and cached per path.
+#[tauri::command]
+pub async fn get_page_blurhash(
+    scene_index: usize,
+    page_index: usize,
+    x_comp: u32,
+    y_comp: u32,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, AppError> {
+    if !(1..=9).contains(&x_comp) || !(1..=9).contains(&y_comp) {
+        return Err(format!("BlurHash components must be 1-9, got {}x{}", x_comp, y_comp).into());
+    }
+
+    let scene = scene_at(&state, scene_index)?;
+    let path = page_path(&scene, page_index)?;
+    let key = format!("{}#{}x{}", path, x_comp, y_comp);
+    if let Some(hash) = state.blurhash_cache.lock().unwrap().get(&key) {
+        return Ok(hash.clone());
+    }
+
+    let _permit = acquire_decode_permit(&state).await?;
+    let hash = decode_pool::run(move || -> Result<_, AppError> {
+        let img = load_placeholder_source(&app.state::<AppState>(), &scene, &path)?;
+        blurhash(&img, x_comp, y_comp).map_err(|e| format!("Failed to compute BlurHash: {}", e).into())
+    }).await??;
+    state.blurhash_cache.lock().unwrap().insert(key, hash.clone());
+    Ok(hash)
 }
 
-/// Load a scene collection from a directory
+/// Export pages `start..=end` of a scene as JPEGs resized to fit within
+/// `max_dimension`, written to `{dest_dir}/page_{n}.jpg`
+///
+/// Creates dest_dir if needed and emits export-progress as pages are written.
+/// Pages that fail are logged and skipped; returns the number written.
 #[tauri::command]
-pub async fn load_scene_collection(
-    path: String,
+#[allow(clippy::too_many_arguments)]
+pub async fn export_page_range(
+    scene_index: usize,
+    start: usize,
+    end: usize,
+    dest_dir: String,
+    max_dimension: u32,
+    quality: u8,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    let collection = SceneCollection::new(&path)
-        .map_err(|e| format!("Failed to load scene collection: {}", e))?;
+    app: AppHandle,
+) -> Result<usize, AppError> {
+    if max_dimension == 0 {
+        return Err("Max dimension must be greater than zero".into());
+    }
+    if !(1..=100).contains(&quality) {
+        return Err(format!("Quality must be between 1 and 100, got {}", quality).into());
+    }
+    let scene = scene_at(&state, scene_index)?;
+    if start > end || end >= scene.page_count() {
+        return Err(format!(
+            "Invalid page range {}..={} (scene has {} pages)",
+            start, end, scene.page_count()
+        ).into());
+    }
 
-    let scene_count = collection.scene_count();
+    let dest_dir = PathBuf::from(dest_dir);
+    std::fs::create_dir_all(&dest_dir)
+        .map_err(|e| format!("Failed to create {}: {}", dest_dir.display(), e))?;
 
-    // Load the first scene
-    if scene_count > 0 {
-        let scene = collection.load_scene(0)
-            .map_err(|e| format!("Failed to load first scene: {}", e))?;
+    let rotation = scene_rotation(&state, scene_index);
+    let jpeg_options = *state.jpeg_options.lock().unwrap();
+    let total = end - start + 1;
+    let (mut done, mut written) = (0, 0);
 
-        *state.current_scene.lock().unwrap() = Some(scene);
-        *state.current_collection.lock().unwrap() = Some(collection);
-        *state.current_scene_index.lock().unwrap() = 0;
-        *state.current_page_index.lock().unwrap() = 0;
+    run_bounded(start..=end, EXPORT_CONCURRENCY, |tasks, page_index| {
+        let decode_limit = state.decode_limit.clone();
+        let src = scene.pages[page_index].image.clone();
+        let dest = dest_dir.join(format!("page_{}.jpg", page_index));
+        tasks.spawn(async move {
+            let Ok(permit) = decode_limit.acquire_owned().await else { return false };
+            let written = decode_pool::run(move || {
+                let _permit = permit;
+                let result = load_image_with_retry(&src)
+                    .map(|img| rotate(&resize_to_fit(&img, max_dimension, max_dimension), rotation))
+                    .and_then(|img| image_to_jpeg(&img, quality, &jpeg_options))
+                    .and_then(|bytes| Ok(std::fs::write(&dest, bytes)?));
+                match result {
+                    Ok(()) => true,
+                    Err(e) => {
+                        warn!("Failed to export {} to {:?}: {}", src, dest, e);
+                        false
+                    }
+                }
+            }).await;
+            written.unwrap_or_else(|e| {
+                warn!("Failed to export page {}: {}", page_index, e);
+                false
+            })
+        });
+    }, |result| {
+        done += 1;
+        if let Ok(true) = result {
+            written += 1;
+        }
+        let _ = app.emit("export-progress", ExportProgress { done, total });
+        Ok(())
+    }).await?;
 
-        // Preload initial images in background
-        let cache = state.cache.clone();
-        let encoded_cache = state.encoded_cache.clone();
-        let current_scene = state.current_scene.clone();
-        let current_page_index = state.current_page_index.clone();
+    info!("Exported {} of {} pages of scene {} to {:?}", written, total, scene_index, dest_dir);
+    Ok(written)
+}
 
-        tokio::spawn(async move {
-            let _ = preload_next_images_task(cache, encoded_cache, current_scene, current_page_index, 3).await;
-        });
+/// Get a spread of a scene as one image: its authored `spreads` entry, or
+/// the grouping computed for the spread mode when the scene has none
+///
+/// Pages are laid out in the reading direction, so a right-to-left spread
+/// has its first page on the right.
+#[tauri::command]
+pub async fn get_defined_spread(
+    scene_index: usize,
+    spread_index: usize,
+    state: State<'_, AppState>,
+) -> Result<SpreadImage, AppError> {
+    let scene = scene_at(&state, scene_index)?;
+    let spreads = scene.spreads(*state.spread_mode.lock().unwrap());
+    let mut pages = spreads.get(spread_index)
+        .ok_or_else(|| format!("Spread index {} out of range (scene has {} spreads)", spread_index, spreads.len()))?
+        .clone();
+    if *state.reading_direction.lock().unwrap() == ReadingDirection::RightToLeft {
+        pages.reverse();
     }
 
-    Ok(format!("Loaded {} scenes", scene_count))
+    let _permit = acquire_decode_permit(&state).await?;
+    let rotation = scene_rotation(&state, scene_index);
+    let max_dimension = *state.max_dimension.lock().unwrap();
+    let paths = pages.iter()
+        .map(|&page| Ok((page, page_path(&scene, page)?)))
+        .collect::<Result<Vec<_>, AppError>>()?;
+    let format = state.tier_formats.lock().unwrap().high;
+    let jpeg_options = *state.jpeg_options.lock().unwrap();
+    let cache = state.cache.clone();
+    let (image, width, height) = decode_pool::run(move || -> Result<_, AppError> {
+        let images = paths.iter()
+            .map(|(page, path)| {
+                load_image_cached_with_size(path, max_dimension, &cache)
+                    .map(|img| rotate(&img, rotation))
+                    .map_err(|e| format!("Failed to load page {}: {}", page, e))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let spread = compose_spread(&images);
+        let image = encode_image(&spread, format, 85, &jpeg_options)
+            .map_err(|e| format!("Failed to encode spread: {}", e))?;
+        Ok((image, spread.width(), spread.height()))
+    }).await??;
+
+    Ok(SpreadImage {
+        image,
+        pages,
+        spread_count: spreads.len(),
+        width,
+        height,
+    })
 }
 
-/// Get the current scene information
+/// Get the index of the spread containing a page, for showing the spread
+/// of the current page
 #[tauri::command]
-pub async fn get_scene_info(state: State<'_, AppState>) -> Result<SceneInfo, String> {
-    let scene = state.current_scene.lock().unwrap();
-    let scene_index = *state.current_scene_index.lock().unwrap();
-    let page_index = *state.current_page_index.lock().unwrap();
+pub async fn get_spread_index(scene_index: usize, page_index: usize, state: State<'_, AppState>) -> Result<usize, AppError> {
+    let scene = scene_at(&state, scene_index)?;
+    scene.spreads(*state.spread_mode.lock().unwrap())
+        .iter()
+        .position(|spread| spread.contains(&page_index))
+        .ok_or_else(|| format!("Page {} is in no spread of scene {}", page_index, scene_index).into())
+}
 
-    if let Some(scene) = scene.as_ref() {
-        Ok(SceneInfo {
-            scene_name: scene.metadata.scene_name.clone(),
-            scene_index,
-            total_pages: scene.page_count(),
-            current_page: page_index,
-        })
-    } else {
-        Err("No scene loaded".to_string())
-    }
+/// Left and right page of the two-page spread containing `page_index`, or
+/// None when the page is out of range
+///
+/// Pages pair up as computed_spreads does for `mode`. A page alone goes on
+/// the side the first page of a spread would: left when reading left to
+/// right, right when reading right to left.
+fn spread_sides(
+    page_index: usize,
+    page_count: usize,
+    mode: SpreadMode,
+    direction: ReadingDirection,
+) -> Option<(Option<usize>, Option<usize>)> {
+    let spread = computed_spreads(page_count, mode).into_iter().find(|spread| spread.contains(&page_index))?;
+    let (first, second) = (spread.first().copied(), spread.get(1).copied());
+    Some(match direction {
+        ReadingDirection::LeftToRight => (first, second),
+        ReadingDirection::RightToLeft => (second, first),
+    })
 }
 
-/// Get an image at a specific page
+/// Get the two pages of the spread containing a page as separate images,
+/// for showing them side by side
+///
+/// Uses the current scene when `scene_index` is omitted. Pages pair up by
+/// the spread mode, so CoverFirst shows page 0 alone; authored spreads are
+/// left to get_defined_spread. Each page is loaded as get_image would,
+/// including the progressive preview and its `image-upgraded` event, and the
+/// current position moves to `page_index`.
 #[tauri::command]
-pub async fn get_image(
+pub async fn get_spread(
     scene_index: Option<usize>,
     page_index: usize,
     state: State<'_, AppState>,
-) -> Result<ImageData, String> {
-    println!("get_image called: scene_index={:?}, page_index={}", scene_index, page_index);
-    let mut current_scene_idx = state.current_scene_index.lock().unwrap();
-    let collection = state.current_collection.lock().unwrap();
+    app: AppHandle,
+) -> Result<SpreadPages, AppError> {
+    let scene_index = scene_index.unwrap_or_else(|| state.session.read().unwrap().scene_index);
+    let scene = scene_at(&state, scene_index)?;
+    let mode = *state.spread_mode.lock().unwrap();
+    let direction = *state.reading_direction.lock().unwrap();
+    let (left_page, right_page) = spread_sides(page_index, scene.page_count(), mode, direction)
+        .ok_or(AppError::PageOutOfBounds { index: page_index, total: scene.page_count() })?;
 
-    // Load different scene if requested
-    if let Some(new_scene_idx) = scene_index {
-        if new_scene_idx != *current_scene_idx {
-            if let Some(coll) = collection.as_ref() {
-                let scene = coll.load_scene(new_scene_idx)
-                    .map_err(|e| format!("Failed to load scene {}: {}", new_scene_idx, e))?;
+    // The requested page goes last so the position ends up on it
+    let other_page = [left_page, right_page].into_iter().flatten().find(|&page| page != page_index);
+    let mut images = HashMap::new();
+    for page in other_page.into_iter().chain([page_index]) {
+        let image = get_image(Some(scene_index), page, None, state.clone(), app.clone()).await?;
+        images.insert(page, image.main_image);
+    }
+    let mut image_of = |page: Option<usize>| page.and_then(|page| images.remove(&page).flatten());
 
-                *state.current_scene.lock().unwrap() = Some(scene);
-                *current_scene_idx = new_scene_idx;
-            }
-        }
+    Ok(SpreadPages {
+        scene_index,
+        left_page,
+        right_page,
+        left_image: image_of(left_page),
+        right_image: image_of(right_page),
+    })
+}
+
+/// Get how pages are paired into spreads
+#[tauri::command]
+pub async fn get_spread_mode(state: State<'_, AppState>) -> Result<SpreadMode, AppError> {
+    Ok(*state.spread_mode.lock().unwrap())
+}
+
+/// Set how pages are paired into spreads; authored spreads are unaffected
+#[tauri::command]
+pub async fn set_spread_mode(mode: SpreadMode, state: State<'_, AppState>) -> Result<(), AppError> {
+    *state.spread_mode.lock().unwrap() = mode;
+    info!("Spread mode set to {:?}", mode);
+    Ok(())
+}
+
+/// Get the order of the pages of a spread and the direction of page turns
+#[tauri::command]
+pub async fn get_reading_direction(state: State<'_, AppState>) -> Result<ReadingDirection, AppError> {
+    Ok(*state.reading_direction.lock().unwrap())
+}
+
+/// Set the order of the pages of a spread; RightToLeft also swaps next_page
+/// and prev_page (see page_step)
+#[tauri::command]
+pub async fn set_reading_direction(direction: ReadingDirection, state: State<'_, AppState>) -> Result<(), AppError> {
+    *state.reading_direction.lock().unwrap() = direction;
+    info!("Reading direction set to {:?}", direction);
+    Ok(())
+}
+
+/// Get a page at several resolutions for smooth zooming, largest first
+///
+/// The largest level is the original size (up to MAX_TARGET_SIZE) and each
+/// further level halves it. The file is decoded once and every level is
+/// shrunk from the one before it. Levels aren't cached since they would push
+/// preloaded pages out of the encoded cache.
+#[tauri::command]
+pub async fn get_image_pyramid(
+    scene_index: usize,
+    page_index: usize,
+    levels: u32,
+    state: State<'_, AppState>,
+) -> Result<Vec<ImageData>, AppError> {
+    if levels == 0 || levels > MAX_PYRAMID_LEVELS {
+        return Err(format!("Levels must be between 1 and {}, got {}", MAX_PYRAMID_LEVELS, levels).into());
     }
+    let _permit = acquire_decode_permit(&state).await?;
+    let scene = scene_at(&state, scene_index)?;
+    let path = page_path(&scene, page_index)?;
+    let rotation = scene_rotation(&state, scene_index);
+    let format = state.tier_formats.lock().unwrap().high;
+    let jpeg_options = *state.jpeg_options.lock().unwrap();
 
-    drop(current_scene_idx); // Release lock
-    drop(collection); // Release lock
+    decode_pool::run(move || {
+        let page = scene.get_page(page_index);
+        let mut level = load_image_with_retry(&path)
+            .map_err(|e| AppError::ImageDecodeFailed(format!("Failed to load image: {}", e)))?;
+        let (width, height) = level.dimensions();
 
-    let scene = state.current_scene.lock().unwrap();
-    let scene_idx = *state.current_scene_index.lock().unwrap();
+        let mut pyramid = Vec::new();
+        for size in pyramid_sizes(width.max(height).min(MAX_TARGET_SIZE), levels) {
+            level = resize_to_fit(&level, size, size);
+            let rotated = rotate(&level, rotation);
+            let image = encode_keeping_alpha(&rotated, format, 85, &jpeg_options)
+                .map_err(|e| format!("Failed to encode {}px level: {}", size, e))?;
 
-    if let Some(scene) = scene.as_ref() {
-        if page_index >= scene.page_count() {
-            return Err(format!(
-                "Page index {} out of bounds (total: {})",
+            pyramid.push(ImageData {
+                has_alpha: is_png_data_url(&image),
+                main_image: Some(image),
+                thumbnail_image: None,
                 page_index,
-                scene.page_count()
-            ));
+                scene_index,
+                image_path: path.clone(),
+                width: rotated.width(),
+                height: rotated.height(),
+                warning: None,
+                page_title: page.and_then(|p| p.title.clone()),
+                page_caption: page.and_then(|p| p.caption.clone()),
+                tier: ImageTier::High,
+                clamped_to_max_side: size > image_loader::max_side(),
+            });
         }
+        debug!("Built {}-level pyramid of {} ({}x{})", pyramid.len(), path, width, height);
 
-        let main_path = scene.get_page_image(page_index)
-            .ok_or("Failed to get page image")?;
+        Ok(pyramid)
+    }).await?
+}
 
-        let thumbnail_path = scene.get_thumbnail_path(main_path);
+/// Add a page to the read pages of its scene, saving them if it is new
+///
+/// Pages with an ID are remembered by it, others by index.
+fn mark_read(state: &AppState, app: &AppHandle, scene_index: usize, page_index: usize) -> Result<(), AppError> {
+    let scene_file = scene_file_path(state, scene_index)?.to_string_lossy().to_string();
 
-        // Load main image - check encoded cache first
-        let main_image = if let Some(cached) = state.encoded_cache.get(main_path) {
-            Some(cached)
-        } else {
-            match load_image_cached(main_path, &state.cache) {
-                Ok(img) => match image_to_base64_jpeg(&img, 85) {
-                    Ok(base64) => {
-                        // Store in encoded cache for future use
-                        state.encoded_cache.insert(main_path.to_string(), base64.clone());
-                        Some(base64)
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to encode main image: {}", e);
-                        None
-                    }
-                },
-                Err(e) => {
-                    eprintln!("Failed to load main image: {}", e);
-                    None
-                }
+    let page_id = scene_at(state, scene_index)?.get_page(page_index).and_then(|page| page.id.clone());
+    if let Some(id) = page_id {
+        let read_page_ids = {
+            let mut read_page_ids = state.read_page_ids.lock().unwrap();
+            if !read_page_ids.entry(scene_file).or_default().insert(id) {
+                return Ok(());
             }
+            read_page_ids.clone()
         };
+        return storage::save_json(app, READ_PAGE_IDS_FILE, &read_page_ids)
+            .map_err(|e| AppError::Other(format!("Failed to save read page IDs: {}", e)));
+    }
 
-        // Load thumbnail if it exists - check encoded cache first
-        let thumbnail_image = if thumbnail_path.exists() {
-            let thumb_path_str = thumbnail_path.to_str().unwrap();
-            if let Some(cached) = state.encoded_cache.get(thumb_path_str) {
-                Some(cached)
-            } else {
-                match load_image_cached(thumb_path_str, &state.cache) {
-                    Ok(img) => match image_to_base64_jpeg(&img, 75) {
-                        Ok(base64) => {
-                            // Store in encoded cache for future use
-                            state.encoded_cache.insert(thumb_path_str.to_string(), base64.clone());
-                            Some(base64)
-                        }
-                        Err(e) => {
-                            eprintln!("Failed to encode thumbnail: {}", e);
-                            None
-                        }
-                    },
-                    Err(e) => {
-                        eprintln!("Failed to load thumbnail: {}", e);
-                        None
-                    }
-                }
-            }
-        } else {
-            None
-        };
+    let read_pages = {
+        let mut read_pages = state.read_pages.lock().unwrap();
+        if !read_pages.entry(scene_file).or_default().insert(page_index) {
+            return Ok(());
+        }
+        read_pages.clone()
+    };
 
-        // Update current page index
-        *state.current_page_index.lock().unwrap() = page_index;
-        println!("Updated current_page_index to: {}", page_index);
+    storage::save_json(app, READ_PAGES_FILE, &read_pages)
+        .map_err(|e| AppError::Other(format!("Failed to save read pages: {}", e)))
+}
 
-        let result = ImageData {
-            main_image,
-            thumbnail_image,
-            page_index,
-            scene_index: scene_idx,
-            image_path: main_path.to_string(),
-        };
-        println!("Returning ImageData: page_index={}, scene_index={}, path={}", result.page_index, result.scene_index, result.image_path);
-        Ok(result)
-    } else {
-        println!("ERROR: No scene loaded in get_image");
-        Err("No scene loaded".to_string())
+/// Get a scene with its read pages by index and read page IDs (empty if none
+/// were read)
+fn scene_read_pages(state: &AppState, scene_index: usize) -> Result<(Arc<Scene>, PageSet, HashSet<String>), AppError> {
+    let scene = scene_at(state, scene_index)?;
+    let scene_file = scene_file_path(state, scene_index)?.to_string_lossy().to_string();
+    let pages = state.read_pages.lock().unwrap().get(&scene_file).cloned().unwrap_or_default();
+    let ids = state.read_page_ids.lock().unwrap().get(&scene_file).cloned().unwrap_or_default();
+    Ok((scene, pages, ids))
+}
+
+/// Whether a page is read: by its ID if it has one, otherwise by index
+fn is_read(scene: &Scene, page_index: usize, pages: &PageSet, ids: &HashSet<String>) -> bool {
+    match scene.get_page(page_index).and_then(|page| page.id.as_ref()) {
+        Some(id) => ids.contains(id),
+        None => pages.contains(page_index),
+    }
+}
+
+/// Number of read pages of a scene
+fn read_count(scene: &Scene, pages: &PageSet, ids: &HashSet<String>) -> usize {
+    if scene.pages.iter().all(|page| page.id.is_none()) {
+        return pages.count(scene.page_count());
+    }
+    (0..scene.page_count()).filter(|&page| is_read(scene, page, pages, ids)).count()
+}
+
+/// Mark a page as read
+#[tauri::command]
+pub async fn mark_page_read(
+    scene_index: usize,
+    page_index: usize,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), AppError> {
+    let page_count = scene_at(&state, scene_index)?.page_count();
+    if page_index >= page_count {
+        return Err(AppError::PageOutOfBounds { index: page_index, total: page_count });
     }
+    mark_read(&state, &app, scene_index, page_index)
 }
 
-/// Navigate to the next page
+/// Check whether a page has been marked read
 #[tauri::command]
-pub async fn next_page(state: State<'_, AppState>) -> Result<ImageData, String> {
-    println!("=== next_page command called ===");
-    let scene_loop_enabled = *state.scene_loop_enabled.lock().unwrap();
+pub async fn is_page_read(scene_index: usize, page_index: usize, state: State<'_, AppState>) -> Result<bool, AppError> {
+    let (scene, pages, ids) = scene_read_pages(&state, scene_index)?;
+    Ok(is_read(&scene, page_index, &pages, &ids))
+}
 
-    let (mut scene_index, new_page, scene_changed) = {
-        let scene = state.current_scene.lock().unwrap();
-        let page_index = state.current_page_index.lock().unwrap();
-        let scene_index = *state.current_scene_index.lock().unwrap();
+/// Get the number of pages of a scene not marked read yet
+#[tauri::command]
+pub async fn get_unread_count(scene_index: usize, state: State<'_, AppState>) -> Result<usize, AppError> {
+    let (scene, pages, ids) = scene_read_pages(&state, scene_index)?;
+    Ok(scene.page_count() - read_count(&scene, &pages, &ids))
+}
 
-        if let Some(scene) = scene.as_ref() {
-            let current_page = *page_index;
-            let total_pages = scene.page_count();
+/// Get whether pages are marked read as get_image returns them
+#[tauri::command]
+pub async fn get_auto_mark_read(state: State<'_, AppState>) -> Result<bool, AppError> {
+    Ok(*state.auto_mark_read.lock().unwrap())
+}
 
-            if scene_loop_enabled {
-                // Existing behavior: loop within scene
-                let new_page = (current_page + 1) % total_pages;
-                println!("Loop enabled - Current page: {}, Total pages: {}, New page: {}", current_page, total_pages, new_page);
-                (scene_index, new_page, false)
-            } else {
-                // New behavior: transition to next scene at boundary
-                if current_page + 1 >= total_pages {
-                    // At last page, move to next scene
-                    println!("At last page, moving to next scene");
-                    (scene_index, 0, true)
-                } else {
-                    let new_page = current_page + 1;
-                    println!("Normal navigation - Current page: {}, Total pages: {}, New page: {}", current_page, total_pages, new_page);
-                    (scene_index, new_page, false)
-                }
-            }
-        } else {
-            println!("ERROR: No scene loaded");
-            return Err("No scene loaded".to_string());
-        }
-    };
+/// Set whether pages are marked read as get_image returns them
+#[tauri::command]
+pub async fn set_auto_mark_read(enabled: bool, state: State<'_, AppState>) -> Result<(), AppError> {
+    *state.auto_mark_read.lock().unwrap() = enabled;
+    info!("Auto mark read {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
 
-    // Handle scene transition if needed
-    if scene_changed {
-        let collection = state.current_collection.lock().unwrap();
-        let mut scene_idx = state.current_scene_index.lock().unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if let Some(coll) = collection.as_ref() {
-            let new_scene_idx = (scene_index + 1) % coll.scene_count();
-            let scene = coll.load_scene(new_scene_idx)
-                .map_err(|e| format!("Failed to load next scene: {}", e))?;
+    #[test]
+    fn test_thumbnail_source_fallbacks() {
+        let scene: Scene = serde_json::from_str(r#"{
+            "metadata": {
+                "version": "1.0",
+                "sceneName": "Test",
+                "imageSize": { "width": 1920, "height": 1080 },
+                "thumbnailSize": { "width": 320, "height": 180 }
+            },
+            "pages": [{ "image": "/missing/a.jpg" }, { "image": "/missing/b.jpg" }]
+        }"#).unwrap();
+        let source = |fallback| thumbnail_source(&scene, "/missing/b.jpg", &fallback);
 
-            *state.current_scene.lock().unwrap() = Some(scene);
-            *scene_idx = new_scene_idx;
-            scene_index = new_scene_idx;
-            println!("Loaded next scene: {}", new_scene_idx);
-        } else {
-            return Err("No collection loaded".to_string());
-        }
+        assert_eq!(source(ThumbnailFallback::None), None);
+        assert_eq!(
+            source(ThumbnailFallback::GenerateFromMain),
+            Some((PathBuf::from("/missing/b.jpg"), false))
+        );
+        assert_eq!(
+            source(ThumbnailFallback::SceneCover),
+            Some((PathBuf::from("/missing/a.jpg"), false))
+        );
+        assert_eq!(
+            source(ThumbnailFallback::Placeholder("/missing/placeholder.png".to_string())),
+            Some((PathBuf::from("/missing/placeholder.png"), false))
+        );
     }
 
-    println!("Calling get_image with scene_index: {}, page: {}", scene_index, new_page);
-    let result = get_image(Some(scene_index), new_page, state.clone()).await;
+    #[test]
+    fn test_generated_thumbnail() {
+        let dir = std::env::temp_dir().join(format!("fastviewer-gen-thumb-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let main_path = dir.join("page.png");
+        image::RgbImage::from_pixel(400, 200, image::Rgb([200, 100, 50])).save(&main_path).unwrap();
+        let main_path = main_path.to_string_lossy().into_owned();
+        let scene: Scene = serde_json::from_value(serde_json::json!({
+            "metadata": { "sceneName": "Test", "thumbnailSize": { "width": 100, "height": 100 } },
+            "pages": [{ "image": main_path }]
+        })).unwrap();
 
-    // Preload next images in background (don't wait for completion)
-    if result.is_ok() {
-        // Clone the Arcs needed for background task
-        let cache = state.cache.clone();
-        let encoded_cache = state.encoded_cache.clone();
-        let current_scene = state.current_scene.clone();
-        let current_page_index = state.current_page_index.clone();
+        let state = AppState::new();
+        let without = load_thumbnail(&scene, &main_path, 0, &state);
+        *state.thumbnail_fallback.lock().unwrap() = ThumbnailFallback::GenerateFromMain;
+        let with = load_thumbnail(&scene, &main_path, 0, &state);
+        let generated = decode_thumbnail(&scene, &main_path, false, &state);
+        std::fs::remove_dir_all(&dir).unwrap();
 
-        tokio::spawn(async move {
-            let _ = preload_next_images_task(cache, encoded_cache, current_scene, current_page_index, 3).await;
-        });
+        assert_eq!(without, None);
+        assert!(with.is_some());
+        let key = generated_thumbnail_key(&main_path, 0, TierFormats::default().thumbnail);
+        assert_eq!(state.encoded_cache.get(&key), with);
+        assert_eq!(generated.unwrap().dimensions(), (100, 50));
     }
 
-    println!("=== next_page command completed ===");
-    result
-}
+    #[test]
+    fn test_check_unchanged() {
+        let coll = SceneCollection::from_scene_files(PathBuf::from("/books/a"), vec![PathBuf::from("s1.json"), PathBuf::from("s2.json")]);
+        let mut session = ViewerSession { collection: Some(coll.clone()), scene_index: 0, ..Default::default() };
+        assert!(session.check_unchanged(&coll, 1, Some(0)).is_ok());
+        assert!(session.check_unchanged(&coll, 1, Some(1)).is_err());
 
-/// Navigate to the previous page
-#[tauri::command]
-pub async fn prev_page(state: State<'_, AppState>) -> Result<ImageData, String> {
-    println!("=== prev_page command called ===");
-    let scene_loop_enabled = *state.scene_loop_enabled.lock().unwrap();
+        session.collection = Some(SceneCollection::from_scene_files(PathBuf::from("/books/b"), coll.scene_files.clone()));
+        assert!(session.check_unchanged(&coll, 1, None).is_err());
+        session.collection = Some(SceneCollection::from_scene_files(PathBuf::from("/books/a"), vec![PathBuf::from("s1.json"), PathBuf::from("s3.json")]));
+        assert!(session.check_unchanged(&coll, 1, None).is_err());
+        session.collection = None;
+        assert!(session.check_unchanged(&coll, 1, None).is_err());
+    }
 
-    let (mut scene_index, new_page, scene_changed) = {
-        let scene = state.current_scene.lock().unwrap();
-        let page_index = state.current_page_index.lock().unwrap();
-        let scene_index = *state.current_scene_index.lock().unwrap();
+    #[test]
+    fn test_record_page_count_of_open_collection_only() {
+        let open = SceneCollection::from_scene_files(PathBuf::from("/books/a"), vec![PathBuf::from("s1.json")]);
+        let other = SceneCollection::from_scene_files(PathBuf::from("/books/b"), vec![PathBuf::from("s1.json")]);
+        let session = RwLock::new(ViewerSession { collection: Some(open.clone()), ..Default::default() });
+        let counts = Mutex::new(HashMap::new());
 
-        if let Some(scene) = scene.as_ref() {
-            let current_page = *page_index;
-            let total_pages = scene.page_count();
+        record_page_count(&session, &counts, &other, 0, 7);
+        assert!(counts.lock().unwrap().is_empty());
+        record_page_count(&session, &counts, &open, 0, 3);
+        assert_eq!(counts.lock().unwrap().get(&0), Some(&3));
+    }
 
-            if scene_loop_enabled {
-                // Existing behavior: loop within scene
-                let new_page = if current_page == 0 {
-                    total_pages - 1
-                } else {
-                    current_page - 1
-                };
-                println!("Loop enabled - Current page: {}, Total pages: {}, New page: {}", current_page, total_pages, new_page);
-                (scene_index, new_page, false)
-            } else {
-                // New behavior: transition to previous scene at boundary
-                if current_page == 0 {
-                    // At first page, move to previous scene (will load last page of that scene)
-                    println!("At first page, moving to previous scene");
-                    (scene_index, 0, true) // Placeholder page, will be updated after loading scene
-                } else {
-                    let new_page = current_page - 1;
-                    println!("Normal navigation - Current page: {}, Total pages: {}, New page: {}", current_page, total_pages, new_page);
-                    (scene_index, new_page, false)
-                }
-            }
-        } else {
-            println!("ERROR: No scene loaded");
-            return Err("No scene loaded".to_string());
-        }
-    };
+    #[test]
+    fn test_run_bounded() {
+        use std::sync::atomic::AtomicUsize;
 
-    // Handle scene transition if needed
-    let mut final_page = new_page;
-    if scene_changed {
-        let collection = state.current_collection.lock().unwrap();
-        let mut scene_idx = state.current_scene_index.lock().unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let mut results = Vec::new();
+        runtime.block_on(run_bounded(0..10, 3, |tasks, i| {
+            let (running, peak) = (running.clone(), peak.clone());
+            tasks.spawn(async move {
+                peak.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                i * 2
+            });
+        }, |result| {
+            results.push(result.unwrap());
+            Ok(())
+        })).unwrap();
 
-        if let Some(coll) = collection.as_ref() {
-            let new_scene_idx = if scene_index == 0 {
-                coll.scene_count() - 1
-            } else {
-                scene_index - 1
-            };
+        results.sort();
+        assert_eq!(results, (0..10).map(|i| i * 2).collect::<Vec<_>>());
+        assert!(peak.load(Ordering::SeqCst) <= 3);
 
-            let scene = coll.load_scene(new_scene_idx)
-                .map_err(|e| format!("Failed to load previous scene: {}", e))?;
+        let stopped = runtime.block_on(run_bounded(0..10, 2, |tasks, i| {
+            tasks.spawn(async move { i });
+        }, |_| Err("stop".to_string())));
+        assert_eq!(stopped, Err("stop".to_string()));
+    }
 
-            // Get the last page of the previous scene
-            final_page = scene.page_count().saturating_sub(1);
+    #[test]
+    fn test_inflight_upgrade_released_on_panic() {
+        let inflight_upgrades = Arc::new(Mutex::new(HashSet::from(["a.jpg".to_string()])));
+        let inflight = InflightUpgrade { inflight_upgrades: inflight_upgrades.clone(), path: "a.jpg".to_string() };
 
-            *state.current_scene.lock().unwrap() = Some(scene);
-            *scene_idx = new_scene_idx;
-            scene_index = new_scene_idx;
-            println!("Loaded previous scene: {}, last page: {}", new_scene_idx, final_page);
-        } else {
-            return Err("No collection loaded".to_string());
-        }
+        let result = std::thread::spawn(move || {
+            let _inflight = inflight;
+            panic!("upgrade failed");
+        }).join();
+        assert!(result.is_err());
+        assert!(inflight_upgrades.lock().unwrap().is_empty());
     }
 
-    println!("Calling get_image with scene_index: {}, page: {}", scene_index, final_page);
-    let result = get_image(Some(scene_index), final_page, state.clone()).await;
+    #[test]
+    fn test_page_step_follows_reading_direction() {
+        use NavigationDirection::{Backward, Forward};
+        assert_eq!(page_step(ReadingDirection::LeftToRight, true), Forward);
+        assert_eq!(page_step(ReadingDirection::LeftToRight, false), Backward);
+        assert_eq!(page_step(ReadingDirection::RightToLeft, true), Backward);
+        assert_eq!(page_step(ReadingDirection::RightToLeft, false), Forward);
+    }
 
-    // Preload next images in background (don't wait for completion)
-    if result.is_ok() {
-        // Clone the Arcs needed for background task
-        let cache = state.cache.clone();
-        let encoded_cache = state.encoded_cache.clone();
-        let current_scene = state.current_scene.clone();
-        let current_page_index = state.current_page_index.clone();
+    #[test]
+    fn test_spread_sides() {
+        use ReadingDirection::{LeftToRight, RightToLeft};
+        use SpreadMode::{CoverFirst, Paired};
 
-        tokio::spawn(async move {
-            let _ = preload_next_images_task(cache, encoded_cache, current_scene, current_page_index, 3).await;
-        });
+        // Even page count: the cover alone, then pairs, the last page alone
+        let sides = |page| spread_sides(page, 4, CoverFirst, LeftToRight);
+        assert_eq!(sides(0), Some((Some(0), None)));
+        assert_eq!(sides(1), Some((Some(1), Some(2))));
+        assert_eq!(sides(2), Some((Some(1), Some(2))));
+        assert_eq!(sides(3), Some((Some(3), None)));
+        assert_eq!(sides(4), None);
+
+        // Odd page count: the last two pages pair up
+        assert_eq!(spread_sides(4, 5, CoverFirst, LeftToRight), Some((Some(3), Some(4))));
+        assert_eq!(spread_sides(4, 5, Paired, LeftToRight), Some((Some(4), None)));
+        assert_eq!(spread_sides(1, 4, Paired, LeftToRight), Some((Some(0), Some(1))));
+
+        // Right to left: the first page of a spread, or a lone page, on the right
+        assert_eq!(spread_sides(1, 4, CoverFirst, RightToLeft), Some((Some(2), Some(1))));
+        assert_eq!(spread_sides(0, 4, CoverFirst, RightToLeft), Some((None, Some(0))));
+        assert_eq!(spread_sides(0, 1, Paired, RightToLeft), Some((None, Some(0))));
+        assert_eq!(spread_sides(0, 0, Paired, RightToLeft), None);
     }
 
-    println!("=== prev_page command completed ===");
-    result
-}
+    #[test]
+    fn test_slideshow_task_lifecycle() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let state = AppState::new();
+        // Aborted tasks finish once the runtime gets to them
+        let finishes = |task: tokio::task::AbortHandle| async move {
+            tokio::time::timeout(Duration::from_secs(5), async {
+                while !task.is_finished() {
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                }
+            }).await.is_ok()
+        };
+        runtime.block_on(async {
+            assert!(!slideshow_running(&state));
 
-/// Background task to preload next images
-async fn preload_next_images_task(
-    cache: Arc<ImageCache>,
-    encoded_cache: Arc<EncodedImageCache>,
-    current_scene: Arc<Mutex<Option<Scene>>>,
-    current_page_index: Arc<Mutex<usize>>,
-    count: usize,
-) -> Result<(), String> {
-    println!("=== Preloading next {} images ===", count);
+            let first = tokio::spawn(std::future::pending::<()>());
+            let first_abort = first.abort_handle();
+            assert!(!set_slideshow_task(&state, Some(first)));
+            assert!(slideshow_running(&state));
 
-    let scene_guard = current_scene.lock().unwrap();
-    let page_index = *current_page_index.lock().unwrap();
+            // Starting again cancels the running task instead of leaking it
+            let second = tokio::spawn(std::future::pending::<()>());
+            let second_abort = second.abort_handle();
+            assert!(set_slideshow_task(&state, Some(second)));
+            assert!(finishes(first_abort).await);
+            assert!(slideshow_running(&state));
 
-    if let Some(scene) = scene_guard.as_ref() {
-        let total_pages = scene.page_count();
+            assert!(set_slideshow_task(&state, None));
+            assert!(finishes(second_abort).await);
+            assert!(!slideshow_running(&state));
+            assert!(!set_slideshow_task(&state, None));
+        });
+    }
 
-        // Get paths to preload
-        let mut paths_to_load = Vec::new();
-        for i in 1..=count {
-            let next_page = (page_index + i) % total_pages;
-            if let Some(path) = scene.get_page_image(next_page) {
-                paths_to_load.push((path.to_string(), 85)); // main image with quality 85
+    #[test]
+    fn test_session_readers_do_not_block_each_other() {
+        let state = AppState::new();
+        let _reader = state.session.read().unwrap();
 
-                // Also get thumbnail path
-                let thumb_path = scene.get_thumbnail_path(path);
-                if thumb_path.exists() {
-                    if let Some(thumb_str) = thumb_path.to_str() {
-                        paths_to_load.push((thumb_str.to_string(), 75)); // thumbnail with quality 75
-                    }
-                }
-            }
-        }
+        // A second reader (e.g. a get_scene_info poll) gets in right away,
+        // while navigation has to wait for both
+        assert!(state.session.try_read().is_ok());
+        assert!(state.session.try_write().is_err());
+    }
 
-        drop(scene_guard); // Release lock before loading operations
+    #[test]
+    fn test_session_concurrent_readers_and_writers() {
+        // Scene i has i + 1 pages and is named after its index
+        let scene = |i: usize| -> Scene {
+            serde_json::from_value(serde_json::json!({
+                "metadata": { "sceneName": format!("scene-{}", i) },
+                "pages": (0..=i).map(|page| serde_json::json!({ "image": format!("{}.jpg", page) })).collect::<Vec<_>>(),
+            })).unwrap()
+        };
+        let state = AppState::new();
+        state.session.write().unwrap().enter_scene(0, scene(0));
 
-        // Load images into cache and encode them
-        for (path, quality) in paths_to_load {
-            // Skip if already in encoded cache
-            if encoded_cache.get(&path).is_some() {
-                println!("Already in encoded cache: {}", path);
-                continue;
+        std::thread::scope(|threads| {
+            for writer in 0..3 {
+                let state = &state;
+                threads.spawn(move || {
+                    for step in 0..300 {
+                        let index = (writer * 7 + step) % 10;
+                        let mut session = state.session.write().unwrap();
+                        session.enter_scene(index, scene(index));
+                        session.page_index = index;
+                    }
+                });
             }
-
-            match load_image_cached(&path, &cache) {
-                Ok(img) => {
-                    println!("Preloaded to image cache: {}", path);
-                    // Encode and store in encoded cache
-                    match image_to_base64_jpeg(&img, quality) {
-                        Ok(base64) => {
-                            encoded_cache.insert(path.clone(), base64);
-                            println!("Encoded and cached: {}", path);
-                        }
-                        Err(e) => eprintln!("Failed to encode {}: {}", path, e),
+            for _ in 0..16 {
+                let state = &state;
+                threads.spawn(move || {
+                    for _ in 0..2000 {
+                        // Every reader sees scene, index and page from the same write
+                        let session = state.session.read().unwrap();
+                        let scene = session.scene.as_ref().unwrap();
+                        assert_eq!(scene.metadata.scene_name, format!("scene-{}", session.scene_index));
+                        assert!(session.page_index < scene.page_count());
                     }
-                }
-                Err(e) => eprintln!("Failed to preload {}: {}", path, e),
+                });
             }
+        });
+    }
+
+    #[test]
+    fn test_display_resolution_tiers() {
+        let default = DisplayResolution::default();
+        assert_eq!((default.high_dimension(), default.preview_dimension()), (1920, 640));
+
+        let portrait = DisplayResolution { width: 1440, height: 2560 };
+        assert_eq!((portrait.high_dimension(), portrait.preview_dimension()), (2560, 853));
+
+        let tiny = DisplayResolution { width: 2, height: 1 };
+        assert_eq!(tiny.preview_dimension(), 1);
+
+        assert_eq!(portrait.tier_dimensions(1920), (1920, 853));
+        assert_eq!(portrait.tier_dimensions(500), (500, 500));
+        assert_eq!(default.tier_dimensions(4096), (1920, 640));
+    }
+
+    #[test]
+    fn test_navigation_direction() {
+        let mut history = NavigationHistory::default();
+        assert_eq!(history.predicted(), NavigationDirection::Forward);
+
+        history.record(NavigationDirection::Backward);
+        assert_eq!(history.predicted(), NavigationDirection::Backward);
+        history.record(NavigationDirection::Forward);
+        assert_eq!(history.predicted(), NavigationDirection::Forward);
+
+        // Only the last NAVIGATION_HISTORY_LEN moves count
+        for _ in 0..NAVIGATION_HISTORY_LEN {
+            history.record(NavigationDirection::Backward);
         }
-        println!("=== Preloading completed ===");
+        history.record(NavigationDirection::Forward);
+        assert_eq!(history.moves.len(), NAVIGATION_HISTORY_LEN);
+        assert_eq!(history.predicted(), NavigationDirection::Backward);
+
+        let counts = PreloadCounts::default();
+        assert_eq!(preload_offsets(NavigationDirection::Forward, counts), [1, 2, 3, -1, -2]);
+        assert_eq!(preload_offsets(NavigationDirection::Backward, counts), [-1, -2, -3, 1, 2]);
+        assert_eq!(preload_offsets(NavigationDirection::Forward, PreloadCounts { ahead: 1, behind: 0 }), [1]);
     }
 
-    Ok(())
-}
+    #[test]
+    fn test_preload_pages() {
+        let offsets = preload_offsets(NavigationDirection::Forward, PreloadCounts::default());
+        assert_eq!(preload_pages(5, 10, &offsets), [6, 7, 8, 4, 3]);
+        // Near the first and last page the indices wrap around the scene
+        assert_eq!(preload_pages(0, 10, &offsets), [1, 2, 3, 9, 8]);
+        assert_eq!(preload_pages(9, 10, &offsets), [0, 1, 2, 8, 7]);
 
-/// Get list of available scene collections
-#[tauri::command]
-pub async fn get_scene_list(parent_dir: String) -> Result<Vec<SceneListItem>, String> {
-    let collections = SceneCollection::find_scene_collections(&parent_dir)
-        .map_err(|e| format!("Failed to find scene collections: {}", e))?;
+        let offsets = preload_offsets(NavigationDirection::Backward, PreloadCounts::default());
+        assert_eq!(preload_pages(0, 10, &offsets), [9, 8, 7, 1, 2]);
 
-    let items = collections
-        .into_iter()
-        .map(|path| SceneListItem {
-            name: path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("Unknown")
-                .to_string(),
-            path: path.to_string_lossy().to_string(),
-        })
-        .collect();
+        // A short scene yields each other page once, never the current one
+        assert_eq!(preload_pages(0, 3, &offsets), [2, 1]);
+        assert!(preload_pages(0, 1, &offsets).is_empty());
+        assert!(preload_pages(0, 0, &offsets).is_empty());
+    }
 
-    Ok(items)
-}
+    #[test]
+    fn test_dimension_summary() {
+        assert!(DimensionSummary::from_dimensions(&[]).is_none());
 
-/// Navigate to next scene
-#[tauri::command]
-pub async fn next_scene(state: State<'_, AppState>) -> Result<SceneInfo, String> {
-    {
-        let collection = state.current_collection.lock().unwrap();
-        let mut scene_index = state.current_scene_index.lock().unwrap();
+        let summary = DimensionSummary::from_dimensions(&[(800, 1200), (800, 1200)]).unwrap();
+        assert!(summary.consistent);
+        assert_eq!((summary.avg.width, summary.avg.height), (800, 1200));
 
-        if let Some(coll) = collection.as_ref() {
-            let new_index = (*scene_index + 1) % coll.scene_count();
+        let summary = DimensionSummary::from_dimensions(&[(800, 1200), (1000, 1100), (801, 1201)]).unwrap();
+        assert!(!summary.consistent);
+        assert_eq!((summary.min.width, summary.min.height), (800, 1100));
+        assert_eq!((summary.max.width, summary.max.height), (1000, 1201));
+        assert_eq!((summary.avg.width, summary.avg.height), (867, 1167));
+    }
 
-            let scene = coll.load_scene(new_index)
-                .map_err(|e| format!("Failed to load next scene: {}", e))?;
+    #[test]
+    fn test_read_pages_by_id() {
+        let mut scene: Scene = serde_json::from_str(r#"{
+            "metadata": { "sceneName": "Test" },
+            "pages": [{ "image": "a.jpg", "id": "a" }, { "image": "b.jpg" }, { "image": "c.jpg", "id": "c" }]
+        }"#).unwrap();
+        let mut pages = PageSet::default();
+        pages.insert(1);
+        let ids = HashSet::from(["c".to_string()]);
 
-            *state.current_scene.lock().unwrap() = Some(scene);
-            *scene_index = new_index;
-            *state.current_page_index.lock().unwrap() = 0;
-        } else {
-            return Err("No collection loaded".to_string());
-        }
+        assert!(!is_read(&scene, 0, &pages, &ids));
+        assert!(is_read(&scene, 1, &pages, &ids));
+        assert!(is_read(&scene, 2, &pages, &ids));
+        assert_eq!(read_count(&scene, &pages, &ids), 2);
+
+        // The page with an ID stays read when moved; the one without follows its index
+        scene.move_page(2, 0).unwrap();
+        assert!(is_read(&scene, 0, &pages, &ids));
+        assert!(!is_read(&scene, 2, &pages, &ids));
     }
 
-    get_scene_info(state).await
-}
+    #[test]
+    fn test_layout_hint() {
+        let hint = LayoutHint::from_dimensions(&[(800, 1200), (800, 1200), (800, 1200), (800, 1200), (1600, 1200)]);
+        assert_eq!(hint.orientation, Orientation::Portrait);
+        assert!((hint.avg_aspect - (4.0 * 800.0 / 1200.0 + 1600.0 / 1200.0) / 5.0).abs() < 1e-9);
 
-/// Navigate to previous scene
-#[tauri::command]
-pub async fn prev_scene(state: State<'_, AppState>) -> Result<SceneInfo, String> {
-    {
-        let collection = state.current_collection.lock().unwrap();
-        let mut scene_index = state.current_scene_index.lock().unwrap();
+        let hint = LayoutHint::from_dimensions(&[(1920, 1080), (1920, 1080)]);
+        assert_eq!(hint.orientation, Orientation::Landscape);
 
-        if let Some(coll) = collection.as_ref() {
-            let new_index = if *scene_index == 0 {
-                coll.scene_count() - 1
-            } else {
-                *scene_index - 1
-            };
+        let hint = LayoutHint::from_dimensions(&[(800, 1200), (1920, 1080)]);
+        assert_eq!(hint.orientation, Orientation::Mixed);
+
+        assert_eq!(LayoutHint::from_dimensions(&[(0, 0)]).orientation, Orientation::Mixed);
+    }
+
+    #[test]
+    fn test_pyramid_sizes() {
+        assert_eq!(pyramid_sizes(2048, 4), vec![2048, 1024, 512, 256]);
+        assert_eq!(pyramid_sizes(3, 4), vec![3, 1]);
+        assert_eq!(pyramid_sizes(100, 1), vec![100]);
+    }
 
-            let scene = coll.load_scene(new_index)
-                .map_err(|e| format!("Failed to load previous scene: {}", e))?;
+    #[test]
+    fn test_empty_scene_navigation_errors() {
+        let scene: Scene = serde_json::from_str(r#"{
+            "metadata": { "sceneName": "Empty" },
+            "pages": []
+        }"#).unwrap();
+        assert_eq!(navigable_page_count(&scene), Err("Scene has no pages".to_string()));
+        assert!(resolve_skip(0, 0, 1, 1, LoopMode::Scene, |_| Ok(scene.page_count())).is_err());
 
-            *state.current_scene.lock().unwrap() = Some(scene);
-            *scene_index = new_index;
-            *state.current_page_index.lock().unwrap() = 0;
-        } else {
-            return Err("No collection loaded".to_string());
+        let state = AppState::new();
+        state.session.write().unwrap().scene = Some(Arc::new(scene));
+        assert!(peek_page(&state, true).is_err());
+        assert!(peek_page(&state, false).is_err());
+    }
+
+    #[test]
+    fn test_empty_collection_navigation_errors() {
+        let coll = SceneCollection::from_scene_files(PathBuf::from("/scenes-empty"), Vec::new());
+        assert_eq!(navigable_scene_count(&coll), Err("Collection is empty".to_string()));
+
+        // A scene left open while its collection's files disappeared
+        let scene: Scene = serde_json::from_str(r#"{
+            "metadata": { "sceneName": "Orphan" },
+            "pages": [{ "image": "/missing/a.jpg" }]
+        }"#).unwrap();
+        let state = AppState::new();
+        state.session.write().unwrap().open(coll, scene);
+        assert_eq!(peek_page(&state, true).unwrap_err().to_string(), "Collection is empty");
+        assert_eq!(peek_page(&state, false).unwrap_err().to_string(), "Collection is empty");
+    }
+
+    #[test]
+    fn test_clear_image_caches() {
+        let state = AppState::new();
+        state.cache.insert("/a.jpg".to_string(), Arc::new(DynamicImage::new_rgb8(2, 2)));
+        state.encoded_cache.insert("/a.jpg".to_string(), "data".to_string());
+
+        let cleared = clear_image_caches(&state, true);
+        assert_eq!(cleared, ClearedCaches { entries: 1, bytes: 4 });
+        assert_eq!((state.cache.size(), state.encoded_cache.size()), (1, 0));
+
+        let cleared = clear_image_caches(&state, false);
+        assert_eq!(cleared, ClearedCaches { entries: 1, bytes: 2 * 2 * 3 });
+        assert_eq!((state.cache.size(), state.encoded_cache.size()), (0, 0));
+    }
+
+    #[test]
+    fn test_update_max_dimension() {
+        let state = AppState::new();
+        assert_eq!(*state.max_dimension.lock().unwrap(), MAX_DIMENSION);
+        for key in ["/a.jpg@1920", "/a.jpg@640"] {
+            state.cache.insert(key.to_string(), Arc::new(DynamicImage::new_rgb8(1, 1)));
         }
+        state.encoded_cache.insert("/a.jpg".to_string(), "data".to_string());
+
+        // 1920 is still the high tier of the default 1920x1080 screen
+        update_max_dimension(&state, 2560);
+        assert_eq!(*state.max_dimension.lock().unwrap(), 2560);
+        assert!(state.cache.get("/a.jpg@1920").is_some());
+        assert!(state.cache.get("/a.jpg@640").is_some());
+        assert_eq!(state.encoded_cache.size(), 0);
+
+        state.cache.insert("/t.jpg@2560".to_string(), Arc::new(DynamicImage::new_rgb8(1, 1)));
+        update_max_dimension(&state, 1280);
+        assert!(state.cache.get("/t.jpg@2560").is_none());
+        assert!(state.cache.get("/a.jpg@640").is_some());
+        assert_eq!(state.display_resolution.lock().unwrap().tier_dimensions(1280), (1280, 640));
     }
 
-    get_scene_info(state).await
-}
+    #[test]
+    fn test_find_scene_by_name() {
+        let dir = std::env::temp_dir().join(format!("fastviewer-names-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, name) in [("scene_1.json", "Intro"), ("scene_2.json", "Chapter One"), ("scene_10.json", "Finale")] {
+            let json = format!(r#"{{ "metadata": {{ "sceneName": "{}" }}, "pages": [{{ "image": "a.jpg" }}] }}"#, name);
+            std::fs::write(dir.join(file), json).unwrap();
+        }
+        std::fs::write(dir.join("scene_3.json"), "not json").unwrap();
+        let coll = SceneCollection::new(&dir, &SceneCollectionConfig::default()).unwrap();
 
-/// Get scene loop enabled state
-#[tauri::command]
-pub async fn get_scene_loop_enabled(state: State<'_, AppState>) -> Result<bool, String> {
-    Ok(*state.scene_loop_enabled.lock().unwrap())
-}
+        let find = |name, ignore_case| find_scene_by_name(&coll, name, ignore_case);
+        assert_eq!(find("Chapter One", false), Some(1));
+        assert_eq!(find("chapter one", false), None);
+        assert_eq!(find("chapter one", true), Some(1));
+        // Past the unreadable scene_3
+        assert_eq!(find("Finale", false), Some(3));
+        assert_eq!(find("scene_3", false), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 
-/// Set scene loop enabled state
-#[tauri::command]
-pub async fn set_scene_loop_enabled(enabled: bool, state: State<'_, AppState>) -> Result<(), String> {
-    *state.scene_loop_enabled.lock().unwrap() = enabled;
-    Ok(())
-}
\ No newline at end of file
+    #[test]
+    fn test_resolve_skip() {
+        let counts = [3, 0, 2, 4];
+        let skip = |scene, page, delta, mode| {
+            resolve_skip(scene, page, delta, counts.len(), mode, |i| Ok(counts[i]))
+        };
+
+        // Within a scene, and across an empty scene into the next ones
+        assert_eq!(skip(0, 0, 2, LoopMode::None), Ok((0, 2)));
+        assert_eq!(skip(0, 1, 3, LoopMode::None), Ok((2, 1)));
+        assert_eq!(skip(0, 1, 5, LoopMode::None), Ok((3, 1)));
+        assert_eq!(skip(3, 1, -4, LoopMode::None), Ok((0, 2)));
+
+        // Clamped at the ends without looping
+        assert_eq!(skip(2, 0, 100, LoopMode::None), Ok((3, 3)));
+        assert_eq!(skip(2, 0, -100, LoopMode::None), Ok((0, 0)));
+
+        // Wrapped around the collection (9 pages in total)
+        assert_eq!(skip(3, 3, 1, LoopMode::Collection), Ok((0, 0)));
+        assert_eq!(skip(0, 0, -1, LoopMode::Collection), Ok((3, 3)));
+        assert_eq!(skip(0, 0, 9 * 1000 + 4, LoopMode::Collection), Ok((2, 1)));
+        assert_eq!(skip(0, 0, -9 * 1000 - 1, LoopMode::Collection), Ok((3, 3)));
+
+        // Wrapped within the scene
+        assert_eq!(skip(3, 1, 7, LoopMode::Scene), Ok((3, 0)));
+        assert_eq!(skip(1, 0, 1, LoopMode::Scene), Err("No pages to navigate to".to_string()));
+    }
+
+    #[test]
+    fn test_scene_list_item_name() {
+        let item = SceneListItem::from_path(Path::new("/library/scenes-日本語"));
+        assert_eq!(item.name, "scenes-日本語");
+        assert_eq!(item.path, "/library/scenes-日本語");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scene_list_item_non_utf8_names_stay_distinct() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let a = Path::new("/library").join(OsStr::from_bytes(b"scenes-\xFFa"));
+        let b = Path::new("/library").join(OsStr::from_bytes(b"scenes-\xFFb"));
+
+        let item_a = SceneListItem::from_path(&a);
+        let item_b = SceneListItem::from_path(&b);
+        assert_eq!(item_a.name, "scenes-\u{FFFD}a");
+        assert_ne!(item_a.name, item_b.name);
+    }
+}
@@ -0,0 +1,103 @@
+use log::{error, info};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// Thread pool for background decoding, resizing and encoding
+///
+/// Kept apart from tokio's blocking pool so bulk decode work (preloading,
+/// cover warming, exports) neither starves nor gets starved by other blocking
+/// work. Header reads and directory scans stay on the blocking pool.
+static POOL: RwLock<Option<Arc<ThreadPool>>> = RwLock::new(None);
+
+/// Default number of decode threads: one per core
+pub fn default_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
+
+fn build_pool(threads: usize) -> Result<ThreadPool, String> {
+    ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|i| format!("decode-{}", i))
+        // Without a handler a panicking job would abort the whole app
+        .panic_handler(|_| error!("Decode task panicked"))
+        .build()
+        .map_err(|e| format!("Failed to start decode threads: {}", e))
+}
+
+/// The current pool, started with default_threads on first use
+fn pool() -> Arc<ThreadPool> {
+    if let Some(pool) = POOL.read().unwrap().as_ref() {
+        return pool.clone();
+    }
+    let mut slot = POOL.write().unwrap();
+    slot.get_or_insert_with(|| Arc::new(build_pool(default_threads()).expect("default decode pool starts")))
+        .clone()
+}
+
+/// Number of threads of the decode pool
+pub fn threads() -> usize {
+    pool().current_num_threads()
+}
+
+/// Replace the decode pool with one of `threads` threads (at least 1)
+///
+/// Jobs already running finish on the old pool.
+pub fn set_threads(threads: usize) -> Result<(), String> {
+    let pool = build_pool(threads.max(1))?;
+    *POOL.write().unwrap() = Some(Arc::new(pool));
+    info!("Using {} decode threads", threads.max(1));
+    Ok(())
+}
+
+/// A decode pool job panicked instead of returning a result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobPanicked;
+
+impl fmt::Display for JobPanicked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Decode task panicked")
+    }
+}
+
+impl std::error::Error for JobPanicked {}
+
+/// Run `job` on the decode pool and wait for its result without blocking
+/// the async runtime
+///
+/// A panic in `job` is logged by the pool and returned as JobPanicked, so
+/// one bad image doesn't take down the task waiting for it.
+pub async fn run<T, F>(job: F) -> Result<T, JobPanicked>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    pool().spawn(move || {
+        let _ = tx.send(job());
+    });
+    rx.await.map_err(|_| JobPanicked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_pool() {
+        let pool = build_pool(2).unwrap();
+        assert_eq!(pool.current_num_threads(), 2);
+
+        let name = pool.install(|| std::thread::current().name().map(str::to_string));
+        assert!(name.is_some_and(|name| name.starts_with("decode-")));
+    }
+
+    #[test]
+    fn test_run_reports_panic() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        assert_eq!(runtime.block_on(run(|| 42)), Ok(42));
+        assert_eq!(runtime.block_on(run(|| -> u32 { panic!("bad image") })), Err(JobPanicked));
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::{Context, Result};
+use log::debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Directory in the app cache directory holding encoded thumbnails
+pub const DISK_THUMBNAIL_DIR: &str = "thumbnails";
+
+/// On-disk store of encoded thumbnails, so generated ones survive restarts
+///
+/// Entries are keyed by a hash of the source file's path, modification time
+/// and size plus a variant (rotation, format, target size), so an edited
+/// source file simply misses and gets a new entry.
+#[derive(Debug, Clone)]
+pub struct DiskThumbnailCache {
+    dir: PathBuf,
+}
+
+impl DiskThumbnailCache {
+    pub fn new(dir: PathBuf) -> Self {
+        DiskThumbnailCache { dir }
+    }
+
+    /// Read the encoded thumbnail of `source` for `variant`, if stored
+    pub fn get(&self, source: &Path, variant: &str) -> Option<String> {
+        let path = self.entry_path(source, variant)?;
+        let encoded = fs::read_to_string(&path).ok()?;
+        debug!("Disk thumbnail cache hit: {:?}", source);
+        Some(encoded)
+    }
+
+    /// Store the encoded thumbnail of `source` for `variant`
+    pub fn insert(&self, source: &Path, variant: &str, encoded: &str) -> Result<()> {
+        let path = self.entry_path(source, variant)
+            .with_context(|| format!("Failed to read metadata of {:?}", source))?;
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {:?}", self.dir))?;
+
+        // Write then rename so a concurrent reader never sees half an entry
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, encoded)
+            .with_context(|| format!("Failed to write {:?}", tmp_path))?;
+        fs::rename(&tmp_path, &path)
+            .with_context(|| format!("Failed to replace {:?}", path))?;
+        Ok(())
+    }
+
+    /// Delete every stored thumbnail, returning the number of bytes freed
+    pub fn clear(&self) -> Result<u64> {
+        if !self.dir.exists() {
+            return Ok(0);
+        }
+
+        let mut freed = 0;
+        for entry in fs::read_dir(&self.dir).with_context(|| format!("Failed to read {:?}", self.dir))? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            fs::remove_file(&path).with_context(|| format!("Failed to delete {:?}", path))?;
+            freed += size;
+        }
+        Ok(freed)
+    }
+
+    /// File holding the entry for `source` and `variant`; None if the source
+    /// can't be stat'ed
+    fn entry_path(&self, source: &Path, variant: &str) -> Option<PathBuf> {
+        let metadata = fs::metadata(source).ok()?;
+        let modified = metadata.modified().ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_nanos())
+            .unwrap_or(0);
+        let key = format!("{}\0{}\0{}\0{}", source.to_string_lossy(), modified, metadata.len(), variant);
+        Some(self.dir.join(format!("{:016x}.thumb", fnv1a(key.as_bytes()))))
+    }
+}
+
+/// 64-bit FNV-1a hash; unlike DefaultHasher it is stable across builds, so
+/// entries stay valid after an update
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fnv1a() {
+        // Reference values of the 64-bit FNV-1a test vectors
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+}
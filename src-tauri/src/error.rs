@@ -0,0 +1,108 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::fmt;
+
+/// Error returned by the Tauri commands
+///
+/// Serialized as `{ "kind": "PageOutOfBounds", "message": "...", ... }` so the
+/// frontend can branch on `kind` instead of matching error text; `message` is
+/// the Display text. Errors without a dedicated variant are `Other`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppError {
+    NoSceneLoaded,
+    NoCollectionLoaded,
+    PageOutOfBounds { index: usize, total: usize },
+    SceneLoadFailed(String),
+    ImageDecodeFailed(String),
+    /// An encrypted archive needs a password and none or the wrong one was given
+    WrongPassword,
+    Other(String),
+}
+
+impl AppError {
+    /// Variant name, the `kind` field of the serialized error
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::NoSceneLoaded => "NoSceneLoaded",
+            AppError::NoCollectionLoaded => "NoCollectionLoaded",
+            AppError::PageOutOfBounds { .. } => "PageOutOfBounds",
+            AppError::SceneLoadFailed(_) => "SceneLoadFailed",
+            AppError::ImageDecodeFailed(_) => "ImageDecodeFailed",
+            AppError::WrongPassword => "WrongPassword",
+            AppError::Other(_) => "Other",
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::NoSceneLoaded => write!(f, "No scene loaded"),
+            AppError::NoCollectionLoaded => write!(f, "No collection loaded"),
+            AppError::PageOutOfBounds { index, total } => {
+                write!(f, "Page index {} out of bounds (total: {})", index, total)
+            }
+            AppError::WrongPassword => write!(f, "Wrong or missing password for encrypted archive"),
+            AppError::SceneLoadFailed(message)
+            | AppError::ImageDecodeFailed(message)
+            | AppError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("AppError", 4)?;
+        error.serialize_field("kind", self.kind())?;
+        error.serialize_field("message", &self.to_string())?;
+        if let AppError::PageOutOfBounds { index, total } = self {
+            error.serialize_field("index", index)?;
+            error.serialize_field("total", total)?;
+        }
+        error.end()
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Other(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::Other(message.to_string())
+    }
+}
+
+impl From<crate::decode_pool::JobPanicked> for AppError {
+    fn from(error: crate::decode_pool::JobPanicked) -> Self {
+        AppError::ImageDecodeFailed(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_error_serialization() {
+        let error = AppError::PageOutOfBounds { index: 5, total: 3 };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "kind": "PageOutOfBounds",
+                "message": "Page index 5 out of bounds (total: 3)",
+                "index": 5,
+                "total": 3,
+            })
+        );
+
+        let error = AppError::from("Target size must be greater than zero");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "kind": "Other", "message": "Target size must be greater than zero" })
+        );
+    }
+}
@@ -1,8 +1,73 @@
 use anyhow::{Context, Result};
+use crate::archive;
 use std::path::Path;
+use std::io;
 use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+use log::{debug, warn};
 use image::{DynamicImage, GenericImageView};  // GenericImageViewを追加
+use image::metadata::Orientation;
+use serde::{Deserialize, Serialize};
+
+/// Default maximum dimension for decoded images (the high-res tier)
+pub const MAX_DIMENSION: u32 = 1920;
+
+/// Maximum dimension of the quick preview tier used by progressive loading
+pub const PREVIEW_DIMENSION: u32 = 640;
+
+/// Upper bound for caller-requested sizes, to prevent huge allocations
+pub const MAX_TARGET_SIZE: u32 = 8192;
+
+/// Delay before the first retry of a failed load; doubled for each further retry
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// Number of times a load is retried after a transient I/O error
+static LOAD_RETRIES: AtomicU32 = AtomicU32::new(2);
+
+/// Set how many times a load is retried after a transient I/O error
+pub fn set_load_retries(count: u32) {
+    LOAD_RETRIES.store(count, Ordering::Relaxed);
+}
+
+/// Whether cached loads are shrunk to their requested size
+static RESIZE_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Whether cached loads are shrunk to their requested size
+pub fn resize_enabled() -> bool {
+    RESIZE_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Enable or disable shrinking cached loads; when disabled every image keeps
+/// its original pixels whatever size is requested
+pub fn set_resize_enabled(enabled: bool) {
+    RESIZE_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Default for the longest side any decoded image may have, in pixels
+pub const DEFAULT_MAX_SIDE: u32 = 8192;
+
+/// Longest side any decoded image may have, whatever size is requested and
+/// even with resizing disabled, so pathological sources don't exceed GPU
+/// texture limits and render blank
+static MAX_SIDE: AtomicU32 = AtomicU32::new(DEFAULT_MAX_SIDE);
+
+/// Longest side any decoded image may have
+pub fn max_side() -> u32 {
+    MAX_SIDE.load(Ordering::Relaxed)
+}
+
+/// Set the longest side any decoded image may have (at least 1)
+pub fn set_max_side(side: u32) {
+    MAX_SIDE.store(side.max(1), Ordering::Relaxed);
+}
+
+/// Whether an image of this size is scaled down by the max side limit
+pub fn exceeds_max_side(width: u32, height: u32) -> bool {
+    width.max(height) > max_side()
+}
 
 /// Represents an image with both main and thumbnail versions
 #[derive(Clone)]
@@ -13,35 +78,239 @@ pub struct ImagePair {
     pub thumbnail_image: Option<Arc<DynamicImage>>,
 }
 
+/// A cached value with the tick of its last access
+struct CacheEntry<V> {
+    value: V,
+    last_access: u64,
+}
+
+/// LRU storage shared by ImageCache and EncodedImageCache
+///
+/// Pinned keys are never chosen for eviction, but still count toward capacity.
+struct CacheStore<V> {
+    entries: HashMap<String, CacheEntry<V>>,
+    pinned: HashSet<String>,
+    tick: u64,
+}
+
+impl<V: Clone> CacheStore<V> {
+    fn new() -> Self {
+        CacheStore {
+            entries: HashMap::new(),
+            pinned: HashSet::new(),
+            tick: 0,
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<V> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(key).map(|entry| {
+            entry.last_access = tick;
+            entry.value.clone()
+        })
+    }
+
+    fn insert(&mut self, key: String, value: V, max_size: usize) {
+        if !self.entries.contains_key(&key) && self.entries.len() >= max_size {
+            self.evict(self.entries.len() + 1 - max_size);
+        }
+
+        self.tick += 1;
+        self.entries.insert(key, CacheEntry { value, last_access: self.tick });
+    }
+
+    /// Evict up to `count` least recently used entries that aren't pinned
+    fn evict(&mut self, count: usize) {
+        let mut candidates: BinaryHeap<Reverse<(u64, &String)>> = self.entries
+            .iter()
+            .filter(|(key, _)| !self.pinned.contains(*key))
+            .map(|(key, entry)| Reverse((entry.last_access, key)))
+            .collect();
+
+        let mut victims = Vec::with_capacity(count);
+        while victims.len() < count {
+            match candidates.pop() {
+                Some(Reverse((_, key))) => victims.push(key.clone()),
+                None => break,
+            }
+        }
+
+        if victims.len() < count {
+            debug!("Cache over capacity: only {} of {} entries evictable", victims.len(), count);
+        }
+        for key in victims {
+            debug!("Evicting from cache: {}", key);
+            self.entries.remove(&key);
+        }
+    }
+
+    /// Evict least recently used unpinned entries until the total `weight` of
+    /// the values is at most `budget`
+    fn evict_to_budget(&mut self, budget: usize, weight: impl Fn(&V) -> usize) {
+        let mut total = self.bytes(&weight);
+        if total <= budget {
+            return;
+        }
+
+        let mut candidates: Vec<(u64, &String, usize)> = self.entries
+            .iter()
+            .filter(|(key, _)| !self.pinned.contains(*key))
+            .map(|(key, entry)| (entry.last_access, key, weight(&entry.value)))
+            .collect();
+        candidates.sort_unstable_by_key(|(last_access, _, _)| *last_access);
+
+        let mut victims = Vec::new();
+        for (_, key, bytes) in candidates {
+            if total <= budget {
+                break;
+            }
+            victims.push(key.clone());
+            total -= bytes;
+        }
+
+        if total > budget {
+            debug!("Cache over memory budget: {} bytes left after evicting all unpinned entries", total);
+        }
+        for key in victims {
+            debug!("Evicting from cache: {}", key);
+            self.entries.remove(&key);
+        }
+    }
+
+    fn pin(&mut self, key: String) {
+        self.pinned.insert(key);
+    }
+
+    fn unpin(&mut self, key: &str) {
+        self.pinned.remove(key);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Remove the entries of an image path: the path itself and its variant
+    /// keys (`{path}@...`, `{path}#...`); returns how many were removed
+    fn remove_path(&mut self, path: &str) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|key, _| {
+            key.strip_prefix(path)
+                .is_none_or(|rest| !(rest.is_empty() || rest.starts_with('@') || rest.starts_with('#')))
+        });
+        before - self.entries.len()
+    }
+
+    fn remove_key(&mut self, key: &str) {
+        self.entries.remove(key);
+    }
+
+    /// Remove the entries decoded at a size (`{path}@{size}`); returns how
+    /// many were removed
+    fn remove_size(&mut self, size: u32) -> usize {
+        let suffix = format!("@{}", size);
+        let before = self.entries.len();
+        self.entries.retain(|key, _| !key.ends_with(&suffix));
+        before - self.entries.len()
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Total size of the cached values, as measured by `weight`
+    fn bytes(&self, weight: impl Fn(&V) -> usize) -> usize {
+        self.entries.values().map(|entry| weight(&entry.value)).sum()
+    }
+}
+
+/// Decoded size of an image as counted against an ImageCache memory budget:
+/// four bytes per pixel, whatever the actual pixel format
+fn estimated_bytes(img: &Arc<DynamicImage>) -> usize {
+    img.width() as usize * img.height() as usize * 4
+}
+
+/// Count a cache lookup as a hit or a miss
+fn count_lookup(hits: &AtomicU64, misses: &AtomicU64, hit: bool) {
+    let counter = if hit { hits } else { misses };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
 /// Image cache with a maximum capacity
 pub struct ImageCache {
-    cache: Arc<Mutex<HashMap<String, Arc<DynamicImage>>>>,
+    cache: Arc<Mutex<CacheStore<Arc<DynamicImage>>>>,
     max_size: usize,
+    /// When set, least recently used images are evicted to keep the
+    /// estimated_bytes total within this many bytes
+    memory_budget: Option<usize>,
+    /// When false, loads skip the cache and only return the decoded image
+    cache_decoded: AtomicBool,
+    hits: AtomicU64,
+    misses: AtomicU64,
 }
 
 impl ImageCache {
     pub fn new(max_size: usize) -> Self {
         ImageCache {
-            cache: Arc::new(Mutex::new(HashMap::new())),
+            cache: Arc::new(Mutex::new(CacheStore::new())),
             max_size,
+            memory_budget: None,
+            cache_decoded: AtomicBool::new(true),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// A cache bounded by the estimated size of its images instead of their
+    /// number, so a few 4K pages take as much room as many thumbnails
+    pub fn with_memory_budget(bytes: usize) -> Self {
+        ImageCache {
+            memory_budget: Some(bytes),
+            ..Self::new(usize::MAX)
+        }
+    }
+
+    /// Whether loaded images are kept in the cache
+    pub fn cache_decoded(&self) -> bool {
+        self.cache_decoded.load(Ordering::Relaxed)
+    }
+
+    /// Enable or disable keeping loaded images; disabling also empties the cache
+    pub fn set_cache_decoded(&self, enabled: bool) {
+        self.cache_decoded.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.clear();
         }
     }
 
     /// Get an image from cache
     pub fn get(&self, path: &str) -> Option<Arc<DynamicImage>> {
-        self.cache.lock().unwrap().get(path).cloned()
+        let image = self.cache.lock().unwrap().get(path);
+        count_lookup(&self.hits, &self.misses, image.is_some());
+        image
     }
 
-    /// Insert an image into the cache
+    /// Insert an image into the cache, evicting the least recently used
+    /// unpinned entries if the cache is full or over its memory budget
+    ///
+    /// The new image itself is kept even if it alone exceeds the budget.
     pub fn insert(&self, path: String, image: Arc<DynamicImage>) {
         let mut cache = self.cache.lock().unwrap();
-
-        // Simple eviction: if cache is full, clear it
-        if cache.len() >= self.max_size {
-            cache.clear();
+        if let Some(budget) = self.memory_budget {
+            cache.remove_key(&path);
+            cache.evict_to_budget(budget.saturating_sub(estimated_bytes(&image)), estimated_bytes);
         }
+        cache.insert(path, image, self.max_size);
+    }
+
+    /// Protect a key from eviction
+    pub fn pin(&self, key: String) {
+        self.cache.lock().unwrap().pin(key);
+    }
 
-        cache.insert(path, image);
+    /// Allow a previously pinned key to be evicted again
+    pub fn unpin(&self, key: &str) {
+        self.cache.lock().unwrap().unpin(key);
     }
 
     /// Clear the entire cache
@@ -49,41 +318,97 @@ impl ImageCache {
         self.cache.lock().unwrap().clear();
     }
 
+    /// Drop every cached variant of an image path; returns how many entries
+    /// were removed
+    pub fn invalidate(&self, path: &str) -> usize {
+        self.cache.lock().unwrap().remove_path(path)
+    }
+
+    /// Drop every image decoded at `size` (see load_image_cached_with_size);
+    /// returns how many entries were removed
+    pub fn invalidate_size(&self, size: u32) -> usize {
+        self.cache.lock().unwrap().remove_size(size)
+    }
+
     /// Get current cache size
     pub fn size(&self) -> usize {
         self.cache.lock().unwrap().len()
     }
+
+    /// Maximum number of entries
+    pub fn capacity(&self) -> usize {
+        self.max_size
+    }
+
+    /// Number of lookups that found an entry
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of lookups that found nothing
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Approximate memory held by the cached pixel data
+    pub fn memory_bytes(&self) -> usize {
+        self.cache.lock().unwrap().bytes(|img| img.as_bytes().len())
+    }
+
+    /// Total estimated_bytes of the cached images, as checked against the
+    /// memory budget
+    pub fn current_bytes(&self) -> usize {
+        self.cache.lock().unwrap().bytes(estimated_bytes)
+    }
+
+    /// Evict the least recently used unpinned image; false if there is none
+    pub fn evict_one(&self) -> bool {
+        let mut cache = self.cache.lock().unwrap();
+        let before = cache.len();
+        cache.evict(1);
+        cache.len() < before
+    }
 }
 
 /// Cache for base64-encoded images
 pub struct EncodedImageCache {
-    cache: Arc<Mutex<HashMap<String, String>>>,
+    cache: Arc<Mutex<CacheStore<String>>>,
     max_size: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
 }
 
 impl EncodedImageCache {
     pub fn new(max_size: usize) -> Self {
         EncodedImageCache {
-            cache: Arc::new(Mutex::new(HashMap::new())),
+            cache: Arc::new(Mutex::new(CacheStore::new())),
             max_size,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
         }
     }
 
     /// Get an encoded image from cache
     pub fn get(&self, path: &str) -> Option<String> {
-        self.cache.lock().unwrap().get(path).cloned()
+        let encoded = self.cache.lock().unwrap().get(path);
+        count_lookup(&self.hits, &self.misses, encoded.is_some());
+        encoded
     }
 
-    /// Insert an encoded image into the cache
+    /// Insert an encoded image into the cache, evicting the least recently
+    /// used unpinned entry if the cache is full
     pub fn insert(&self, path: String, encoded: String) {
-        let mut cache = self.cache.lock().unwrap();
+        self.cache.lock().unwrap().insert(path, encoded, self.max_size);
+    }
 
-        // Simple eviction: if cache is full, clear it
-        if cache.len() >= self.max_size {
-            cache.clear();
-        }
+    /// Protect a key from eviction
+    pub fn pin(&self, key: String) {
+        self.cache.lock().unwrap().pin(key);
+    }
 
-        cache.insert(path, encoded);
+    /// Allow a previously pinned key to be evicted again
+    pub fn unpin(&self, key: &str) {
+        self.cache.lock().unwrap().unpin(key);
     }
 
     /// Clear the entire cache
@@ -91,50 +416,587 @@ impl EncodedImageCache {
         self.cache.lock().unwrap().clear();
     }
 
+    /// Drop every cached variant of an image path; returns how many entries
+    /// were removed
+    pub fn invalidate(&self, path: &str) -> usize {
+        self.cache.lock().unwrap().remove_path(path)
+    }
+
     /// Get current cache size
     pub fn size(&self) -> usize {
         self.cache.lock().unwrap().len()
     }
+
+    /// Maximum number of entries
+    pub fn capacity(&self) -> usize {
+        self.max_size
+    }
+
+    /// Number of lookups that found an entry
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of lookups that found nothing
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Approximate memory held by the encoded strings
+    pub fn memory_bytes(&self) -> usize {
+        self.cache.lock().unwrap().bytes(String::len)
+    }
+
+    /// Evict the least recently used unpinned entry; false if there is none
+    pub fn evict_one(&self) -> bool {
+        let mut cache = self.cache.lock().unwrap();
+        let before = cache.len();
+        cache.evict(1);
+        cache.len() < before
+    }
 }
 
 /// Load an image from a file path
 pub fn load_image<P: AsRef<Path>>(path: P) -> Result<DynamicImage> {
     let path = path.as_ref();
 
-    image::open(path)
-        .with_context(|| format!("Failed to load image: {:?}", path))
+    // The image crate can't read HEIC/HEIF, so route those through libheif
+    if is_heif_path(path) {
+        return load_heif(path);
+    }
+    // Pages of an archive scene are entries of the archive
+    if let Some((archive, entry)) = archive::split_entry_path(&path.to_string_lossy()) {
+        let data = archive::read_entry(archive, entry)?;
+        return decode_image_data(&data, path);
+    }
+    // JPEGs are read up front so CMYK ones can be told apart before decoding
+    if is_jpeg_path(path) {
+        let data = std::fs::read(path)
+            .with_context(|| format!("Failed to read image: {:?}", path))?;
+        return decode_image_data(&data, path);
+    }
+
+    let img = image::open(path)
+        .with_context(|| format!("Failed to load image: {:?}", path))?;
+    if is_tiff_path(path) {
+        let orientation = std::fs::File::open(path).ok()
+            .and_then(|file| exif_orientation(&mut io::BufReader::new(file)));
+        return Ok(apply_exif_orientation(img, orientation, path));
+    }
+    Ok(img)
 }
 
-/// Load an image with caching
-pub fn load_image_cached(path: &str, cache: &ImageCache) -> Result<Arc<DynamicImage>> {
+/// Decode an image already read into memory; `path` tells its format apart
+/// and names it in errors
+fn decode_image_data(data: &[u8], path: &Path) -> Result<DynamicImage> {
+    if is_jpeg_path(path) {
+        let img = decode_jpeg(data)
+            .with_context(|| format!("Failed to load image: {:?}", path))?;
+        return Ok(apply_exif_orientation(img, exif_orientation(&mut io::Cursor::new(data)), path));
+    }
+
+    let img = image::load_from_memory(data)
+        .with_context(|| format!("Failed to load image: {:?}", path))?;
+    if is_tiff_path(path) {
+        return Ok(apply_exif_orientation(img, exif_orientation(&mut io::Cursor::new(data)), path));
+    }
+    Ok(img)
+}
+
+/// Read the pixel size of an image from its header, also for pages inside
+/// an archive
+pub fn image_dimensions<P: AsRef<Path>>(path: P) -> Result<(u32, u32)> {
+    let path = path.as_ref();
+    if let Some((archive, entry)) = archive::split_entry_path(&path.to_string_lossy()) {
+        let data = archive::read_entry(archive, entry)?;
+        let dimensions = image::ImageReader::new(io::Cursor::new(data))
+            .with_guessed_format()?
+            .into_dimensions()
+            .with_context(|| format!("Failed to read image size: {:?}", path))?;
+        return Ok(dimensions);
+    }
+    Ok(image::image_dimensions(path)?)
+}
+
+/// Check whether a path has a TIFF extension
+fn is_tiff_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| matches!(ext.to_ascii_lowercase().as_str(), "tif" | "tiff"))
+        .unwrap_or(false)
+}
+
+/// Orientation tag of the EXIF data in a JPEG or TIFF, if there is one
+fn exif_orientation<R: io::BufRead + io::Seek>(reader: &mut R) -> Option<Orientation> {
+    let exif = exif::Reader::new().read_from_container(reader).ok()?;
+    let value = exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?.value.get_uint(0)?;
+    Orientation::from_exif(u8::try_from(value).ok()?)
+}
+
+/// Rotate/flip a decoded image so it displays upright, as phones record
+/// sideways shots with an orientation tag instead of rotated pixels
+fn apply_exif_orientation(mut img: DynamicImage, orientation: Option<Orientation>, path: &Path) -> DynamicImage {
+    if let Some(orientation) = orientation.filter(|o| *o != Orientation::NoTransforms) {
+        debug!("Applying EXIF orientation {:?} to {:?}", orientation, path);
+        img.apply_orientation(orientation);
+    }
+    img
+}
+
+/// Check whether a path has a JPEG extension
+fn is_jpeg_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| matches!(ext.to_ascii_lowercase().as_str(), "jpg" | "jpeg" | "jpe" | "jfif"))
+        .unwrap_or(false)
+}
+
+/// How a JPEG stores its color, as far as CMYK decoding is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JpegColor {
+    /// Not CMYK, or YCCK; the image crate converts these correctly
+    Other,
+    /// CMYK with an Adobe APP14 marker: channels are stored inverted, which
+    /// the image crate's conversion assumes
+    AdobeCmyk,
+    /// CMYK without an Adobe marker: channels are plain ink amounts, which the
+    /// image crate would turn into inverted colors
+    PlainCmyk,
+}
+
+/// Find the color layout of a JPEG from its frame header and APP14 segment
+fn jpeg_color(data: &[u8]) -> JpegColor {
+    let mut components = None;
+    let mut adobe_transform = None;
+
+    let mut i = 2; // Skip SOI
+    while i + 4 <= data.len() && data[i] == 0xFF {
+        let marker = data[i + 1];
+        match marker {
+            // Fill byte before a marker
+            0xFF => {
+                i += 1;
+                continue;
+            }
+            // Standalone markers have no length
+            0x01 | 0xD0..=0xD7 => {
+                i += 2;
+                continue;
+            }
+            // Image data starts (SOS) or ends (EOI); all headers are read
+            0xDA | 0xD9 => break,
+            _ => {}
+        }
+
+        let length = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+        // The length counts its own two bytes; anything shorter is corrupt
+        if length < 2 {
+            return JpegColor::Other;
+        }
+        let segment = &data[(i + 4).min(data.len())..(i + 2 + length).min(data.len())];
+        match marker {
+            // SOFn, except DHT (C4), JPG (C8) and DAC (CC)
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                components = segment.get(5).copied();
+            }
+            0xEE if segment.starts_with(b"Adobe") => {
+                adobe_transform = segment.get(11).copied();
+            }
+            _ => {}
+        }
+        i += 2 + length;
+    }
+
+    match (components, adobe_transform) {
+        (Some(4), Some(0)) => JpegColor::AdobeCmyk,
+        (Some(4), None) => JpegColor::PlainCmyk,
+        _ => JpegColor::Other,
+    }
+}
+
+/// Decode JPEG data, converting plain (non-Adobe) CMYK to RGB ourselves
+fn decode_jpeg(data: &[u8]) -> Result<DynamicImage> {
+    if jpeg_color(data) != JpegColor::PlainCmyk {
+        return Ok(image::load_from_memory_with_format(data, image::ImageFormat::Jpeg)?);
+    }
+
+    use zune_core::bytestream::ZCursor;
+    use zune_core::colorspace::ColorSpace;
+    use zune_core::options::DecoderOptions;
+
+    let options = DecoderOptions::default()
+        .set_strict_mode(false)
+        .jpeg_set_out_colorspace(ColorSpace::CMYK);
+    let mut decoder = zune_jpeg::JpegDecoder::new_with_options(ZCursor::new(data), options);
+    let cmyk = decoder.decode()
+        .map_err(|e| anyhow::anyhow!("Failed to decode CMYK JPEG: {:?}", e))?;
+    let (width, height) = decoder.dimensions()
+        .context("CMYK JPEG has no dimensions")?;
+
+    // Ink amounts: R = (1 - C)(1 - K) and so on
+    let rgb: Vec<u8> = cmyk.chunks_exact(4)
+        .flat_map(|px| {
+            let white = 255 - px[3] as u32;
+            [0, 1, 2].map(|c| ((255 - px[c] as u32) * white / 255) as u8)
+        })
+        .collect();
+    let img = image::RgbImage::from_raw(width as u32, height as u32, rgb)
+        .context("CMYK JPEG has an unexpected pixel count")?;
+    debug!("Decoded plain CMYK JPEG ({}x{})", width, height);
+    Ok(DynamicImage::ImageRgb8(img))
+}
+
+/// Check whether a path has a HEIC/HEIF extension
+fn is_heif_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| matches!(ext.to_ascii_lowercase().as_str(), "heic" | "heif"))
+        .unwrap_or(false)
+}
+
+/// Decode a HEIC/HEIF image via libheif
+#[cfg(feature = "heic")]
+fn load_heif(path: &Path) -> Result<DynamicImage> {
+    use image::{RgbImage, RgbaImage};
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let path_str = path.to_str()
+        .with_context(|| format!("HEIF path is not valid UTF-8: {:?}", path))?;
+
+    let lib_heif = LibHeif::new();
+    let ctx = HeifContext::read_from_file(path_str)
+        .with_context(|| format!("Failed to read HEIF file: {:?}", path))?;
+    let handle = ctx.primary_image_handle()?;
+
+    let has_alpha = handle.has_alpha_channel();
+    let chroma = if has_alpha { RgbChroma::Rgba } else { RgbChroma::Rgb };
+    let image = lib_heif.decode(&handle, ColorSpace::Rgb(chroma), None)
+        .with_context(|| format!("Failed to decode HEIF image: {:?}", path))?;
+
+    let plane = image.planes().interleaved
+        .with_context(|| format!("HEIF image has no interleaved plane: {:?}", path))?;
+
+    // Copy rows out of the (possibly padded) plane into a tightly packed buffer
+    let channels = if has_alpha { 4 } else { 3 };
+    let row_len = plane.width as usize * channels;
+    let mut pixels = Vec::with_capacity(row_len * plane.height as usize);
+    for row in plane.data.chunks(plane.stride).take(plane.height as usize) {
+        pixels.extend_from_slice(&row[..row_len]);
+    }
+
+    let img = if has_alpha {
+        RgbaImage::from_raw(plane.width, plane.height, pixels).map(DynamicImage::ImageRgba8)
+    } else {
+        RgbImage::from_raw(plane.width, plane.height, pixels).map(DynamicImage::ImageRgb8)
+    };
+    img.with_context(|| format!("HEIF pixel buffer size mismatch: {:?}", path))
+}
+
+/// HEIC/HEIF support is not compiled in
+#[cfg(not(feature = "heic"))]
+fn load_heif(path: &Path) -> Result<DynamicImage> {
+    anyhow::bail!(
+        "Cannot decode HEIC/HEIF image {:?}: FastViewer was built without the `heic` feature",
+        path
+    )
+}
+
+/// Number of leading bytes read to detect a file's format
+const FORMAT_HEADER_LEN: usize = 64;
+
+/// Detect an image file's format from its magic bytes rather than its extension
+pub fn detect_format<P: AsRef<Path>>(path: P) -> Result<String> {
+    use std::io::Read;
+
+    let path = path.as_ref();
+    let mut header = Vec::with_capacity(FORMAT_HEADER_LEN);
+    std::fs::File::open(path)
+        .with_context(|| format!("Failed to open {:?}", path))?
+        .take(FORMAT_HEADER_LEN as u64)
+        .read_to_end(&mut header)
+        .with_context(|| format!("Failed to read {:?}", path))?;
+
+    format_from_header(&header)
+        .with_context(|| format!("Unrecognized image format: {:?}", path))
+}
+
+/// Name the image format of a file header, if recognized
+fn format_from_header(header: &[u8]) -> Option<String> {
+    // image doesn't know HEIF, which is an ISO-BMFF "ftyp" box with a HEIF brand
+    if header.len() >= 12 && &header[4..8] == b"ftyp" {
+        let brand = &header[8..12];
+        if matches!(brand, b"heic" | b"heix" | b"hevc" | b"hevx" | b"heim" | b"heis" | b"mif1" | b"msf1") {
+            return Some("Heif".to_string());
+        }
+    }
+
+    image::guess_format(header).ok().map(|format| format!("{:?}", format))
+}
+
+/// Load an image, retrying with exponential backoff on transient I/O errors
+/// (e.g. a network share briefly dropping). Decode errors are not retried.
+pub fn load_image_with_retry<P: AsRef<Path>>(path: P) -> Result<DynamicImage> {
+    let path = path.as_ref();
+    let retries = LOAD_RETRIES.load(Ordering::Relaxed);
+    let mut attempt = 0;
+
+    loop {
+        match load_image(path) {
+            Err(e) if attempt < retries && is_transient_error(&e) => {
+                let delay = RETRY_BASE_DELAY * 2u32.pow(attempt);
+                attempt += 1;
+                warn!("Transient error loading {:?}, retry {}/{} in {:?}: {:#}", path, attempt, retries, delay, e);
+                std::thread::sleep(delay);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Check whether an error was caused by an I/O error that may go away on retry
+fn is_transient_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        let io_err = cause.downcast_ref::<io::Error>().or_else(|| match cause.downcast_ref::<image::ImageError>() {
+            Some(image::ImageError::IoError(e)) => Some(e),
+            _ => None,
+        });
+        io_err.map(|e| is_transient_io_kind(e.kind())).unwrap_or(false)
+    })
+}
+
+/// I/O error kinds that plausibly clear up on their own
+fn is_transient_io_kind(kind: io::ErrorKind) -> bool {
+    matches!(
+        kind,
+        io::ErrorKind::Interrupted
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::ResourceBusy
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::NetworkDown
+            | io::ErrorKind::NetworkUnreachable
+            | io::ErrorKind::HostUnreachable
+    )
+}
+
+/// Decode the small thumbnail embedded in a JPEG's EXIF data, if there is one
+///
+/// Only the EXIF segment is read, so this is much cheaper than decoding the
+/// image itself.
+pub fn extract_exif_thumbnail<P: AsRef<Path>>(path: P) -> Option<DynamicImage> {
+    let path = path.as_ref();
+    let is_jpeg = path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| matches!(ext.to_ascii_lowercase().as_str(), "jpg" | "jpeg"))
+        .unwrap_or(false);
+    if !is_jpeg {
+        return None;
+    }
+
+    let file = std::fs::File::open(path).ok()?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut io::BufReader::new(file))
+        .ok()?;
+    let orientation = exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0))
+        .and_then(|value| Orientation::from_exif(u8::try_from(value).ok()?));
+
+    let offset = exif.get_field(exif::Tag::JPEGInterchangeFormat, exif::In::THUMBNAIL)?
+        .value.get_uint(0)? as usize;
+    let length = exif.get_field(exif::Tag::JPEGInterchangeFormatLength, exif::In::THUMBNAIL)?
+        .value.get_uint(0)? as usize;
+    let data = exif.buf().get(offset..offset.checked_add(length)?)?;
+
+    // The thumbnail is stored unrotated like the main image
+    match image::load_from_memory_with_format(data, image::ImageFormat::Jpeg) {
+        Ok(img) => Some(apply_exif_orientation(img, orientation, path)),
+        Err(e) => {
+            debug!("Invalid EXIF thumbnail in {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Load an image with caching, resized to fit within `max_dimension`
+/// Cached under `{path}@{max_dimension}` so different sizes don't collide
+pub fn load_image_cached_with_size(path: &str, max_dimension: u32, cache: &ImageCache) -> Result<Arc<DynamicImage>> {
+    load_image_cached_timed(path, max_dimension, cache).map(|(img, _)| img)
+}
+
+/// Time spent producing a decoded image
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoadTimings {
+    pub decode: Duration,
+    pub resize: Duration,
+    pub from_cache: bool,
+}
+
+/// Same as `load_image_cached_with_size`, also reporting decode/resize timings
+pub fn load_image_cached_timed(path: &str, max_dimension: u32, cache: &ImageCache) -> Result<(Arc<DynamicImage>, LoadTimings)> {
+    let key = format!("{}@{}", path, max_dimension);
+
     // Check cache first
-    if let Some(cached) = cache.get(path) {
-        return Ok(cached);
+    if let Some(cached) = cache.get(&key) {
+        debug!("Image cache hit: {}", key);
+        return Ok((cached, LoadTimings { from_cache: true, ..Default::default() }));
     }
 
     // Load from disk
-    let img = load_image(path)?;
-    let img_arc = Arc::new(img);
+    let start = Instant::now();
+    let img = load_image_with_retry(path)?;
+    let decode = start.elapsed();
 
-    // Store in cache
-    cache.insert(path.to_string(), img_arc.clone());
+    // Clamp to the requested size, and always to the max side limit
+    let start = Instant::now();
+    let (width, height) = img.dimensions();
+    let limit = if resize_enabled() { max_dimension } else { u32::MAX };
+    let resized = if width > limit || height > limit || exceeds_max_side(width, height) {
+        resize_to_fit(&img, limit, limit)
+    } else {
+        img
+    };
+    let resize = start.elapsed();
 
-    Ok(img_arc)
+    debug!(
+        "Decoded {} ({}x{}) in {:?}, resized to {}x{} in {:?}",
+        path, width, height, decode, resized.width(), resized.height(), resize
+    );
+    let img_arc = Arc::new(resized);
+
+    // Store in cache, unless only the encoded form is being kept
+    if cache.cache_decoded() {
+        cache.insert(key, img_arc.clone());
+    }
+
+    Ok((img_arc, LoadTimings { decode, resize, from_cache: false }))
+}
+
+/// Chroma subsampling used when encoding JPEGs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChromaSubsampling {
+    /// Full color resolution; sharpest colored edges, largest files
+    Yuv444,
+    /// Half horizontal color resolution
+    Yuv422,
+    /// Half horizontal and vertical color resolution
+    #[default]
+    Yuv420,
+}
+
+impl ChromaSubsampling {
+    fn sampling_factor(self) -> jpeg_encoder::SamplingFactor {
+        match self {
+            ChromaSubsampling::Yuv444 => jpeg_encoder::SamplingFactor::R_4_4_4,
+            ChromaSubsampling::Yuv422 => jpeg_encoder::SamplingFactor::R_4_2_2,
+            ChromaSubsampling::Yuv420 => jpeg_encoder::SamplingFactor::R_4_2_0,
+        }
+    }
+}
+
+/// JPEG encoder settings; the default is baseline 4:2:0
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JpegOptions {
+    pub subsampling: ChromaSubsampling,
+    /// Encode progressively so browsers can render the image in passes
+    pub progressive: bool,
 }
 
 /// Convert an image to base64 encoded JPEG
-pub fn image_to_base64_jpeg(img: &DynamicImage, quality: u8) -> Result<String> {
-    use std::io::Cursor;  // use image::ImageFormat; を削除
+pub fn image_to_base64_jpeg(img: &DynamicImage, quality: u8, options: &JpegOptions) -> Result<String> {
+    let buffer = image_to_jpeg(img, quality, options)?;
+    let base64 = base64_encode(&buffer);
+    Ok(format!("data:image/jpeg;base64,{}", base64))
+}
 
-    let mut buffer = Cursor::new(Vec::new());
+/// Encode an image as JPEG file bytes
+pub fn image_to_jpeg(img: &DynamicImage, quality: u8, options: &JpegOptions) -> Result<Vec<u8>> {
+    let (width, height) = img.dimensions();
+    let (width, height) = match (u16::try_from(width), u16::try_from(height)) {
+        (Ok(w), Ok(h)) => (w, h),
+        _ => anyhow::bail!("Image too large for JPEG: {}x{}", width, height),
+    };
 
     let rgb_img = img.to_rgb8();
+    let mut buffer = Vec::new();
 
-    let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buffer, quality);
-    rgb_img.write_with_encoder(encoder)?;
+    let mut encoder = jpeg_encoder::Encoder::new(&mut buffer, quality);
+    encoder.set_sampling_factor(options.subsampling.sampling_factor());
+    encoder.set_progressive(options.progressive);
+    encoder.encode(rgb_img.as_raw(), width, height, jpeg_encoder::ColorType::Rgb)?;
+    Ok(buffer)
+}
 
-    let base64 = base64_encode(buffer.get_ref());
-    Ok(format!("data:image/jpeg;base64,{}", base64))
+/// Format images are encoded in before being sent to the frontend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EncodeFormat {
+    #[default]
+    Jpeg,
+    /// Lossless WebP; quality and JPEG options don't apply
+    WebP,
+}
+
+/// Convert an image to a base64 data URL in the given format
+pub fn encode_image(img: &DynamicImage, format: EncodeFormat, quality: u8, options: &JpegOptions) -> Result<String> {
+    match format {
+        EncodeFormat::Jpeg => image_to_base64_jpeg(img, quality, options),
+        // Fall back to JPEG rather than fail the page if WebP encoding
+        // isn't available in this build or errors on the image
+        EncodeFormat::WebP => image_to_base64_webp(img).or_else(|e| {
+            warn!("WebP encoding failed, falling back to JPEG: {:#}", e);
+            image_to_base64_jpeg(img, quality, options)
+        }),
+    }
+}
+
+/// Encode an image like encode_image, except that images with transparent
+/// pixels become PNG so their transparency isn't flattened away
+pub fn encode_keeping_alpha(img: &DynamicImage, format: EncodeFormat, quality: u8, options: &JpegOptions) -> Result<String> {
+    if has_transparency(img) {
+        image_to_base64_png(img)
+    } else {
+        encode_image(img, format, quality, options)
+    }
+}
+
+/// Check whether an image has an alpha channel with any non-opaque pixel
+pub fn has_transparency(img: &DynamicImage) -> bool {
+    if !img.color().has_alpha() {
+        return false;
+    }
+    match img.as_rgba8() {
+        Some(rgba) => rgba.pixels().any(|p| p[3] < 255),
+        None => img.to_rgba8().pixels().any(|p| p[3] < 255),
+    }
+}
+
+/// Check whether an encoded data URL is a PNG
+pub fn is_png_data_url(data_url: &str) -> bool {
+    data_url.starts_with("data:image/png;")
+}
+
+/// Convert an image to base64 encoded lossless WebP
+///
+/// The image crate only has a lossless WebP encoder, so there is no quality
+/// setting; photos come out larger than JPEG but line art and flat colours
+/// are usually smaller.
+pub fn image_to_base64_webp(img: &DynamicImage) -> Result<String> {
+    let base64 = base64_encode(&image_to_webp(img)?);
+    Ok(format!("data:image/webp;base64,{}", base64))
+}
+
+/// Encode an image as lossless WebP bytes
+pub fn image_to_webp(img: &DynamicImage) -> Result<Vec<u8>> {
+    use image::ImageFormat;
+    use std::io::Cursor;
+
+    // Alpha is dropped like for JPEG; the WebP encoder only takes 8-bit data
+    let rgb_img = DynamicImage::ImageRgb8(img.to_rgb8());
+    let mut buffer = Cursor::new(Vec::new());
+    rgb_img.write_to(&mut buffer, ImageFormat::WebP)?;
+    Ok(buffer.into_inner())
 }
 
 /// Convert an image to base64 encoded PNG
@@ -149,54 +1011,46 @@ pub fn image_to_base64_png(img: &DynamicImage) -> Result<String> {
     Ok(format!("data:image/png;base64,{}", base64))
 }
 
-/// Simple base64 encoding
+/// Base64 encoding into an output buffer sized up front
 fn base64_encode(data: &[u8]) -> String {
     const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
 
-    let mut result = String::new();
-    let mut i = 0;
-
-    while i + 2 < data.len() {
-        let b1 = data[i];
-        let b2 = data[i + 1];
-        let b3 = data[i + 2];
-
-        result.push(ALPHABET[(b1 >> 2) as usize] as char);
-        result.push(ALPHABET[(((b1 & 0x03) << 4) | (b2 >> 4)) as usize] as char);
-        result.push(ALPHABET[(((b2 & 0x0F) << 2) | (b3 >> 6)) as usize] as char);
-        result.push(ALPHABET[(b3 & 0x3F) as usize] as char);
-
-        i += 3;
+    let mut out = Vec::with_capacity(4 * data.len().div_ceil(3));
+    let mut chunks = data.chunks_exact(3);
+    for chunk in &mut chunks {
+        let n = (chunk[0] as u32) << 16 | (chunk[1] as u32) << 8 | chunk[2] as u32;
+        out.extend_from_slice(&[
+            ALPHABET[(n >> 18) as usize & 0x3F],
+            ALPHABET[(n >> 12) as usize & 0x3F],
+            ALPHABET[(n >> 6) as usize & 0x3F],
+            ALPHABET[n as usize & 0x3F],
+        ]);
     }
 
-    // Handle remaining bytes
-    match data.len() - i {
-        1 => {
-            let b1 = data[i];
-            result.push(ALPHABET[(b1 >> 2) as usize] as char);
-            result.push(ALPHABET[((b1 & 0x03) << 4) as usize] as char);
-            result.push_str("==");
-        }
-        2 => {
-            let b1 = data[i];
-            let b2 = data[i + 1];
-            result.push(ALPHABET[(b1 >> 2) as usize] as char);
-            result.push(ALPHABET[(((b1 & 0x03) << 4) | (b2 >> 4)) as usize] as char);
-            result.push(ALPHABET[((b2 & 0x0F) << 2) as usize] as char);
-            result.push('=');
-        }
+    // Pad the last one or two bytes
+    match *chunks.remainder() {
+        [b1] => out.extend_from_slice(&[
+            ALPHABET[(b1 >> 2) as usize],
+            ALPHABET[((b1 & 0x03) << 4) as usize],
+            b'=',
+            b'=',
+        ]),
+        [b1, b2] => out.extend_from_slice(&[
+            ALPHABET[(b1 >> 2) as usize],
+            ALPHABET[(((b1 & 0x03) << 4) | (b2 >> 4)) as usize],
+            ALPHABET[((b2 & 0x0F) << 2) as usize],
+            b'=',
+        ]),
         _ => {}
     }
 
-    result
+    String::from_utf8(out).expect("base64 output is ASCII")
 }
 
-/// Resize an image to fit within max dimensions while preserving aspect ratio
-pub fn resize_to_fit(img: &DynamicImage, max_width: u32, max_height: u32) -> DynamicImage {
-    let (width, height) = img.dimensions();
-
+/// Compute the dimensions `resize_to_fit` produces for an image of the given size
+pub fn fit_dimensions(width: u32, height: u32, max_width: u32, max_height: u32) -> (u32, u32) {
     if width <= max_width && height <= max_height {
-        return img.clone();
+        return (width, height);
     }
 
     let width_ratio = max_width as f32 / width as f32;
@@ -206,6 +1060,23 @@ pub fn resize_to_fit(img: &DynamicImage, max_width: u32, max_height: u32) -> Dyn
     let new_width = (width as f32 * ratio) as u32;
     let new_height = (height as f32 * ratio) as u32;
 
+    (new_width, new_height)
+}
+
+/// Resize an image to fit within max dimensions while preserving aspect ratio
+///
+/// Neither side ends up longer than the max side limit, whatever the max
+/// dimensions are.
+pub fn resize_to_fit(img: &DynamicImage, max_width: u32, max_height: u32) -> DynamicImage {
+    let (width, height) = img.dimensions();
+    let (max_width, max_height) = (max_width.min(max_side()), max_height.min(max_side()));
+
+    if width <= max_width && height <= max_height {
+        return img.clone();
+    }
+
+    let (new_width, new_height) = fit_dimensions(width, height, max_width, max_height);
+
     img.resize(new_width, new_height, image::imageops::FilterType::Lanczos3)
 }
 
@@ -222,10 +1093,377 @@ mod tests {
         // This is a placeholder for future integration tests
     }
 
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let cache = EncodedImageCache::new(2);
+        cache.insert("a".to_string(), "A".to_string());
+        cache.insert("b".to_string(), "B".to_string());
+
+        // Touch "a" so "b" becomes the eviction candidate
+        assert!(cache.get("a").is_some());
+        cache.insert("c".to_string(), "C".to_string());
+
+        assert_eq!(cache.size(), 2);
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+    }
+
+    #[test]
+    fn test_cache_pinned_entries_survive_eviction() {
+        let cache = EncodedImageCache::new(2);
+        cache.insert("current".to_string(), "X".to_string());
+        cache.pin("current".to_string());
+
+        for i in 0..5 {
+            cache.insert(format!("prefetch{}", i), "P".to_string());
+        }
+        assert_eq!(cache.size(), 2);
+        assert!(cache.get("current").is_some());
+
+        // Once unpinned it is evictable again
+        cache.unpin("current");
+        cache.insert("next".to_string(), "N".to_string());
+        cache.insert("next2".to_string(), "N".to_string());
+        assert!(cache.get("current").is_none());
+    }
+
+    #[test]
+    fn test_cache_memory_and_evict_one() {
+        let cache = EncodedImageCache::new(4);
+        cache.insert("a".to_string(), "AAAA".to_string());
+        cache.insert("b".to_string(), "BB".to_string());
+        cache.pin("b".to_string());
+        assert_eq!(cache.memory_bytes(), 6);
+
+        // The unpinned entry goes first, then nothing is left to evict
+        assert!(cache.evict_one());
+        assert_eq!(cache.memory_bytes(), 2);
+        assert!(!cache.evict_one());
+
+        let images = ImageCache::new(4);
+        images.insert("img".to_string(), Arc::new(DynamicImage::new_rgb8(4, 4)));
+        assert_eq!(images.memory_bytes(), 4 * 4 * 3);
+    }
+
+    #[test]
+    fn test_cache_memory_budget() {
+        let budget = 64 * 64 * 4;
+        let cache = ImageCache::with_memory_budget(budget);
+        for side in [8u32, 16, 24, 32, 40, 48, 56, 64] {
+            cache.insert(format!("img{}", side), Arc::new(DynamicImage::new_rgb8(side, side)));
+            assert!(cache.current_bytes() <= budget, "{} bytes over budget", cache.current_bytes());
+            assert!(cache.get(&format!("img{}", side)).is_some());
+        }
+        // Only the newest image fits alongside nothing else
+        assert_eq!(cache.size(), 1);
+
+        // Pinned images are kept even over budget
+        let cache = ImageCache::with_memory_budget(budget);
+        cache.insert("pinned".to_string(), Arc::new(DynamicImage::new_rgb8(48, 48)));
+        cache.pin("pinned".to_string());
+        cache.insert("small".to_string(), Arc::new(DynamicImage::new_rgb8(8, 8)));
+        cache.insert("large".to_string(), Arc::new(DynamicImage::new_rgb8(48, 48)));
+        assert!(cache.get("pinned").is_some());
+        assert!(cache.get("small").is_none());
+        assert_eq!(cache.current_bytes(), 2 * 48 * 48 * 4);
+    }
+
+    #[test]
+    fn test_cache_hit_miss_counts() {
+        let cache = EncodedImageCache::new(4);
+        assert_eq!(cache.capacity(), 4);
+        cache.insert("a".to_string(), "A".to_string());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert_eq!((cache.hits(), cache.misses()), (2, 1));
+
+        let images = ImageCache::new(8);
+        assert!(images.get("missing").is_none());
+        assert_eq!((images.hits(), images.misses(), images.capacity()), (0, 1, 8));
+    }
+
+    #[test]
+    fn test_cache_invalidate() {
+        let cache = EncodedImageCache::new(8);
+        for key in ["/a.jpg", "/a.jpg@1920", "/a.jpg@thumb#rot90", "/a.jpg#webp", "/a.jpg2", "/b.jpg"] {
+            cache.insert(key.to_string(), "X".to_string());
+        }
+        assert_eq!(cache.invalidate("/a.jpg"), 4);
+        assert!(cache.get("/a.jpg2").is_some());
+        assert!(cache.get("/b.jpg").is_some());
+        assert_eq!(cache.size(), 2);
+    }
+
+    #[test]
+    fn test_resize_to_fit() {
+        let img = DynamicImage::new_rgb8(4000, 2000);
+
+        let resized = resize_to_fit(&img, 1000, 1000);
+        assert_eq!(resized.dimensions(), (1000, 500));
+        assert_eq!(fit_dimensions(4000, 2000, 1000, 1000), (1000, 500));
+
+        // Smaller images are left untouched
+        let small = resize_to_fit(&img, 8000, 8000);
+        assert_eq!(small.dimensions(), (4000, 2000));
+    }
+
+    #[test]
+    #[cfg(not(feature = "heic"))]
+    fn test_heif_without_feature_errors() {
+        let err = load_image("/path/to/photo.HEIC").unwrap_err();
+        assert!(err.to_string().contains("`heic` feature"));
+    }
+
+    #[test]
+    fn test_max_side_limit() {
+        let img = DynamicImage::new_rgb8(DEFAULT_MAX_SIDE * 2, 100);
+        assert!(exceeds_max_side(img.width(), img.height()));
+        assert_eq!(resize_to_fit(&img, u32::MAX, u32::MAX).dimensions(), (DEFAULT_MAX_SIDE, 50));
+        assert!(!exceeds_max_side(DEFAULT_MAX_SIDE, DEFAULT_MAX_SIDE));
+    }
+
+    #[test]
+    fn test_only_transient_io_errors_are_retried() {
+        let timed_out = anyhow::Error::new(image::ImageError::IoError(io::Error::from(io::ErrorKind::TimedOut)))
+            .context("Failed to load image");
+        assert!(is_transient_error(&timed_out));
+
+        let not_found = anyhow::Error::new(io::Error::from(io::ErrorKind::NotFound));
+        assert!(!is_transient_error(&not_found));
+
+        let decode = anyhow::anyhow!("Invalid JPEG header");
+        assert!(!is_transient_error(&decode));
+    }
+
+    #[test]
+    fn test_jpeg_options_encode() {
+        let img = DynamicImage::new_rgb8(17, 9);
+        let baseline = image_to_base64_jpeg(&img, 85, &JpegOptions::default()).unwrap();
+        let progressive = image_to_base64_jpeg(&img, 85, &JpegOptions {
+            subsampling: ChromaSubsampling::Yuv444,
+            progressive: true,
+        }).unwrap();
+
+        assert!(baseline.starts_with("data:image/jpeg;base64,"));
+        assert_ne!(baseline, progressive);
+    }
+
+    #[test]
+    fn test_encode_image_formats() {
+        let img = DynamicImage::new_rgba8(8, 8);
+        let options = JpegOptions::default();
+
+        let jpeg = encode_image(&img, EncodeFormat::Jpeg, 75, &options).unwrap();
+        assert!(jpeg.starts_with("data:image/jpeg;base64,"));
+
+        // "UklGR" is the base64 of the RIFF header
+        let webp = encode_image(&img, EncodeFormat::WebP, 75, &options).unwrap();
+        assert!(webp.starts_with("data:image/webp;base64,UklGR"));
+    }
+
+    #[test]
+    fn test_webp_round_trip() {
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(16, 8, |x, y| {
+            image::Rgb([(x * 16) as u8, (y * 32) as u8, 200])
+        }));
+        let webp = image_to_webp(&img).unwrap();
+        let decoded = image::load_from_memory_with_format(&webp, image::ImageFormat::WebP).unwrap();
+
+        // Lossless, so every pixel survives
+        assert_eq!(decoded.dimensions(), (16, 8));
+        assert_eq!(decoded.to_rgb8(), img.to_rgb8());
+    }
+
+    #[test]
+    fn test_encode_keeping_alpha() {
+        let options = JpegOptions::default();
+        let opaque = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(8, 8, image::Rgba([10, 20, 30, 255])));
+        let mut transparent = opaque.to_rgba8();
+        transparent.put_pixel(3, 3, image::Rgba([0, 0, 0, 0]));
+        let transparent = DynamicImage::ImageRgba8(transparent);
+
+        assert!(!has_transparency(&opaque));
+        assert!(has_transparency(&transparent));
+        assert!(!has_transparency(&DynamicImage::new_rgb8(8, 8)));
+
+        let encoded = encode_keeping_alpha(&opaque, EncodeFormat::Jpeg, 75, &options).unwrap();
+        assert!(!is_png_data_url(&encoded));
+        let encoded = encode_keeping_alpha(&transparent, EncodeFormat::Jpeg, 75, &options).unwrap();
+        assert!(is_png_data_url(&encoded));
+    }
+
+    /// Encode a 16x16 CMYK JPEG of one ink color; jpeg-encoder stores the
+    /// channels inverted and marks them as Adobe
+    fn cmyk_jpeg(cmyk: [u8; 4]) -> Vec<u8> {
+        let data: Vec<u8> = (0..16 * 16).flat_map(|_| cmyk).collect();
+        let mut jpeg = Vec::new();
+        jpeg_encoder::Encoder::new(&mut jpeg, 95)
+            .encode(&data, 16, 16, jpeg_encoder::ColorType::Cmyk)
+            .unwrap();
+        jpeg
+    }
+
+    #[test]
+    fn test_cmyk_jpeg_is_not_inverted() {
+        // Pure cyan ink, stored inverted the Adobe way
+        let adobe = cmyk_jpeg([255, 0, 0, 0]);
+        assert_eq!(jpeg_color(&adobe), JpegColor::AdobeCmyk);
+
+        // The same ink stored plainly, without the APP14 segment
+        let mut plain = cmyk_jpeg([0, 255, 255, 255]);
+        let app14 = plain.windows(9).position(|w| w == b"\xFF\xEE\x00\x0EAdobe").unwrap();
+        plain.drain(app14..app14 + 16);
+        assert_eq!(jpeg_color(&plain), JpegColor::PlainCmyk);
+
+        for data in [adobe, plain] {
+            let [r, g, b] = decode_jpeg(&data).unwrap().to_rgb8().get_pixel(8, 8).0;
+            assert!(r < 30 && g > 225 && b > 225, "expected cyan, got {:?}", (r, g, b));
+        }
+
+        let rgb = image_to_jpeg(&DynamicImage::new_rgb8(8, 8), 80, &JpegOptions::default()).unwrap();
+        assert_eq!(jpeg_color(&rgb), JpegColor::Other);
+    }
+
+    #[test]
+    fn test_jpeg_color_bad_segment_length() {
+        for length in [0, 1] {
+            let data = [0xFF, 0xD8, 0xFF, 0xE0, 0x00, length, 0x00, 0x00];
+            assert_eq!(jpeg_color(&data), JpegColor::Other);
+        }
+    }
+
+    /// A 4x2 JPEG with an EXIF APP1 segment holding just an orientation tag
+    fn jpeg_with_orientation(orientation: u8) -> Vec<u8> {
+        let jpeg = image_to_jpeg(&DynamicImage::new_rgb8(4, 2), 80, &JpegOptions::default()).unwrap();
+        let mut tiff = b"Exif\0\0MM\0\x2A\0\0\0\x08".to_vec();
+        tiff.extend_from_slice(&[0, 1]); // One IFD entry
+        tiff.extend_from_slice(&[0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, orientation, 0, 0]); // Orientation, SHORT
+        tiff.extend_from_slice(&[0, 0, 0, 0]); // No next IFD
+
+        let mut data = jpeg[..2].to_vec();
+        data.extend_from_slice(&[0xFF, 0xE1]);
+        data.extend_from_slice(&(tiff.len() as u16 + 2).to_be_bytes());
+        data.extend_from_slice(&tiff);
+        data.extend_from_slice(&jpeg[2..]);
+        data
+    }
+
+    #[test]
+    fn test_exif_orientation() {
+        assert_eq!(exif_orientation(&mut io::Cursor::new(jpeg_with_orientation(6))), Some(Orientation::Rotate90));
+        let plain = image_to_jpeg(&DynamicImage::new_rgb8(4, 2), 80, &JpegOptions::default()).unwrap();
+        assert_eq!(exif_orientation(&mut io::Cursor::new(plain)), None);
+
+        let dir = std::env::temp_dir().join(format!("fastviewer-orientation-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let sizes: Vec<_> = [1, 3, 6, 8].iter().map(|&orientation| {
+            let path = dir.join(format!("page{}.jpg", orientation));
+            std::fs::write(&path, jpeg_with_orientation(orientation)).unwrap();
+            load_image(&path).map(|img| img.dimensions())
+        }).collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // 90 and 270 degree rotations swap width and height
+        let sizes: Vec<_> = sizes.into_iter().map(Result::unwrap).collect();
+        assert_eq!(sizes, [(4, 2), (4, 2), (2, 4), (2, 4)]);
+    }
+
+    #[test]
+    fn test_format_from_header() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        assert_eq!(format_from_header(png).as_deref(), Some("Png"));
+
+        let jpeg = [0xFF, 0xD8, 0xFF, 0xE0, 0, 0x10];
+        assert_eq!(format_from_header(&jpeg).as_deref(), Some("Jpeg"));
+
+        let heic = b"\0\0\0\x18ftypheic\0\0\0\0";
+        assert_eq!(format_from_header(heic).as_deref(), Some("Heif"));
+
+        assert_eq!(format_from_header(b"not an image"), None);
+    }
+
     #[test]
     fn test_base64_encode() {
         let data = b"Hello, World!";
         let encoded = base64_encode(data);
         assert_eq!(encoded, "SGVsbG8sIFdvcmxkIQ==");
     }
+
+    /// The original char-by-char encoder, kept to check output and speed against
+    fn base64_encode_reference(data: &[u8]) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+        let mut result = String::new();
+        let mut i = 0;
+
+        while i + 2 < data.len() {
+            let b1 = data[i];
+            let b2 = data[i + 1];
+            let b3 = data[i + 2];
+
+            result.push(ALPHABET[(b1 >> 2) as usize] as char);
+            result.push(ALPHABET[(((b1 & 0x03) << 4) | (b2 >> 4)) as usize] as char);
+            result.push(ALPHABET[(((b2 & 0x0F) << 2) | (b3 >> 6)) as usize] as char);
+            result.push(ALPHABET[(b3 & 0x3F) as usize] as char);
+
+            i += 3;
+        }
+
+        // Handle remaining bytes
+        match data.len() - i {
+            1 => {
+                let b1 = data[i];
+                result.push(ALPHABET[(b1 >> 2) as usize] as char);
+                result.push(ALPHABET[((b1 & 0x03) << 4) as usize] as char);
+                result.push_str("==");
+            }
+            2 => {
+                let b1 = data[i];
+                let b2 = data[i + 1];
+                result.push(ALPHABET[(b1 >> 2) as usize] as char);
+                result.push(ALPHABET[(((b1 & 0x03) << 4) | (b2 >> 4)) as usize] as char);
+                result.push(ALPHABET[((b2 & 0x0F) << 2) as usize] as char);
+                result.push('=');
+            }
+            _ => {}
+        }
+
+        result
+    }
+
+    #[test]
+    fn test_base64_encode_matches_reference() {
+        for len in 0..8 {
+            let data: Vec<u8> = (0..len).map(|i| (i * 37 + 11) as u8).collect();
+            assert_eq!(base64_encode(&data), base64_encode_reference(&data));
+        }
+
+        let data = pseudo_random_bytes(4096 + 1);
+        let encoded = base64_encode(&data);
+        assert_eq!(encoded, base64_encode_reference(&data));
+        assert_eq!(encoded.len(), 4 * data.len().div_ceil(3));
+    }
+
+    fn pseudo_random_bytes(len: u32) -> Vec<u8> {
+        (0..len).map(|i: u32| (i.wrapping_mul(2654435761) >> 13) as u8).collect()
+    }
+
+    /// Throughput comparison with the char-by-char encoder; run with
+    /// `cargo test --release -- --ignored --nocapture base64_throughput`
+    #[test]
+    #[ignore]
+    fn test_base64_throughput() {
+        let data = pseudo_random_bytes(1024 * 1024);
+        let start = Instant::now();
+        let encoded = base64_encode(&data);
+        let fast = start.elapsed();
+        let start = Instant::now();
+        let reference = base64_encode_reference(&data);
+        let slow = start.elapsed();
+
+        assert_eq!(encoded, reference);
+        println!("base64 of 1 MB: {:?} (char-by-char: {:?})", fast, slow);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,495 @@
+use crate::image_loader::resize_to_fit;
+use image::{DynamicImage, GrayImage, Luma, Rgb, RgbImage};
+use image::imageops::{self, FilterType};
+
+/// Per-channel difference above which a pixel counts as changed
+const DIFF_THRESHOLD: u8 = 32;
+
+/// Build a visual difference overlay of two images
+///
+/// Both images are resized to the smaller of their dimensions. Unchanged
+/// pixels are shown as a faded grayscale copy of `a`, changed pixels are
+/// painted red with an intensity proportional to the difference.
+pub fn diff_images(a: &DynamicImage, b: &DynamicImage) -> DynamicImage {
+    let width = a.width().min(b.width());
+    let height = a.height().min(b.height());
+
+    let a = a.resize_exact(width, height, FilterType::Triangle).to_rgb8();
+    let b = b.resize_exact(width, height, FilterType::Triangle).to_rgb8();
+
+    let mut out = RgbImage::new(width, height);
+    for (x, y, pixel) in out.enumerate_pixels_mut() {
+        let pa = a.get_pixel(x, y);
+        let pb = b.get_pixel(x, y);
+
+        let diff = pa.0.iter()
+            .zip(pb.0.iter())
+            .map(|(ca, cb)| ca.abs_diff(*cb))
+            .max()
+            .unwrap_or(0);
+
+        *pixel = if diff > DIFF_THRESHOLD {
+            Rgb([128 + diff / 2, 0, 0])
+        } else {
+            // Faded luma of the original for context
+            let luma = (pa.0[0] as u32 * 299 + pa.0[1] as u32 * 587 + pa.0[2] as u32 * 114) / 1000;
+            let faded = (191 + luma / 4) as u8;
+            Rgb([faded, faded, faded])
+        };
+    }
+
+    DynamicImage::ImageRgb8(out)
+}
+
+/// Pack images into a grid atlas of `cols` columns of `cell_size` square cells
+///
+/// Each image is shrunk to fit its cell and centered in it. `None` entries
+/// (e.g. pages that failed to load) leave their cell blank so cell indices
+/// always match page indices.
+pub fn pack_sprite_sheet(images: &[Option<DynamicImage>], cols: u32, cell_size: u32) -> DynamicImage {
+    let rows = sprite_rows(images.len(), cols);
+    let mut atlas = RgbImage::new(cols * cell_size, rows * cell_size);
+
+    for (i, img) in images.iter().enumerate() {
+        let Some(img) = img else { continue };
+        let cell = resize_to_fit(img, cell_size, cell_size).to_rgb8();
+
+        let col = i as u32 % cols;
+        let row = i as u32 / cols;
+        let x = col * cell_size + (cell_size - cell.width()) / 2;
+        let y = row * cell_size + (cell_size - cell.height()) / 2;
+        imageops::replace(&mut atlas, &cell, x as i64, y as i64);
+    }
+
+    DynamicImage::ImageRgb8(atlas)
+}
+
+/// Place pages side by side, left to right, as one spread image
+///
+/// Taller pages are shrunk to the height of the shortest one so the spread
+/// lines up without upscaling anything.
+pub fn compose_spread(pages: &[DynamicImage]) -> DynamicImage {
+    let height = pages.iter().map(|p| p.height()).min().unwrap_or(0);
+    let scaled: Vec<RgbImage> = pages.iter()
+        .map(|p| {
+            let width = (p.width() as u64 * height as u64 / p.height().max(1) as u64).max(1) as u32;
+            p.resize_exact(width, height, FilterType::Lanczos3).to_rgb8()
+        })
+        .collect();
+
+    let mut spread = RgbImage::new(scaled.iter().map(|p| p.width()).sum(), height);
+    let mut x = 0;
+    for page in &scaled {
+        imageops::replace(&mut spread, page, x as i64, 0);
+        x += page.width();
+    }
+
+    DynamicImage::ImageRgb8(spread)
+}
+
+/// Number of rows needed to lay out `count` cells in `cols` columns
+pub fn sprite_rows(count: usize, cols: u32) -> u32 {
+    (count as u32).div_ceil(cols)
+}
+
+/// Rotate an image clockwise by a multiple of 90 degrees
+pub fn rotate(img: &DynamicImage, degrees: u32) -> DynamicImage {
+    match degrees % 360 {
+        90 => img.rotate90(),
+        180 => img.rotate180(),
+        270 => img.rotate270(),
+        _ => img.clone(),
+    }
+}
+
+/// Average color of an image, computed on a small downscale
+pub fn average_color(img: &DynamicImage) -> [u8; 3] {
+    let small = img.resize(16, 16, FilterType::Triangle).to_rgb8();
+    let count = small.pixels().len().max(1) as u64;
+
+    let mut sums = [0u64; 3];
+    for pixel in small.pixels() {
+        for (sum, channel) in sums.iter_mut().zip(pixel.0) {
+            *sum += channel as u64;
+        }
+    }
+    sums.map(|sum| (sum / count) as u8)
+}
+
+/// Largest side of the downscale a BlurHash is computed from
+const BLURHASH_SOURCE_SIZE: u32 = 32;
+
+/// Compute the BlurHash of an image with the given number of components
+/// along each axis (1 to 9 each)
+pub fn blurhash(img: &DynamicImage, x_components: u32, y_components: u32) -> anyhow::Result<String> {
+    let small = img
+        .resize(BLURHASH_SOURCE_SIZE, BLURHASH_SOURCE_SIZE, FilterType::Triangle)
+        .to_rgba8();
+    let hash = blurhash::encode(x_components, y_components, small.width(), small.height(), small.as_raw())?;
+    Ok(hash)
+}
+
+/// Padding kept around trimmed content, as a fraction of the content size
+const TRIM_PADDING_RATIO: f32 = 0.01;
+
+/// Find the bounding box of non-blank content as (x, y, width, height)
+///
+/// A row or column counts as content when its mean brightness is below
+/// `threshold` (0-255). Returns None when the whole image is blank.
+pub fn content_bounds(img: &DynamicImage, threshold: u8) -> Option<(u32, u32, u32, u32)> {
+    let gray = img.to_luma8();
+    let (width, height) = gray.dimensions();
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let mut row_sums = vec![0u64; height as usize];
+    let mut col_sums = vec![0u64; width as usize];
+    for (x, y, pixel) in gray.enumerate_pixels() {
+        row_sums[y as usize] += pixel.0[0] as u64;
+        col_sums[x as usize] += pixel.0[0] as u64;
+    }
+
+    let limit = threshold as u64;
+    let is_content = |sum: &u64, len: u32| *sum < limit * len as u64;
+    let top = row_sums.iter().position(|s| is_content(s, width))?;
+    let bottom = row_sums.iter().rposition(|s| is_content(s, width))?;
+    let left = col_sums.iter().position(|s| is_content(s, height))?;
+    let right = col_sums.iter().rposition(|s| is_content(s, height))?;
+
+    Some((left as u32, top as u32, (right - left + 1) as u32, (bottom - top + 1) as u32))
+}
+
+/// Crop away near-white borders, keeping a small padding around the content
+///
+/// Images with no detectable content are returned unchanged.
+pub fn trim_borders(img: &DynamicImage, threshold: u8) -> DynamicImage {
+    let Some((x, y, width, height)) = content_bounds(img, threshold) else {
+        return img.clone();
+    };
+
+    let padding = (width.max(height) as f32 * TRIM_PADDING_RATIO).ceil() as u32;
+    let left = x.saturating_sub(padding);
+    let top = y.saturating_sub(padding);
+    let right = (x + width + padding).min(img.width());
+    let bottom = (y + height + padding).min(img.height());
+
+    img.crop_imm(left, top, right - left, bottom - top)
+}
+
+/// Side length of the grayscale image a perceptual hash is computed from
+const PHASH_SIZE: usize = 32;
+
+/// Side length of the low-frequency DCT block kept for the hash (64 bits)
+const PHASH_LOW_FREQ: usize = 8;
+
+/// Compute a 64-bit DCT-based perceptual hash (pHash)
+///
+/// The image is shrunk to 32x32 grayscale and transformed with a 2D DCT.
+/// Each bit of the hash is set when the matching coefficient of the top-left
+/// 8x8 (lowest frequency) block is above the block's median, so small edits,
+/// re-encodes and resizes barely change the hash.
+pub fn perceptual_hash(img: &DynamicImage) -> u64 {
+    let gray = img
+        .resize_exact(PHASH_SIZE as u32, PHASH_SIZE as u32, FilterType::Triangle)
+        .to_luma8();
+    let pixels: Vec<f64> = gray.pixels().map(|p| p.0[0] as f64).collect();
+
+    // cos((2x + 1) * u * pi / 2N) for every sample x and frequency u
+    let n = PHASH_SIZE as f64;
+    let mut cosines = [[0.0; PHASH_SIZE]; PHASH_LOW_FREQ];
+    for (u, row) in cosines.iter_mut().enumerate() {
+        for (x, c) in row.iter_mut().enumerate() {
+            *c = ((2 * x + 1) as f64 * u as f64 * std::f64::consts::PI / (2.0 * n)).cos();
+        }
+    }
+
+    let mut coefficients = Vec::with_capacity(PHASH_LOW_FREQ * PHASH_LOW_FREQ);
+    for v in 0..PHASH_LOW_FREQ {
+        for u in 0..PHASH_LOW_FREQ {
+            let mut sum = 0.0;
+            for y in 0..PHASH_SIZE {
+                for x in 0..PHASH_SIZE {
+                    sum += pixels[y * PHASH_SIZE + x] * cosines[u][x] * cosines[v][y];
+                }
+            }
+            coefficients.push(sum);
+        }
+    }
+
+    // The DC term only reflects overall brightness, so leave it out of the median
+    let mut sorted = coefficients[1..].to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = sorted[sorted.len() / 2];
+
+    coefficients.iter()
+        .enumerate()
+        .filter(|(_, c)| **c > median)
+        .fold(0u64, |hash, (i, _)| hash | (1 << i))
+}
+
+/// Number of differing bits between two hashes
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Group indices whose hashes are within `threshold` bits of each other
+///
+/// Grouping is transitive: if a~b and b~c, all three end up in one group.
+/// Only groups with at least two members are returned, each sorted, in order
+/// of their first index.
+pub fn group_similar(hashes: &[u64], threshold: u32) -> Vec<Vec<usize>> {
+    // Union-find over indices
+    let mut parent: Vec<usize> = (0..hashes.len()).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    for i in 0..hashes.len() {
+        for j in i + 1..hashes.len() {
+            if hamming_distance(hashes[i], hashes[j]) <= threshold {
+                let (ri, rj) = (find(&mut parent, i), find(&mut parent, j));
+                if ri != rj {
+                    parent[rj.max(ri)] = ri.min(rj);
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_of_root = std::collections::HashMap::new();
+    for i in 0..hashes.len() {
+        let root = find(&mut parent, i);
+        let group = *group_of_root.entry(root).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[group].push(i);
+    }
+
+    groups.retain(|g| g.len() > 1);
+    groups
+}
+
+/// Percentage below the local mean brightness at which a pixel counts as ink
+const OCR_THRESHOLD_PERCENT: u64 = 15;
+
+/// Binarize a page for OCR: black text on a white background
+///
+/// Uses an adaptive (local mean) threshold so uneven lighting, yellowed paper
+/// and gradients don't swallow text. Pages that are mostly dark (light text
+/// on a dark background) are inverted first.
+pub fn ocr_binarize(img: &DynamicImage) -> GrayImage {
+    let mut gray = img.to_luma8();
+    let (width, height) = gray.dimensions();
+    if width == 0 || height == 0 {
+        return gray;
+    }
+
+    let pixel_count = width as u64 * height as u64;
+    let mean = gray.pixels().map(|p| p.0[0] as u64).sum::<u64>() / pixel_count;
+    if mean < 128 {
+        imageops::invert(&mut gray);
+    }
+
+    // Summed-area table, one row and column larger than the image
+    let stride = width as usize + 1;
+    let mut integral = vec![0u64; stride * (height as usize + 1)];
+    for y in 0..height as usize {
+        let mut row_sum = 0;
+        for x in 0..width as usize {
+            row_sum += gray.get_pixel(x as u32, y as u32).0[0] as u64;
+            integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + row_sum;
+        }
+    }
+
+    let half = (width.max(height) / 32).max(1) as usize;
+    GrayImage::from_fn(width, height, |x, y| {
+        let (x, y) = (x as usize, y as usize);
+        let (x0, y0) = (x.saturating_sub(half), y.saturating_sub(half));
+        let (x1, y1) = ((x + half + 1).min(width as usize), (y + half + 1).min(height as usize));
+        let count = ((x1 - x0) * (y1 - y0)) as u64;
+        let sum = integral[y1 * stride + x1] + integral[y0 * stride + x0]
+            - integral[y0 * stride + x1] - integral[y1 * stride + x0];
+
+        let value = gray.get_pixel(x as u32, y as u32).0[0] as u64;
+        if value * count * 100 < sum * (100 - OCR_THRESHOLD_PERCENT) {
+            Luma([0])
+        } else {
+            Luma([255])
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::GenericImageView;
+
+    #[test]
+    fn test_diff_images_highlights_changes() {
+        let a = RgbImage::from_pixel(4, 4, Rgb([255, 255, 255]));
+        let mut b = a.clone();
+        b.put_pixel(1, 2, Rgb([0, 0, 0]));
+
+        let diff = diff_images(&DynamicImage::ImageRgb8(a), &DynamicImage::ImageRgb8(b)).to_rgb8();
+
+        let changed = diff.get_pixel(1, 2);
+        assert!(changed.0[0] > 128 && changed.0[1] == 0 && changed.0[2] == 0);
+
+        let unchanged = diff.get_pixel(0, 0);
+        assert_eq!(unchanged.0[0], unchanged.0[1]);
+    }
+
+    #[test]
+    fn test_diff_images_uses_smaller_size() {
+        let a = DynamicImage::new_rgb8(10, 8);
+        let b = DynamicImage::new_rgb8(6, 12);
+
+        let diff = diff_images(&a, &b);
+        assert_eq!(diff.dimensions(), (6, 8));
+    }
+
+    #[test]
+    fn test_ocr_binarize() {
+        // Dark text on a background that fades from white to mid gray
+        let page = RgbImage::from_fn(64, 64, |x, y| {
+            if (20..24).contains(&y) && (10..50).contains(&x) {
+                Rgb([40, 40, 40])
+            } else {
+                let shade = (255 - x * 2) as u8;
+                Rgb([shade, shade, shade])
+            }
+        });
+        let binary = ocr_binarize(&DynamicImage::ImageRgb8(page));
+        assert_eq!(binary.get_pixel(12, 21).0[0], 0);
+        assert_eq!(binary.get_pixel(48, 21).0[0], 0);
+        assert_eq!(binary.get_pixel(60, 50).0[0], 255);
+        assert_eq!(binary.get_pixel(2, 2).0[0], 255);
+
+        // Light text on black comes out as black on white too
+        let inverted = page_text_on(Rgb([0, 0, 0]), Rgb([230, 230, 230]));
+        let binary = ocr_binarize(&DynamicImage::ImageRgb8(inverted));
+        assert_eq!(binary.get_pixel(30, 21).0[0], 0);
+        assert_eq!(binary.get_pixel(30, 50).0[0], 255);
+    }
+
+    /// A 64x64 page of `background` with a bar of `text` across it
+    fn page_text_on(background: Rgb<u8>, text: Rgb<u8>) -> RgbImage {
+        RgbImage::from_fn(64, 64, |x, y| {
+            if (20..24).contains(&y) && (10..50).contains(&x) { text } else { background }
+        })
+    }
+
+    #[test]
+    fn test_rotate() {
+        let img = DynamicImage::new_rgb8(4, 2);
+        assert_eq!(rotate(&img, 90).dimensions(), (2, 4));
+        assert_eq!(rotate(&img, 180).dimensions(), (4, 2));
+        assert_eq!(rotate(&img, 270).dimensions(), (2, 4));
+        assert_eq!(rotate(&img, 0).dimensions(), (4, 2));
+    }
+
+    #[test]
+    fn test_pack_sprite_sheet_layout() {
+        let white = DynamicImage::ImageRgb8(RgbImage::from_pixel(20, 10, Rgb([255, 255, 255])));
+        let images = vec![Some(white.clone()), None, Some(white)];
+
+        let atlas = pack_sprite_sheet(&images, 2, 10).to_rgb8();
+        assert_eq!(atlas.dimensions(), (20, 20));
+
+        // First cell: 20x10 shrunk to 10x5, centered vertically
+        assert_eq!(atlas.get_pixel(5, 5).0, [255, 255, 255]);
+        assert_eq!(atlas.get_pixel(5, 0).0, [0, 0, 0]);
+        // Second cell is blank, third starts the next row
+        assert_eq!(atlas.get_pixel(15, 5).0, [0, 0, 0]);
+        assert_eq!(atlas.get_pixel(5, 15).0, [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_compose_spread() {
+        let left = DynamicImage::ImageRgb8(RgbImage::from_pixel(10, 20, Rgb([255, 0, 0])));
+        let right = DynamicImage::ImageRgb8(RgbImage::from_pixel(20, 40, Rgb([0, 0, 255])));
+
+        // The taller page is halved to match the shorter one
+        let spread = compose_spread(&[left, right]).to_rgb8();
+        assert_eq!(spread.dimensions(), (20, 20));
+        assert_eq!(spread.get_pixel(5, 10).0, [255, 0, 0]);
+        assert_eq!(spread.get_pixel(15, 10).0, [0, 0, 255]);
+    }
+
+    #[test]
+    fn test_average_color() {
+        let mut img = RgbImage::from_pixel(32, 32, Rgb([200, 0, 0]));
+        for x in 0..16 {
+            for y in 0..32 {
+                img.put_pixel(x, y, Rgb([0, 0, 100]));
+            }
+        }
+        assert_eq!(average_color(&DynamicImage::ImageRgb8(img)), [100, 0, 50]);
+    }
+
+    #[test]
+    fn test_blurhash() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(100, 50, Rgb([40, 120, 200])));
+        let hash = blurhash(&img, 4, 3).unwrap();
+
+        // 1 size char + 1 max AC char + 4 DC chars + 2 per AC component
+        assert_eq!(hash.len(), 6 + 2 * (4 * 3 - 1));
+        assert!(blurhash(&img, 0, 3).is_err());
+        assert!(blurhash(&img, 4, 10).is_err());
+    }
+
+    #[test]
+    fn test_trim_borders() {
+        let mut page = RgbImage::from_pixel(200, 100, Rgb([255, 255, 255]));
+        for x in 50..150 {
+            for y in 20..60 {
+                page.put_pixel(x, y, Rgb([0, 0, 0]));
+            }
+        }
+        let page = DynamicImage::ImageRgb8(page);
+
+        assert_eq!(content_bounds(&page, 240), Some((50, 20, 100, 40)));
+
+        // 1% padding of the 100px content on each side
+        assert_eq!(trim_borders(&page, 240).dimensions(), (102, 42));
+
+        let blank = DynamicImage::ImageRgb8(RgbImage::from_pixel(10, 10, Rgb([255, 255, 255])));
+        assert_eq!(content_bounds(&blank, 240), None);
+        assert_eq!(trim_borders(&blank, 240).dimensions(), (10, 10));
+    }
+
+    /// A test pattern whose content depends on `seed`
+    fn pattern(seed: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(64, 64, |x, y| {
+            let v = ((x * seed + y * (seed + 3)) % 97) as u8 * 2;
+            Rgb([v, v, v])
+        }))
+    }
+
+    #[test]
+    fn test_perceptual_hash_survives_resize() {
+        let img = pattern(5);
+        let resized = img.resize_exact(40, 40, FilterType::Lanczos3);
+
+        let distance = hamming_distance(perceptual_hash(&img), perceptual_hash(&resized));
+        assert!(distance <= 6, "distance {}", distance);
+
+        let other = hamming_distance(perceptual_hash(&img), perceptual_hash(&pattern(11)));
+        assert!(other > 10, "distance {}", other);
+    }
+
+    #[test]
+    fn test_group_similar() {
+        assert_eq!(hamming_distance(0b1011, 0b0001), 2);
+
+        let hashes = [0x0, u64::MAX, 0x1, 0x3, u64::MAX ^ 0x10];
+        assert_eq!(group_similar(&hashes, 1), vec![vec![0, 2, 3], vec![1, 4]]);
+        assert!(group_similar(&hashes, 0).is_empty());
+    }
+}
@@ -1,20 +1,150 @@
 mod scene;
+mod archive;
 mod image_loader;
+mod image_ops;
+mod storage;
+mod recent;
+mod reading;
+mod decode_pool;
+mod disk_cache;
+mod error;
+mod share_link;
 mod commands;
 
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
+
 use commands::{
     AppState, load_scene_collection, get_scene_info, get_image,
     next_page, prev_page, get_scene_list, next_scene, prev_scene,
-    get_scene_loop_enabled, set_scene_loop_enabled,
+    get_scene_loop_enabled, set_scene_loop_enabled, set_log_level, get_last_page_timings,
+    get_loop_mode, set_loop_mode, diff_pages,
+    get_scene_page_count, get_image_native, set_load_retries,
+    get_recent_collections, clear_recent_collections, get_page_titles,
+    get_thumbnail_sprite, get_jpeg_options, set_jpeg_options,
+    detect_page_format, get_collection_config, set_collection_config,
+    warm_scene_covers, get_scene_cover, load_single_scene,
+    get_preload_strategy, set_preload_strategy, page_phash, find_duplicate_pages,
+    get_scene_rotation, set_scene_rotation, get_max_concurrent_decodes, set_max_concurrent_decodes,
+    probe_collection, get_progressive_loading, set_progressive_loading,
+    get_image_trimmed, get_cache_decoded, set_cache_decoded,
+    get_scene_source, save_scene_source, get_page_dominant_color, get_page_blurhash,
+    skip_pages, get_thumbnail_fallback, set_thumbnail_fallback,
+    get_strict_scene_loading, set_strict_scene_loading, get_tier_formats, set_tier_formats,
+    count_scene_collections, export_page_range, get_defined_spread,
+    get_position, set_position, get_image_pyramid,
+    mark_page_read, is_page_read, get_unread_count, get_auto_mark_read, set_auto_mark_read,
+    get_display_resolution, set_display_resolution, reorder_scenes, get_page_file_info, get_scene_file_infos,
+    get_preload_concurrency, set_preload_concurrency, get_collection_montage,
+    is_upgrade_in_progress, get_inflight_upgrades, get_memory_limit_mb, set_memory_limit_mb, trim_memory,
+    next_collection, prev_collection, get_wrap_collections, set_wrap_collections,
+    get_at_collection_end, set_at_collection_end, get_image_ocr_prep, get_table_of_contents,
+    get_resize_enabled, set_resize_enabled, get_disk_thumbnail_cache, set_disk_thumbnail_cache, clear_disk_thumbnail_cache,
+    get_scene_layout_hint, get_share_link, open_share_link, remove_page, move_page, save_scene,
+    benchmark_cold_page, get_spread_index, get_spread_mode, set_spread_mode, get_reading_direction, set_reading_direction,
+    get_navigation_direction, get_image_rgba, get_max_image_side, set_max_image_side,
+    peek_next_page, peek_prev_page, get_preload_paused, set_preload_paused,
+    get_scene_dimension_summary, verify_page_decodes, verify_all_decodes,
+    get_decode_threads, set_decode_threads, get_image_dimensions,
+    get_cache_stats, clear_caches, get_max_dimension, set_max_dimension,
+    get_preload_counts, set_preload_counts, jump_to_scene_by_name,
+    get_scene_summaries, start_slideshow, stop_slideshow, get_slideshow_running,
+    get_spread,
 };
 
+/// Initialize logging. RUST_LOG overrides the defaults when set;
+/// otherwise our own logs are shown at info (debug builds) or warn (release)
+fn init_logging() {
+    let default_level = if cfg!(debug_assertions) {
+        log::LevelFilter::Info
+    } else {
+        log::LevelFilter::Warn
+    };
+
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Warn)
+        .filter_module("fastviewer_lib", log::LevelFilter::Trace)
+        .parse_default_env()
+        .init();
+
+    // Keep the runtime level adjustable via set_log_level
+    if std::env::var_os("RUST_LOG").is_none() {
+        log::set_max_level(default_level);
+    }
+}
+
+/// Open a `fastviewer://` share link handed over by the OS, either the one
+/// the app was launched with or one clicked while it is running
+fn open_deep_link(app: AppHandle, url: tauri::Url) {
+    if url.scheme() != share_link::SHARE_LINK_SCHEME {
+        log::warn!("Ignoring deep link with unknown scheme: {}", url);
+        return;
+    }
+    let url = url.to_string();
+
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        match commands::open_share_link(url.clone(), state, app.clone()).await {
+            Ok(_) => {
+                // The frontend may already show the first page; tell it to follow
+                let Ok(position) = commands::get_position(app.state::<AppState>()).await else { return };
+                if let Err(e) = app.emit("share-link-opened", position) {
+                    log::warn!("Failed to emit share-link-opened: {}", e);
+                }
+            }
+            Err(e) => log::warn!("Failed to open share link {}: {}", url, e),
+        }
+    });
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    init_logging();
+
     let app_state = AppState::new();
 
-    tauri::Builder::default()
+    #[allow(unused_mut)]
+    let mut builder = tauri::Builder::default();
+    // Registered first so a link opened while the app runs reaches this
+    // instance (through on_open_url) instead of starting a second one
+    #[cfg(desktop)]
+    {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.set_focus();
+            }
+        }));
+    }
+
+    builder
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_opener::init())
         .manage(app_state)
+        .setup(|app| {
+            app.state::<AppState>().load_persisted(app.handle());
+            commands::spawn_memory_watchdog(app.handle().clone());
+
+            // Installers register the scheme; this covers dev and portable builds
+            #[cfg(any(windows, target_os = "linux"))]
+            if let Err(e) = app.deep_link().register_all() {
+                log::warn!("Failed to register deep link schemes: {}", e);
+            }
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    open_deep_link(handle.clone(), url);
+                }
+            });
+            match app.deep_link().get_current() {
+                Ok(urls) => {
+                    for url in urls.unwrap_or_default() {
+                        open_deep_link(app.handle().clone(), url);
+                    }
+                }
+                Err(e) => log::warn!("Failed to get the launch deep link: {}", e),
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             load_scene_collection,
             get_scene_info,
@@ -26,6 +156,126 @@ pub fn run() {
             prev_scene,
             get_scene_loop_enabled,
             set_scene_loop_enabled,
+            set_log_level,
+            get_last_page_timings,
+            get_loop_mode,
+            set_loop_mode,
+            diff_pages,
+            get_scene_page_count,
+            get_image_native,
+            set_load_retries,
+            get_recent_collections,
+            clear_recent_collections,
+            get_page_titles,
+            get_thumbnail_sprite,
+            get_jpeg_options,
+            set_jpeg_options,
+            detect_page_format,
+            get_collection_config,
+            set_collection_config,
+            warm_scene_covers,
+            get_scene_cover,
+            load_single_scene,
+            get_preload_strategy,
+            set_preload_strategy,
+            page_phash,
+            find_duplicate_pages,
+            get_scene_rotation,
+            set_scene_rotation,
+            get_max_concurrent_decodes,
+            set_max_concurrent_decodes,
+            probe_collection,
+            get_progressive_loading,
+            set_progressive_loading,
+            get_image_trimmed,
+            get_cache_decoded,
+            set_cache_decoded,
+            get_scene_source,
+            save_scene_source,
+            get_page_dominant_color,
+            get_page_blurhash,
+            skip_pages,
+            get_thumbnail_fallback,
+            set_thumbnail_fallback,
+            get_strict_scene_loading,
+            set_strict_scene_loading,
+            get_tier_formats,
+            set_tier_formats,
+            count_scene_collections,
+            export_page_range,
+            get_defined_spread,
+            get_position,
+            set_position,
+            get_image_pyramid,
+            mark_page_read,
+            is_page_read,
+            get_unread_count,
+            get_auto_mark_read,
+            set_auto_mark_read,
+            get_display_resolution,
+            set_display_resolution,
+            reorder_scenes,
+            get_page_file_info,
+            get_scene_file_infos,
+            get_preload_concurrency,
+            set_preload_concurrency,
+            get_collection_montage,
+            is_upgrade_in_progress,
+            get_inflight_upgrades,
+            get_memory_limit_mb,
+            set_memory_limit_mb,
+            trim_memory,
+            next_collection,
+            prev_collection,
+            get_wrap_collections,
+            set_wrap_collections,
+            get_at_collection_end,
+            set_at_collection_end,
+            get_image_ocr_prep,
+            get_table_of_contents,
+            get_resize_enabled,
+            set_resize_enabled,
+            get_disk_thumbnail_cache,
+            set_disk_thumbnail_cache,
+            clear_disk_thumbnail_cache,
+            get_scene_layout_hint,
+            get_share_link,
+            open_share_link,
+            remove_page,
+            move_page,
+            save_scene,
+            benchmark_cold_page,
+            get_spread_index,
+            get_spread_mode,
+            set_spread_mode,
+            get_reading_direction,
+            set_reading_direction,
+            get_navigation_direction,
+            get_image_rgba,
+            get_max_image_side,
+            set_max_image_side,
+            peek_next_page,
+            peek_prev_page,
+            get_preload_paused,
+            set_preload_paused,
+            get_scene_dimension_summary,
+            verify_page_decodes,
+            verify_all_decodes,
+            get_decode_threads,
+            set_decode_threads,
+            get_image_dimensions,
+            get_cache_stats,
+            clear_caches,
+            get_max_dimension,
+            set_max_dimension,
+            get_preload_counts,
+            set_preload_counts,
+            jump_to_scene_by_name,
+            get_scene_summaries,
+            start_slideshow,
+            stop_slideshow,
+            get_slideshow_running,
+            get_spread,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
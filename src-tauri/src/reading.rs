@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+/// File in the app data directory holding the read pages of every scene
+pub const READ_PAGES_FILE: &str = "read_pages.json";
+
+/// File in the app data directory holding the IDs of read pages that have
+/// one, per scene; those pages aren't in READ_PAGES_FILE
+pub const READ_PAGE_IDS_FILE: &str = "read_page_ids.json";
+
+/// Set of page indices stored as a bitset, 64 pages per word
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PageSet(Vec<u64>);
+
+impl PageSet {
+    /// Add a page, returning false if it was already in the set
+    pub fn insert(&mut self, page: usize) -> bool {
+        let (word, bit) = (page / 64, 1u64 << (page % 64));
+        if word >= self.0.len() {
+            self.0.resize(word + 1, 0);
+        }
+        let added = self.0[word] & bit == 0;
+        self.0[word] |= bit;
+        added
+    }
+
+    /// Check whether a page is in the set
+    pub fn contains(&self, page: usize) -> bool {
+        self.0.get(page / 64)
+            .is_some_and(|word| word & (1u64 << (page % 64)) != 0)
+    }
+
+    /// Number of pages in the set below `page_count`, so pages left over from
+    /// an older, longer version of a scene aren't counted
+    pub fn count(&self, page_count: usize) -> usize {
+        self.0.iter()
+            .enumerate()
+            .map(|(i, word)| {
+                let start = i * 64;
+                if start >= page_count {
+                    0
+                } else if page_count - start >= 64 {
+                    word.count_ones() as usize
+                } else {
+                    (word & ((1u64 << (page_count - start)) - 1)).count_ones() as usize
+                }
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_set() {
+        let mut pages = PageSet::default();
+        assert!(pages.insert(3));
+        assert!(pages.insert(64));
+        assert!(pages.insert(130));
+        assert!(!pages.insert(3));
+
+        assert!(pages.contains(3) && pages.contains(64) && pages.contains(130));
+        assert!(!pages.contains(4) && !pages.contains(1000));
+
+        assert_eq!(pages.count(200), 3);
+        assert_eq!(pages.count(65), 2);
+        assert_eq!(pages.count(64), 1);
+        assert_eq!(pages.count(0), 0);
+
+        // Serialized as the bare list of words
+        assert_eq!(serde_json::to_string(&pages).unwrap(), "[8,1,4]");
+    }
+}
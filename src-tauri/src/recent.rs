@@ -0,0 +1,97 @@
+use crate::storage::{load_json, save_json};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+/// File in the app data directory holding the recent collections list
+const RECENT_FILE: &str = "recent_collections.json";
+
+/// Maximum number of recent collections remembered
+pub const MAX_RECENT: usize = 20;
+
+/// A recently opened collection as persisted to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentEntry {
+    pub path: String,
+    /// Seconds since the Unix epoch
+    pub last_opened: u64,
+}
+
+/// A recently opened collection as returned to the frontend
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecentItem {
+    pub path: String,
+    pub name: String,
+    pub last_opened: u64,
+    /// False when the directory no longer exists
+    pub exists: bool,
+}
+
+/// Move `path` to the front of the list, dropping the oldest entries past MAX_RECENT
+pub fn push_recent(entries: &mut Vec<RecentEntry>, path: &str, last_opened: u64) {
+    entries.retain(|e| e.path != path);
+    entries.insert(0, RecentEntry { path: path.to_string(), last_opened });
+    entries.truncate(MAX_RECENT);
+}
+
+/// Load the recent collections list, most recent first
+pub fn load_recent(app: &AppHandle) -> Result<Vec<RecentEntry>> {
+    Ok(load_json(app, RECENT_FILE)?.unwrap_or_default())
+}
+
+/// Record that a collection was just opened
+pub fn record_recent(app: &AppHandle, path: &str) -> Result<()> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let mut entries = load_recent(app)?;
+    push_recent(&mut entries, path, now);
+    save_json(app, RECENT_FILE, &entries)
+}
+
+/// Forget all recent collections
+pub fn clear_recent(app: &AppHandle) -> Result<()> {
+    save_json(app, RECENT_FILE, &Vec::<RecentEntry>::new())
+}
+
+impl From<RecentEntry> for RecentItem {
+    fn from(entry: RecentEntry) -> Self {
+        let path = Path::new(&entry.path);
+        RecentItem {
+            name: path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("Unknown")
+                .to_string(),
+            exists: path.is_dir(),
+            last_opened: entry.last_opened,
+            path: entry.path,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_recent_dedupes_and_caps() {
+        let mut entries = Vec::new();
+        for i in 0..MAX_RECENT + 5 {
+            push_recent(&mut entries, &format!("/c/{}", i), i as u64);
+        }
+        assert_eq!(entries.len(), MAX_RECENT);
+        assert_eq!(entries[0].path, format!("/c/{}", MAX_RECENT + 4));
+
+        // Reopening moves an existing entry to the front instead of duplicating it
+        push_recent(&mut entries, "/c/10", 100);
+        assert_eq!(entries.len(), MAX_RECENT);
+        assert_eq!(entries[0].path, "/c/10");
+        assert_eq!(entries[0].last_opened, 100);
+        assert_eq!(entries.iter().filter(|e| e.path == "/c/10").count(), 1);
+    }
+}
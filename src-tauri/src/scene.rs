@@ -1,48 +1,265 @@
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use anyhow::{Context, Result};
+use crate::archive;
+use crate::image_loader;
+use log::{debug, warn};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Width and height in pixels; 0x0 when a scene file doesn't give them
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct ImageSize {
     pub width: u32,
     pub height: u32,
 }
 
+/// Scene format versions this build reads; a file with the same major
+/// version as one of these and a newer minor version is read too
+const SUPPORTED_VERSIONS: &[&str] = &["1.0"];
+
+/// Major and minor number of a `MAJOR[.MINOR[.PATCH]]` version string
+fn parse_version(version: &str) -> Option<(u64, u64)> {
+    let mut parts = version.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    match (parts.next(), parts.next()) {
+        (None | Some(Some(_)), None) => Some((major, minor)),
+        _ => None,
+    }
+}
+
+/// Check that a scene format version is one this build can read
+///
+/// An empty version (files from before the field existed) counts as 1.0.
+fn check_version(version: &str) -> Result<()> {
+    if version.is_empty() {
+        return Ok(());
+    }
+    let (major, _) = parse_version(version)
+        .with_context(|| format!("Malformed scene version {:?}", version))?;
+    let supported = SUPPORTED_VERSIONS.iter()
+        .filter_map(|supported| parse_version(supported))
+        .any(|(supported_major, _)| supported_major == major);
+    if !supported {
+        anyhow::bail!("Unsupported scene version {:?} (supported: {})", version, SUPPORTED_VERSIONS.join(", "));
+    }
+    Ok(())
+}
+
+/// Scene file metadata; only the scene name is required, so minimal or
+/// older scene files still load
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SceneMetadata {
+    #[serde(default)]
     pub version: String,
     #[serde(rename = "sceneName")]
     pub scene_name: String,
-    #[serde(rename = "imageSize")]
+    #[serde(rename = "imageSize", default)]
     pub image_size: ImageSize,
-    #[serde(rename = "thumbnailSize")]
+    #[serde(rename = "thumbnailSize", default)]
     pub thumbnail_size: ImageSize,
+    /// Page a scene opens on when entered fresh, e.g. to skip a blank cover
+    #[serde(rename = "defaultPage", default, skip_serializing_if = "Option::is_none")]
+    pub default_page: Option<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Page {
     pub image: String,
+    /// Optional stable identifier from the exporter; read state follows the
+    /// page by it when pages are reordered or removed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Optional page label, e.g. "Figure 3"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Optional longer description of the page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Scene {
     pub metadata: SceneMetadata,
     pub pages: Vec<Page>,
+    /// Authored page groupings shown side by side, e.g. `[[0], [1, 2]]`;
+    /// computed by `computed_spreads` when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spreads: Option<Vec<Vec<usize>>>,
+}
+
+/// Just the metadata of a scene file; the page list is skipped over
+#[derive(Deserialize)]
+struct SceneHeader {
+    metadata: SceneMetadata,
 }
 
 impl Scene {
+    /// Load a scene from a JSON file, or expand a directory of images or a
+    /// CBZ/ZIP archive into one
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if path.is_dir() {
+            Self::from_image_dir(path)
+        } else if archive::is_archive_path(path) {
+            Self::from_archive(path)
+        } else {
+            Self::load_from_file(path)
+        }
+    }
+
+    /// Whether a scene path is a JSON scene file, as opposed to an image
+    /// directory or archive expanded into a scene
+    pub fn has_scene_file(path: &Path) -> bool {
+        !path.is_dir() && !archive::is_archive_path(path)
+    }
+
+    /// Build a scene from the image files of a directory, one page per file
+    /// in natural filename order (`page2` before `page10`)
+    pub fn from_image_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut images: Vec<PathBuf> = std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read image directory: {:?}", dir))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && is_image_file(path))
+            .collect();
+        if images.is_empty() {
+            anyhow::bail!("No images in directory: {:?}", dir);
+        }
+        let file_name = |path: &Path| path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        images.sort_by(|a, b| natural_cmp(&file_name(a), &file_name(b)));
+
+        let images = images.into_iter().map(|path| path.to_string_lossy().into_owned()).collect();
+        let scene = Self::from_images(file_name(dir), images);
+        debug!("Expanded image directory {:?} ({} pages)", dir, scene.page_count());
+        Ok(scene)
+    }
+
+    /// Build a scene from the image entries of a CBZ/ZIP archive, one page
+    /// per entry in natural order
+    ///
+    /// Pages point into the archive (see archive::entry_path) and are read
+    /// from it when decoded. The scene is named after the archive file.
+    pub fn from_archive<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let entries = archive::list_images(path)?;
+        if entries.is_empty() {
+            anyhow::bail!("No images in archive: {:?}", path);
+        }
+
+        let images = entries.iter().map(|entry| archive::entry_path(path, entry)).collect();
+        let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        let scene = Self::from_images(name, images);
+        debug!("Expanded archive {:?} ({} pages)", path, scene.page_count());
+        Ok(scene)
+    }
+
+    /// A scene of the given page images, sized after the first one
+    fn from_images(scene_name: String, images: Vec<String>) -> Self {
+        // Header-only read
+        let (width, height) = image_loader::image_dimensions(&images[0]).unwrap_or((0, 0));
+        Scene {
+            metadata: SceneMetadata {
+                version: "1.0".to_string(),
+                scene_name,
+                image_size: ImageSize { width, height },
+                thumbnail_size: ImageSize { width: 0, height: 0 },
+                default_page: None,
+            },
+            pages: images.into_iter()
+                .map(|image| Page { image, id: None, title: None, caption: None })
+                .collect(),
+            spreads: None,
+        }
+    }
+
     /// Load a scene from a JSON file
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref();
-        let content = std::fs::read_to_string(path)
-            .with_context(|| format!("Failed to read scene file: {:?}", path))?;
+        let content = Self::read_source(path)?;
 
         let scene: Scene = serde_json::from_str(&content)
             .with_context(|| format!("Failed to parse scene JSON: {:?}", path))?;
+        scene.validate()
+            .with_context(|| format!("Invalid scene: {:?}", path))?;
 
+        debug!("Loaded scene {:?} ({} pages)", path, scene.page_count());
         Ok(scene)
     }
 
+    /// Read the raw JSON text of a scene file
+    pub fn read_source<P: AsRef<Path>>(path: P) -> Result<String> {
+        let path = path.as_ref();
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scene file: {:?}", path))
+    }
+
+    /// Replace a scene file with new JSON text, after checking it parses as a scene
+    ///
+    /// The text is written as-is so unknown fields and formatting survive.
+    pub fn write_source<P: AsRef<Path>>(path: P, content: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let scene: Scene = serde_json::from_str(content)
+            .context("Scene JSON is invalid")?;
+        scene.validate()?;
+        write_scene_file(path, content)?;
+        Ok(scene)
+    }
+
+    /// Write the scene to a JSON file, replacing it
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .context("Failed to serialize scene")?;
+        write_scene_file(path.as_ref(), &content)
+    }
+
+    /// Read only the metadata of a scene file, without building its page list
+    pub fn load_metadata<P: AsRef<Path>>(path: P) -> Result<SceneMetadata> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scene file: {:?}", path))?;
+
+        let header: SceneHeader = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse scene JSON: {:?}", path))?;
+        Ok(header.metadata)
+    }
+
+    /// Check the format version, and references between parts of the scene
+    /// that serde can't
+    pub fn validate(&self) -> Result<()> {
+        check_version(&self.metadata.version)?;
+        if let Some(page) = self.metadata.default_page {
+            if page >= self.page_count() {
+                anyhow::bail!("Default page {} is out of range, the scene has {} pages", page, self.page_count());
+            }
+        }
+        for (i, spread) in self.spreads.iter().flatten().enumerate() {
+            if spread.is_empty() {
+                anyhow::bail!("Spread {} has no pages", i);
+            }
+            if let Some(page) = spread.iter().find(|&&page| page >= self.page_count()) {
+                anyhow::bail!("Spread {} refers to page {} but the scene has {} pages", i, page, self.page_count());
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the page groupings shown side by side: the authored spreads if
+    /// the scene has them, otherwise the pairing computed for `mode`
+    pub fn spreads(&self, mode: SpreadMode) -> Vec<Vec<usize>> {
+        match &self.spreads {
+            Some(spreads) => spreads.clone(),
+            None => computed_spreads(self.page_count(), mode),
+        }
+    }
+
+    /// Get the page the scene opens on: its default page, or the first
+    pub fn start_page(&self) -> usize {
+        self.metadata.default_page.unwrap_or(0)
+    }
+
     /// Get total number of pages in the scene
     pub fn page_count(&self) -> usize {
         self.pages.len()
@@ -53,6 +270,69 @@ impl Scene {
         self.pages.get(index).map(|p| p.image.as_str())
     }
 
+    /// Get a page by index
+    pub fn get_page(&self, index: usize) -> Option<&Page> {
+        self.pages.get(index)
+    }
+
+    /// Remove a page, keeping authored spreads pointing at the same pages;
+    /// a scene's only page can't be removed
+    pub fn remove_page(&mut self, index: usize) -> Result<Page> {
+        if index >= self.page_count() {
+            anyhow::bail!("Page index {} out of bounds (total: {})", index, self.page_count());
+        }
+        if self.page_count() == 1 {
+            anyhow::bail!("Can't remove the only page of a scene");
+        }
+
+        if let Some(spreads) = &mut self.spreads {
+            for spread in spreads.iter_mut() {
+                spread.retain(|&page| page != index);
+                for page in spread.iter_mut() {
+                    if *page > index {
+                        *page -= 1;
+                    }
+                }
+            }
+            spreads.retain(|spread| !spread.is_empty());
+        }
+        let page = self.pages.remove(index);
+        let count = self.pages.len();
+        if let Some(default_page) = &mut self.metadata.default_page {
+            if *default_page > index || *default_page == count {
+                *default_page -= 1;
+            }
+        }
+        Ok(page)
+    }
+
+    /// Move a page from `from` to `to`, shifting the pages in between and
+    /// keeping authored spreads pointing at the same pages
+    pub fn move_page(&mut self, from: usize, to: usize) -> Result<()> {
+        let count = self.page_count();
+        if from >= count || to >= count {
+            anyhow::bail!("Page index {} out of bounds (total: {})", from.max(to), count);
+        }
+
+        let page = self.pages.remove(from);
+        self.pages.insert(to, page);
+        let moved = self.spreads.iter_mut().flatten().flatten().chain(&mut self.metadata.default_page);
+        for page in moved {
+            *page = match *page {
+                p if p == from => to,
+                p if from < p && p <= to => p - 1,
+                p if to <= p && p < from => p + 1,
+                p => p,
+            };
+        }
+        Ok(())
+    }
+
+    /// Get the title of every page, in page order
+    pub fn page_titles(&self) -> Vec<Option<String>> {
+        self.pages.iter().map(|p| p.title.clone()).collect()
+    }
+
     /// Get thumbnail path for a specific page
     /// Follows the pattern: {main_dir}/thumbnail/{filename}
     pub fn get_thumbnail_path(&self, main_path: &str) -> PathBuf {
@@ -66,17 +346,293 @@ impl Scene {
     }
 }
 
+/// Check whether a path has the extension of an image format FastViewer reads
+pub(crate) fn is_image_file(path: &Path) -> bool {
+    let heif = path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "heic" | "heif"));
+    heif || image::ImageFormat::from_path(path).is_ok_and(|format| format.reading_enabled())
+}
+
+/// Compare file names in natural order: runs of digits compare by their
+/// numeric value, the rest case-insensitively
+///
+/// Names that only differ in zero-padding (`1.jpg` and `001.jpg`) put the
+/// shorter one first, so the order is total and deterministic.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (a_runs, b_runs) = (digit_runs(a), digit_runs(b));
+    for (x, y) in a_runs.iter().zip(&b_runs) {
+        let is_number = |run: &str| run.starts_with(|c: char| c.is_ascii_digit());
+        let order = if is_number(x) && is_number(y) {
+            let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+            x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+        } else {
+            x.to_lowercase().cmp(&y.to_lowercase())
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    a_runs.len().cmp(&b_runs.len())
+        .then_with(|| a.len().cmp(&b.len()))
+        .then_with(|| a.cmp(b))
+}
+
+/// Split a string into alternating runs of ASCII digits and other characters
+fn digit_runs(s: &str) -> Vec<&str> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    let mut chars = s.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        if let Some(&(next, n)) = chars.peek() {
+            if n.is_ascii_digit() != c.is_ascii_digit() {
+                runs.push(&s[start..next]);
+                start = next;
+            }
+        }
+    }
+    if start < s.len() {
+        runs.push(&s[start..]);
+    }
+    runs
+}
+
+/// Compare paths by natural_cmp of their file names, then by the whole path
+fn natural_path_cmp(a: &Path, b: &Path) -> Ordering {
+    let file_name = |path: &Path| path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    natural_cmp(&file_name(a), &file_name(b)).then_with(|| a.cmp(b))
+}
+
+/// Natural order of whole paths, comparing them component by component
+/// (`a/scenes-2` before `a/scenes-10` before `b/scenes-1`)
+fn natural_full_path_cmp(a: &Path, b: &Path) -> Ordering {
+    let names = |path: &Path| -> Vec<String> {
+        path.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect()
+    };
+    let (a_names, b_names) = (names(a), names(b));
+    a_names.iter().zip(&b_names)
+        .map(|(x, y)| natural_cmp(x, y))
+        .find(|order| order.is_ne())
+        .unwrap_or_else(|| a_names.len().cmp(&b_names.len()))
+}
+
+/// How deep find_scene_collections_recursive looks by default
+pub const DEFAULT_COLLECTION_SEARCH_DEPTH: usize = 4;
+
+/// How pages are paired into spreads when a scene has no authored spreads
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpreadMode {
+    /// The cover (page 0) alone, then `[1, 2]`, `[3, 4]`, ...
+    #[default]
+    CoverFirst,
+    /// Pairs from the first page: `[0, 1]`, `[2, 3]`, ...
+    Paired,
+}
+
+/// Computed spread pairing for `mode`, with a trailing odd page alone
+pub fn computed_spreads(page_count: usize, mode: SpreadMode) -> Vec<Vec<usize>> {
+    let pairs_from = match mode {
+        SpreadMode::CoverFirst => page_count.min(1),
+        SpreadMode::Paired => 0,
+    };
+    (0..pairs_from).map(|page| vec![page])
+        .chain((pairs_from..page_count).step_by(2).map(|first| (first..(first + 2).min(page_count)).collect()))
+        .collect()
+}
+
+/// File and directory naming patterns used to recognize scene collections
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SceneCollectionConfig {
+    /// Scene files must start with this prefix
+    pub file_prefix: String,
+    /// Scene files must have this extension (without the dot)
+    pub file_ext: String,
+    /// Collection directories must start with this prefix
+    pub dir_prefix: String,
+}
+
+impl Default for SceneCollectionConfig {
+    fn default() -> Self {
+        SceneCollectionConfig {
+            file_prefix: "scene_".to_string(),
+            file_ext: "json".to_string(),
+            dir_prefix: "scenes-".to_string(),
+        }
+    }
+}
+
+impl SceneCollectionConfig {
+    /// Check whether a path looks like a scene file ({file_prefix}*.{file_ext})
+    /// or is a CBZ/ZIP archive, which is opened as a scene of its images
+    pub fn is_scene_file(&self, path: &Path) -> bool {
+        if !path.is_file() {
+            return false;
+        }
+        self.matches_file_name(path) || archive::is_archive_path(path)
+    }
+
+    fn matches_file_name(&self, path: &Path) -> bool {
+        let name_matches = path.file_name()
+            .map(|name| name.to_string_lossy().starts_with(&self.file_prefix))
+            .unwrap_or(false);
+        let ext_matches = path.extension()
+            .map(|ext| ext.to_string_lossy().eq_ignore_ascii_case(&self.file_ext))
+            .unwrap_or(false);
+        name_matches && ext_matches
+    }
+
+    /// Check whether a path looks like a collection directory ({dir_prefix}*)
+    pub fn is_collection_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+            && path.file_name()
+                .map(|name| name.to_string_lossy().starts_with(&self.dir_prefix))
+                .unwrap_or(false)
+    }
+}
+
+/// Name and page count of one scene of a collection
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SceneSummary {
+    pub name: String,
+    pub page_count: usize,
+    /// Why the scene couldn't be read; `name` is then the file name
+    /// and `page_count` 0
+    pub error: Option<String>,
+}
+
+impl SceneSummary {
+    fn read(path: &Path) -> Self {
+        match Scene::open(path) {
+            Ok(scene) => SceneSummary {
+                page_count: scene.page_count(),
+                name: scene.metadata.scene_name,
+                error: None,
+            },
+            Err(e) => {
+                warn!("Failed to read scene summary of {:?}: {:#}", path, e);
+                SceneSummary {
+                    name: path.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+                    page_count: 0,
+                    error: Some(format!("{:#}", e)),
+                }
+            }
+        }
+    }
+}
+
 /// Represents a collection of scenes in a directory
 #[derive(Debug, Clone)]
 pub struct SceneCollection {
     pub base_path: PathBuf,
     pub scene_files: Vec<PathBuf>,
+    /// Read on the first call to scene_summaries; shared between clones
+    summaries: Arc<OnceLock<Vec<SceneSummary>>>,
 }
 
 impl SceneCollection {
     /// Create a new SceneCollection from a base directory
-    pub fn new<P: AsRef<Path>>(base_path: P) -> Result<Self> {
+    pub fn new<P: AsRef<Path>>(base_path: P, config: &SceneCollectionConfig) -> Result<Self> {
         let base_path = base_path.as_ref().to_path_buf();
+        let scene_files = Self::scan_scene_files(&base_path, config, usize::MAX, |_| true)?;
+
+        Ok(Self::from_scene_files(base_path, scene_files))
+    }
+
+    /// Create a collection from an already discovered list of scene files
+    pub fn from_scene_files(base_path: PathBuf, scene_files: Vec<PathBuf>) -> Self {
+        let mut collection = SceneCollection {
+            base_path,
+            scene_files: Vec::new(),
+            summaries: Arc::default(),
+        };
+        collection.set_scene_files(scene_files);
+        collection
+    }
+
+    /// Replace the scene file list, keeping it in natural order
+    /// (`scene_2.json` before `scene_10.json`)
+    pub fn set_scene_files(&mut self, mut scene_files: Vec<PathBuf>) {
+        scene_files.sort_by(|a, b| natural_path_cmp(a, b));
+        self.scene_files = scene_files;
+        self.invalidate_summaries();
+    }
+
+    /// Reorder the scene files so the scene at `new_order[i]` comes at index `i`
+    ///
+    /// `new_order` must be a permutation of the scene indices.
+    pub fn reorder(&mut self, new_order: &[usize]) -> Result<()> {
+        let count = self.scene_count();
+        if new_order.len() != count {
+            anyhow::bail!("Scene order has {} entries, expected {}", new_order.len(), count);
+        }
+        let mut seen = vec![false; count];
+        for &index in new_order {
+            match seen.get_mut(index) {
+                Some(seen @ false) => *seen = true,
+                Some(true) => anyhow::bail!("Scene index {} appears more than once", index),
+                None => anyhow::bail!("Scene index out of bounds: {}", index),
+            }
+        }
+
+        self.scene_files = new_order.iter().map(|&i| self.scene_files[i].clone()).collect();
+        self.invalidate_summaries();
+        Ok(())
+    }
+
+    /// Name and page count of every scene, by scene index
+    ///
+    /// Every scene file is read on the first call and the result kept until
+    /// the scene list changes or invalidate_summaries is called. A scene that
+    /// can't be read gets a placeholder summary instead of failing the rest.
+    pub fn scene_summaries(&self) -> &[SceneSummary] {
+        self.summaries.get_or_init(|| {
+            self.scene_files.iter().map(|path| SceneSummary::read(path)).collect()
+        })
+    }
+
+    /// Forget the summaries so the next scene_summaries call reads the scene
+    /// files again, e.g. after one was edited
+    pub fn invalidate_summaries(&mut self) {
+        self.summaries = Arc::default();
+    }
+
+    /// Find the first scene file in natural order without keeping the rest,
+    /// so a huge collection can start displaying before the scan finishes
+    pub fn find_first_scene_file<P: AsRef<Path>>(base_path: P, config: &SceneCollectionConfig) -> Result<Option<PathBuf>> {
+        let base_path = base_path.as_ref();
+
+        if !base_path.exists() {
+            anyhow::bail!("Scene directory does not exist: {:?}", base_path);
+        }
+
+        let mut first: Option<PathBuf> = None;
+        for entry in std::fs::read_dir(base_path)? {
+            let path = entry?.path();
+            if config.is_scene_file(&path)
+                && first.as_ref().is_none_or(|first| natural_path_cmp(&path, first).is_lt())
+            {
+                first = Some(path);
+            }
+        }
+
+        Ok(first)
+    }
+
+    /// Find all scene files in a directory (unsorted)
+    ///
+    /// `on_progress` is called with the files found so far after every
+    /// `batch_size` matches; returning false stops the scan early.
+    pub fn scan_scene_files<P, F>(
+        base_path: P,
+        config: &SceneCollectionConfig,
+        batch_size: usize,
+        mut on_progress: F,
+    ) -> Result<Vec<PathBuf>>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&[PathBuf]) -> bool,
+    {
+        let base_path = base_path.as_ref();
 
         if !base_path.exists() {
             anyhow::bail!("Scene directory does not exist: {:?}", base_path);
@@ -84,28 +640,19 @@ impl SceneCollection {
 
         let mut scene_files = Vec::new();
 
-        // Find all scene_*.json files
-        for entry in std::fs::read_dir(&base_path)? {
-            let entry = entry?;
-            let path = entry.path();
+        for entry in std::fs::read_dir(base_path)? {
+            let path = entry?.path();
 
-            if path.is_file() {
-                if let Some(filename) = path.file_name() {
-                    let filename_str = filename.to_string_lossy();
-                    if filename_str.starts_with("scene_") && filename_str.ends_with(".json") {
-                        scene_files.push(path);
-                    }
+            if config.is_scene_file(&path) {
+                scene_files.push(path);
+
+                if scene_files.len() % batch_size == 0 && !on_progress(&scene_files) {
+                    break;
                 }
             }
         }
 
-        // Sort scene files by name
-        scene_files.sort();
-
-        Ok(SceneCollection {
-            base_path,
-            scene_files,
-        })
+        Ok(scene_files)
     }
 
     /// Get total number of scenes
@@ -118,11 +665,11 @@ impl SceneCollection {
         let scene_path = self.scene_files.get(index)
             .with_context(|| format!("Scene index out of bounds: {}", index))?;
 
-        Scene::load_from_file(scene_path)
+        Scene::open(scene_path)
     }
 
     /// Get all available scene directories in a parent directory
-    pub fn find_scene_collections<P: AsRef<Path>>(parent_dir: P) -> Result<Vec<PathBuf>> {
+    pub fn find_scene_collections<P: AsRef<Path>>(parent_dir: P, config: &SceneCollectionConfig) -> Result<Vec<PathBuf>> {
         let parent_dir = parent_dir.as_ref();
         let mut collections = Vec::new();
 
@@ -130,25 +677,129 @@ impl SceneCollection {
             let entry = entry?;
             let path = entry.path();
 
-            if path.is_dir() {
-                if let Some(dirname) = path.file_name() {
-                    let dirname_str = dirname.to_string_lossy();
-                    if dirname_str.starts_with("scenes-") {
-                        collections.push(path);
-                    }
+            if config.is_collection_dir(&path) {
+                collections.push(path);
+            }
+        }
+
+        collections.sort_by(|a, b| natural_path_cmp(a, b));
+        Ok(collections)
+    }
+
+    /// Get the scene directories in a parent directory and its subdirectories,
+    /// up to `max_depth` levels down (1 finds the same as find_scene_collections)
+    ///
+    /// Collection directories aren't searched further. Subdirectories that
+    /// can't be read are skipped, and each directory is searched once even if
+    /// symlinks lead back to it. Sorted in natural order by full path.
+    pub fn find_scene_collections_recursive<P: AsRef<Path>>(
+        parent_dir: P,
+        config: &SceneCollectionConfig,
+        max_depth: usize,
+    ) -> Result<Vec<PathBuf>> {
+        let parent_dir = parent_dir.as_ref();
+        let mut collections = Vec::new();
+        if max_depth == 0 {
+            return Ok(collections);
+        }
+
+        let mut visited = HashSet::new();
+        visited.insert(parent_dir.canonicalize()?);
+        let mut pending = vec![(std::fs::read_dir(parent_dir)?, 1)];
+
+        while let Some((entries, depth)) = pending.pop() {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let is_collection = config.is_collection_dir(&path);
+                if !is_collection && (depth >= max_depth || !path.is_dir()) {
+                    continue;
+                }
+                // Canonical paths catch symlink cycles and collections reached twice
+                if !path.canonicalize().is_ok_and(|real| visited.insert(real)) {
+                    continue;
+                }
+
+                if is_collection {
+                    collections.push(path);
+                    continue;
+                }
+                match std::fs::read_dir(&path) {
+                    Ok(entries) => pending.push((entries, depth + 1)),
+                    Err(e) => warn!("Skipping unreadable directory {:?}: {}", path, e),
                 }
             }
         }
 
-        collections.sort();
+        collections.sort_by(|a, b| natural_full_path_cmp(a, b));
         Ok(collections)
     }
+
+    /// Count the scene directories in a parent directory without collecting them
+    pub fn count_scene_collections<P: AsRef<Path>>(parent_dir: P, config: &SceneCollectionConfig) -> Result<usize> {
+        let mut count = 0;
+        for entry in std::fs::read_dir(parent_dir.as_ref())? {
+            if config.is_collection_dir(&entry?.path()) {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+}
+/// Replace a scene file, writing to a temporary file first so a failed write
+/// leaves the old one intact
+fn write_scene_file(path: &Path, content: &str) -> Result<()> {
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, content)
+        .with_context(|| format!("Failed to write {:?}", tmp_path))?;
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to replace scene file: {:?}", path))?;
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_empty_collection_dir() {
+        let dir = std::env::temp_dir().join(format!("fastviewer-empty-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a scene").unwrap();
+
+        let config = SceneCollectionConfig::default();
+        let collection = SceneCollection::new(&dir, &config);
+        let first = SceneCollection::find_first_scene_file(&dir, &config);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(collection.unwrap().scene_count(), 0);
+        assert_eq!(first.unwrap(), None);
+    }
+
+    #[test]
+    fn test_scene_summaries() {
+        let dir = std::env::temp_dir().join(format!("fastviewer-summaries-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("scene_1.json"),
+            r#"{ "metadata": { "sceneName": "Intro" }, "pages": [{ "image": "a.jpg" }, { "image": "b.jpg" }] }"#,
+        ).unwrap();
+        std::fs::write(dir.join("scene_2.json"), "not json").unwrap();
+        std::fs::write(
+            dir.join("scene_10.json"),
+            r#"{ "metadata": { "sceneName": "Finale" }, "pages": [{ "image": "c.jpg" }] }"#,
+        ).unwrap();
+
+        let collection = SceneCollection::new(&dir, &SceneCollectionConfig::default()).unwrap();
+        let summaries = collection.scene_summaries().to_vec();
+        // Read once: later calls don't see changes on disk
+        std::fs::remove_file(dir.join("scene_1.json")).unwrap();
+        assert_eq!(collection.clone().scene_summaries(), summaries.as_slice());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<_> = summaries.iter().map(|s| (s.name.as_str(), s.page_count, s.error.is_some())).collect();
+        assert_eq!(names, [("Intro", 2, false), ("scene_2", 0, true), ("Finale", 1, false)]);
+    }
+
     #[test]
     fn test_thumbnail_path() {
         let scene = Scene {
@@ -157,8 +808,10 @@ mod tests {
                 scene_name: "Test".to_string(),
                 image_size: ImageSize { width: 1920, height: 1080 },
                 thumbnail_size: ImageSize { width: 320, height: 180 },
+                default_page: None,
             },
             pages: vec![],
+            spreads: None,
         };
 
         let main_path = "/path/to/images/image.jpg";
@@ -169,4 +822,286 @@ mod tests {
             "/path/to/images/thumbnail/image.jpg"
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_page_title_is_optional() {
+        let json = r#"{
+            "metadata": {
+                "version": "1.0",
+                "sceneName": "Test",
+                "imageSize": { "width": 1920, "height": 1080 },
+                "thumbnailSize": { "width": 320, "height": 180 }
+            },
+            "pages": [
+                { "image": "a.jpg", "title": "Figure 3", "caption": "Overview" },
+                { "image": "b.jpg" }
+            ]
+        }"#;
+        let scene: Scene = serde_json::from_str(json).unwrap();
+
+        assert_eq!(scene.page_titles(), vec![Some("Figure 3".to_string()), None]);
+        assert_eq!(scene.get_page(0).unwrap().caption.as_deref(), Some("Overview"));
+        assert!(scene.get_page(1).unwrap().caption.is_none());
+
+        // Saving doesn't add null titles and captions to pages without them
+        let saved = serde_json::to_value(&scene).unwrap();
+        assert_eq!(saved["pages"][1], serde_json::json!({ "image": "b.jpg" }));
+    }
+
+    #[test]
+    fn test_scene_header_skips_pages() {
+        let json = r#"{
+            "metadata": {
+                "version": "1.0",
+                "sceneName": "Header only",
+                "imageSize": { "width": 1920, "height": 1080 },
+                "thumbnailSize": { "width": 320, "height": 180 }
+            },
+            "pages": [{ "image": "a.jpg" }, { "unexpected": true }]
+        }"#;
+        let header: SceneHeader = serde_json::from_str(json).unwrap();
+        assert_eq!(header.metadata.scene_name, "Header only");
+    }
+
+    #[test]
+    fn test_minimal_scene() {
+        let json = r#"{
+            "metadata": { "sceneName": "Minimal" },
+            "pages": [{ "image": "a.jpg" }]
+        }"#;
+        let scene: Scene = serde_json::from_str(json).unwrap();
+        scene.validate().unwrap();
+        assert_eq!(scene.metadata.scene_name, "Minimal");
+        assert_eq!((scene.metadata.image_size.width, scene.metadata.image_size.height), (0, 0));
+        assert_eq!(scene.metadata.thumbnail_size.width, 0);
+        assert!(scene.metadata.version.is_empty());
+
+        // The scene name and page list are still required
+        assert!(serde_json::from_str::<Scene>(r#"{ "metadata": {}, "pages": [] }"#).is_err());
+        assert!(serde_json::from_str::<Scene>(r#"{ "metadata": { "sceneName": "x" } }"#).is_err());
+    }
+
+    #[test]
+    fn test_default_page() {
+        let json = |default_page: &str| format!(r#"{{
+            "metadata": {{
+                "version": "1.0",
+                "sceneName": "Test",
+                "imageSize": {{ "width": 1920, "height": 1080 }},
+                "thumbnailSize": {{ "width": 320, "height": 180 }}{}
+            }},
+            "pages": [{{ "image": "cover.jpg" }}, {{ "image": "a.jpg" }}]
+        }}"#, default_page);
+
+        let scene: Scene = serde_json::from_str(&json("")).unwrap();
+        assert_eq!(scene.start_page(), 0);
+
+        let scene: Scene = serde_json::from_str(&json(r#", "defaultPage": 1"#)).unwrap();
+        scene.validate().unwrap();
+        assert_eq!(scene.start_page(), 1);
+
+        let scene: Scene = serde_json::from_str(&json(r#", "defaultPage": 2"#)).unwrap();
+        assert!(scene.validate().is_err());
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        let mut names = vec!["page10.jpg", "page02.jpg", "page1.jpg", "Page3.jpg", "10.jpg", "2.jpg", "1.jpg", "001.jpg", "01.jpg"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, vec!["1.jpg", "01.jpg", "001.jpg", "2.jpg", "10.jpg", "page1.jpg", "page02.jpg", "Page3.jpg", "page10.jpg"]);
+
+        // Mixed segments compare run by run
+        assert_eq!(natural_cmp("vol2_page10", "vol2_page9"), Ordering::Greater);
+        assert_eq!(natural_cmp("vol10_page1", "vol9_page20"), Ordering::Greater);
+        assert_eq!(natural_cmp("a", "a1"), Ordering::Less);
+        assert_eq!(natural_cmp("page", "page"), Ordering::Equal);
+        // Numbers too long for an integer still compare by value
+        assert_eq!(natural_cmp("99999999999999999999999", "100000000000000000000000"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_scene_files_natural_order() {
+        let files = ["scene_20.json", "scene_10.json", "scene_2.json", "scene_1.json"]
+            .map(|name| PathBuf::from("/scenes-a").join(name));
+        let collection = SceneCollection::from_scene_files(PathBuf::from("/scenes-a"), files.to_vec());
+        let names: Vec<_> = collection.scene_files.iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["scene_1.json", "scene_2.json", "scene_10.json", "scene_20.json"]);
+
+        let dir = std::env::temp_dir().join(format!("fastviewer-first-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["scene_57.json", "scene_10.json", "scene_9.json"] {
+            std::fs::write(dir.join(name), "{}").unwrap();
+        }
+        let first = SceneCollection::find_first_scene_file(&dir, &SceneCollectionConfig::default());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(first.unwrap(), Some(dir.join("scene_9.json")));
+
+        let parent = std::env::temp_dir().join(format!("fastviewer-natural-{}", std::process::id()));
+        for name in ["scenes-10", "scenes-2", "scenes-1", "other"] {
+            std::fs::create_dir_all(parent.join(name)).unwrap();
+        }
+        let collections = SceneCollection::find_scene_collections(&parent, &SceneCollectionConfig::default());
+        std::fs::remove_dir_all(&parent).unwrap();
+        assert_eq!(
+            collections.unwrap(),
+            ["scenes-1", "scenes-2", "scenes-10"].map(|name| parent.join(name)).to_vec()
+        );
+    }
+
+    #[test]
+    fn test_find_scene_collections_recursive() {
+        let parent = std::env::temp_dir().join(format!("fastviewer-recursive-{}", std::process::id()));
+        for dir in [
+            "scenes-top",
+            "author/scenes-10",
+            "author/scenes-2",
+            "author/scenes-2/scenes-inner",
+            "author/deep/deeper/scenes-far",
+        ] {
+            std::fs::create_dir_all(parent.join(dir)).unwrap();
+        }
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&parent, parent.join("author/loop")).unwrap();
+
+        let config = SceneCollectionConfig::default();
+        let find = |max_depth| SceneCollection::find_scene_collections_recursive(&parent, &config, max_depth).unwrap();
+        let relative = |paths: Vec<PathBuf>| -> Vec<String> {
+            paths.iter().map(|path| path.strip_prefix(&parent).unwrap().to_string_lossy().replace('\\', "/")).collect()
+        };
+        let (depth_1, depth_2, depth_4, depth_0) = (find(1), find(2), find(DEFAULT_COLLECTION_SEARCH_DEPTH), find(0));
+        std::fs::remove_dir_all(&parent).unwrap();
+
+        assert_eq!(relative(depth_1), ["scenes-top"]);
+        assert_eq!(relative(depth_2), ["author/scenes-2", "author/scenes-10", "scenes-top"]);
+        assert_eq!(
+            relative(depth_4),
+            ["author/deep/deeper/scenes-far", "author/scenes-2", "author/scenes-10", "scenes-top"]
+        );
+        assert!(depth_0.is_empty());
+    }
+
+    #[test]
+    fn test_reorder() {
+        let files = vec![PathBuf::from("a"), PathBuf::from("b"), PathBuf::from("c")];
+        let mut collection = SceneCollection::from_scene_files(PathBuf::new(), files);
+
+        collection.reorder(&[2, 0, 1]).unwrap();
+        assert_eq!(collection.scene_files, vec![PathBuf::from("c"), PathBuf::from("a"), PathBuf::from("b")]);
+
+        assert!(collection.reorder(&[0, 1]).is_err());
+        assert!(collection.reorder(&[0, 1, 1]).is_err());
+        assert!(collection.reorder(&[0, 1, 3]).is_err());
+        assert_eq!(collection.scene_files[0], PathBuf::from("c"));
+    }
+
+    #[test]
+    fn test_remove_and_move_page() {
+        let page = |image: &str| Page { image: image.to_string(), id: None, title: None, caption: None };
+        let mut scene = Scene {
+            metadata: SceneMetadata {
+                version: "1.0".to_string(),
+                scene_name: "Test".to_string(),
+                image_size: ImageSize { width: 1920, height: 1080 },
+                thumbnail_size: ImageSize { width: 320, height: 180 },
+                default_page: Some(3),
+            },
+            pages: vec![page("a"), page("b"), page("c"), page("d")],
+            spreads: Some(vec![vec![0], vec![1, 2], vec![3]]),
+        };
+        let images = |scene: &Scene| scene.pages.iter().map(|p| p.image.clone()).collect::<Vec<_>>();
+
+        scene.move_page(3, 1).unwrap();
+        assert_eq!(images(&scene), ["a", "d", "b", "c"]);
+        assert_eq!(scene.spreads, Some(vec![vec![0], vec![2, 3], vec![1]]));
+        assert_eq!(scene.start_page(), 1);
+
+        scene.move_page(0, 2).unwrap();
+        assert_eq!(images(&scene), ["d", "b", "a", "c"]);
+        assert_eq!(scene.spreads, Some(vec![vec![2], vec![1, 3], vec![0]]));
+
+        assert_eq!(scene.remove_page(0).unwrap().image, "d");
+        assert_eq!(images(&scene), ["b", "a", "c"]);
+        assert_eq!(scene.spreads, Some(vec![vec![1], vec![0, 2]]));
+        assert_eq!(scene.start_page(), 0);
+        scene.validate().unwrap();
+
+        assert!(scene.move_page(0, 3).is_err());
+        assert!(scene.remove_page(3).is_err());
+        scene.remove_page(0).unwrap();
+        scene.remove_page(0).unwrap();
+        assert!(scene.remove_page(0).is_err());
+    }
+
+    #[test]
+    fn test_spreads() {
+        assert_eq!(computed_spreads(0, SpreadMode::CoverFirst), Vec::<Vec<usize>>::new());
+        assert_eq!(computed_spreads(4, SpreadMode::CoverFirst), vec![vec![0], vec![1, 2], vec![3]]);
+        assert_eq!(computed_spreads(5, SpreadMode::CoverFirst), vec![vec![0], vec![1, 2], vec![3, 4]]);
+        assert_eq!(computed_spreads(0, SpreadMode::Paired), Vec::<Vec<usize>>::new());
+        assert_eq!(computed_spreads(1, SpreadMode::Paired), vec![vec![0]]);
+        assert_eq!(computed_spreads(4, SpreadMode::Paired), vec![vec![0, 1], vec![2, 3]]);
+        assert_eq!(computed_spreads(5, SpreadMode::Paired), vec![vec![0, 1], vec![2, 3], vec![4]]);
+
+        let mut scene: Scene = serde_json::from_str(r#"{
+            "metadata": {
+                "version": "1.0",
+                "sceneName": "Test",
+                "imageSize": { "width": 1920, "height": 1080 },
+                "thumbnailSize": { "width": 320, "height": 180 }
+            },
+            "pages": [{ "image": "a.jpg" }, { "image": "b.jpg" }, { "image": "c.jpg" }],
+            "spreads": [[0, 1], [2]]
+        }"#).unwrap();
+        assert!(scene.validate().is_ok());
+        assert_eq!(scene.spreads(SpreadMode::CoverFirst), vec![vec![0, 1], vec![2]]);
+
+        scene.spreads = Some(vec![vec![1, 3]]);
+        assert!(scene.validate().is_err());
+        scene.spreads = Some(vec![vec![]]);
+        assert!(scene.validate().is_err());
+    }
+
+    #[test]
+    fn test_scene_version_check() {
+        for version in ["1.0", "1.3", "1", "1.0.2", ""] {
+            assert!(check_version(version).is_ok(), "{:?} should be supported", version);
+        }
+        assert_eq!(
+            check_version("2.0").unwrap_err().to_string(),
+            r#"Unsupported scene version "2.0" (supported: 1.0)"#
+        );
+        for version in ["one", "1.x", "1.0.0.0", ".1", "v1.0"] {
+            assert!(
+                check_version(version).unwrap_err().to_string().starts_with("Malformed scene version"),
+                "{:?} should be malformed",
+                version
+            );
+        }
+
+        let mut scene: Scene = serde_json::from_str(r#"{
+            "metadata": { "version": "2.0", "sceneName": "Future" },
+            "pages": [{ "image": "a.jpg" }]
+        }"#).unwrap();
+        assert!(scene.validate().is_err());
+        scene.metadata.version = "1.1".to_string();
+        assert!(scene.validate().is_ok());
+    }
+
+    #[test]
+    fn test_scene_file_patterns() {
+        let default = SceneCollectionConfig::default();
+        assert!(default.matches_file_name(Path::new("/c/scene_001.json")));
+        assert!(!default.matches_file_name(Path::new("/c/page-set-01.json")));
+        assert!(!default.matches_file_name(Path::new("/c/scene_001.txt")));
+
+        let custom = SceneCollectionConfig {
+            file_prefix: "page-set-".to_string(),
+            file_ext: "json".to_string(),
+            dir_prefix: "book_".to_string(),
+        };
+        assert!(custom.matches_file_name(Path::new("/c/page-set-01.json")));
+        assert!(!custom.matches_file_name(Path::new("/c/scene_001.json")));
+    }
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::Url;
+
+/// URL scheme of share links
+pub const SHARE_LINK_SCHEME: &str = "fastviewer";
+
+/// A position in a collection, as encoded in a
+/// `fastviewer://open?collection=...&scene=3&page=12` link
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareLink {
+    pub collection: PathBuf,
+    pub scene_index: usize,
+    pub page_index: usize,
+}
+
+impl ShareLink {
+    /// Encode as a share link URL; the collection path is percent-encoded
+    pub fn to_url(&self) -> String {
+        let mut url = Url::parse(&format!("{}://open", SHARE_LINK_SCHEME)).expect("share link base URL is valid");
+        url.query_pairs_mut()
+            .append_pair("collection", &self.collection.to_string_lossy())
+            .append_pair("scene", &self.scene_index.to_string())
+            .append_pair("page", &self.page_index.to_string());
+        url.to_string()
+    }
+
+    /// Parse a share link URL; scene and page default to 0 when missing
+    pub fn parse(link: &str) -> Result<Self, String> {
+        let url = Url::parse(link).map_err(|e| format!("Invalid share link {:?}: {}", link, e))?;
+        if url.scheme() != SHARE_LINK_SCHEME || url.host_str() != Some("open") {
+            return Err(format!("Not a {}://open link: {}", SHARE_LINK_SCHEME, link));
+        }
+
+        let mut collection = None;
+        let mut scene_index = 0;
+        let mut page_index = 0;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "collection" => collection = Some(PathBuf::from(value.as_ref())),
+                "scene" => scene_index = value.parse().map_err(|_| format!("Invalid scene index {:?}", value))?,
+                "page" => page_index = value.parse().map_err(|_| format!("Invalid page index {:?}", value))?,
+                _ => {}
+            }
+        }
+
+        let collection = collection.ok_or_else(|| format!("Share link has no collection: {}", link))?;
+        Ok(ShareLink { collection, scene_index, page_index })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_link_round_trip() {
+        let link = ShareLink {
+            collection: PathBuf::from("/books/My Comics & more/vol #1"),
+            scene_index: 3,
+            page_index: 12,
+        };
+        let url = link.to_url();
+        assert!(url.starts_with("fastviewer://open?collection="));
+        assert!(url.ends_with("&scene=3&page=12"));
+        assert_eq!(ShareLink::parse(&url).unwrap(), link);
+    }
+
+    #[test]
+    fn test_share_link_parse_errors() {
+        assert!(ShareLink::parse("https://open?collection=/a").is_err());
+        assert!(ShareLink::parse("fastviewer://close?collection=/a").is_err());
+        assert!(ShareLink::parse("fastviewer://open?scene=1").is_err());
+        assert!(ShareLink::parse("fastviewer://open?collection=/a&page=x").is_err());
+
+        let link = ShareLink::parse("fastviewer://open?collection=%2Fa").unwrap();
+        assert_eq!((link.scene_index, link.page_index), (0, 0));
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+/// Get the path of a file in the app data directory
+fn data_file_path(app: &AppHandle, file_name: &str) -> Result<PathBuf> {
+    let dir = app.path().app_data_dir()
+        .context("Failed to resolve app data directory")?;
+    Ok(dir.join(file_name))
+}
+
+/// Get the path of a directory in the app cache directory
+pub fn cache_dir(app: &AppHandle, name: &str) -> Result<PathBuf> {
+    let dir = app.path().app_cache_dir()
+        .context("Failed to resolve app cache directory")?;
+    Ok(dir.join(name))
+}
+
+/// Read a JSON file from the app data directory, or None if it doesn't exist yet
+pub fn load_json<T: DeserializeOwned>(app: &AppHandle, file_name: &str) -> Result<Option<T>> {
+    let path = data_file_path(app, file_name)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {:?}", path))?;
+    let value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {:?}", path))?;
+    Ok(Some(value))
+}
+
+/// Write a value as JSON to the app data directory, replacing the file atomically
+pub fn save_json<T: Serialize>(app: &AppHandle, file_name: &str, value: &T) -> Result<()> {
+    let path = data_file_path(app, file_name)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {:?}", dir))?;
+    }
+
+    let content = serde_json::to_string_pretty(value)?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content)
+        .with_context(|| format!("Failed to write {:?}", tmp_path))?;
+    fs::rename(&tmp_path, &path)
+        .with_context(|| format!("Failed to replace {:?}", path))?;
+    Ok(())
+}
@@ -1,6 +1,6 @@
 import { useState, useEffect, useCallback, useRef } from "react";
 import { join, appDataDir } from "@tauri-apps/api/path";
-import { ImageData, SceneInfo } from "../types";
+import { AppError, ImageData, SceneInfo } from "../types";
 import * as imageService from "../services/imageService";
 
 export interface UseImageContentReturn {
@@ -54,7 +54,7 @@ export function useImageContent(): UseImageContentReturn {
       const data = await imageService.getImage(null, 0);
       setImageData(data);
     } catch (err) {
-      setError(`Failed to load scene: ${err}`);
+      setError(`Failed to load scene: ${(err as AppError).message ?? err}`);
       console.error(err);
     } finally {
       setLoading(false);
@@ -5,8 +5,11 @@ import { ImageData, SceneInfo } from "../types";
  * Service layer for Tauri API calls related to image viewing
  */
 
-export async function loadSceneCollection(path: string): Promise<void> {
-  await invoke("load_scene_collection", { path });
+export async function loadSceneCollection(
+  path: string,
+  password?: string
+): Promise<void> {
+  await invoke("load_scene_collection", { path, password });
 }
 
 export async function getSceneInfo(): Promise<SceneInfo> {
@@ -3,17 +3,251 @@ export interface SceneInfo {
     scene_index: number;
     total_pages: number;
     current_page: number;
+    read_count: number;
   }
   
+  export interface Position {
+    scene_index: number;
+    page_index: number;
+  }
+
   export interface ImageData {
     main_image: string | null;
     thumbnail_image: string | null;
     page_index: number;
     scene_index: number;
     image_path: string;
+    width: number;
+    height: number;
+    warning: string | null;
+    page_title: string | null;
+    page_caption: string | null;
+    tier: ImageTier;
+    has_alpha: boolean;
+    clamped_to_max_side: boolean;
+  }
+
+  export type ImageTier = "Instant" | "Preview" | "High";
+
+  export interface ImageUpgradeEvent {
+    scene_index: number;
+    page_index: number;
+    image_path: string;
+    main_image: string;
+    width: number;
+    height: number;
+    tier: ImageTier;
   }
   
+  export interface SpriteSheet {
+    image: string;
+    cols: number;
+    rows: number;
+    cell_size: number;
+    count: number;
+  }
+
+  export interface CollectionProbe {
+    exists: boolean;
+    is_dir: boolean;
+    scene_file_count: number;
+    first_scene_name: string | null;
+  }
+
   export interface SceneListItem {
     name: string;
     path: string;
-  }
\ No newline at end of file
+  }
+
+  export interface RecentItem {
+    path: string;
+    name: string;
+    last_opened: number;
+    exists: boolean;
+  }
+
+  export type ChromaSubsampling = "Yuv444" | "Yuv422" | "Yuv420";
+
+  export interface JpegOptions {
+    subsampling: ChromaSubsampling;
+    progressive: boolean;
+  }
+
+  export interface SceneCollectionConfig {
+    file_prefix: string;
+    file_ext: string;
+    dir_prefix: string;
+  }
+
+  export interface CoverWarmProgress {
+    done: number;
+    total: number;
+  }
+
+  export interface SpreadImage {
+    image: string;
+    pages: number[];
+    spread_count: number;
+    width: number;
+    height: number;
+  }
+
+  export interface ExportProgress {
+    done: number;
+    total: number;
+  }
+
+  export interface SceneLoadFailed {
+    index: number;
+    reason: string;
+  }
+
+  export type PreloadStrategy = "Eager" | "OnIdle" | "Off";
+
+  export type EncodeFormat = "Jpeg" | "WebP";
+
+  export interface TierFormats {
+    preview: EncodeFormat;
+    high: EncodeFormat;
+    thumbnail: EncodeFormat;
+  }
+
+  export interface DisplayResolution {
+    width: number;
+    height: number;
+  }
+
+  export interface PageFileInfo {
+    path: string;
+    size_bytes: number;
+    modified_unix: number | null;
+    format: string | null;
+  }
+
+  export interface CacheMemory {
+    decoded_bytes: number;
+    encoded_bytes: number;
+  }
+
+  export type AtCollectionEnd = "Stop" | "Wrap" | "NextCollection";
+
+  export interface CollectionEndReached {
+    scene_index: number;
+    page_index: number;
+  }
+
+  export interface TocEntry {
+    page_index: number;
+    title: string | null;
+    thumbnail: string | null;
+  }
+
+  export type Orientation = "Portrait" | "Landscape" | "Mixed";
+
+  export interface LayoutHint {
+    orientation: Orientation;
+    avg_aspect: number;
+  }
+
+  export interface BenchmarkResult {
+    preview_ms: number;
+    highres_ms: number;
+    preview_bytes: number;
+    highres_bytes: number;
+  }
+
+  export type SpreadMode = "CoverFirst" | "Paired";
+
+  export type ReadingDirection = "LeftToRight" | "RightToLeft";
+
+  export type NavigationDirection = "Forward" | "Backward";
+
+  export interface RgbaImage {
+    width: number;
+    height: number;
+    data: number[];
+  }
+
+  export interface PagePeek {
+    scene_index: number;
+    page_index: number;
+    image_path: string;
+  }
+
+  export interface ImageSize {
+    width: number;
+    height: number;
+  }
+
+  export interface DimensionSummary {
+    min: ImageSize;
+    max: ImageSize;
+    avg: ImageSize;
+    consistent: boolean;
+  }
+
+  export interface DecodeVerifyProgress {
+    done: number;
+    total: number;
+  }
+
+  export interface DecodeFailure {
+    scene_index: number;
+    page_index: number;
+    image_path: string;
+    error: string;
+  }
+
+  export interface PreloadCounts {
+    ahead: number;
+    behind: number;
+  }
+
+  export interface CacheStats {
+    image_cache_size: number;
+    image_cache_capacity: number;
+    image_cache_hits: number;
+    image_cache_misses: number;
+    encoded_cache_size: number;
+    encoded_cache_capacity: number;
+    encoded_cache_hits: number;
+    encoded_cache_misses: number;
+  }
+
+  export interface ClearedCaches {
+    entries: number;
+    bytes: number;
+  }
+
+  export interface SceneSummary {
+    name: string;
+    page_count: number;
+    error: string | null;
+  }
+
+  export interface SpreadPages {
+    scene_index: number;
+    left_page: number | null;
+    right_page: number | null;
+    left_image: string | null;
+    right_image: string | null;
+  }
+
+  export type AppErrorKind =
+    | "NoSceneLoaded"
+    | "NoCollectionLoaded"
+    | "PageOutOfBounds"
+    | "SceneLoadFailed"
+    | "ImageDecodeFailed"
+    | "WrongPassword"
+    | "Other";
+
+  /** Error rejected by every command; index/total only for PageOutOfBounds */
+  export interface AppError {
+    kind: AppErrorKind;
+    message: string;
+    index?: number;
+    total?: number;
+  }
+
+  export type ThumbnailFallback = "None" | "GenerateFromMain" | "SceneCover" | { Placeholder: string };